@@ -1,8 +1,10 @@
-use crate::font::FontCache;
+use crate::font::{FontCache, FontCacheHandle, GlyphImage, GlyphPosition};
 use crate::*;
 use controller::*;
 use scene::*;
+use std::cell::RefMut;
 use std::ops::{Deref, DerefMut};
+use std::sync::atomic::{AtomicU32, Ordering};
 use tiny_skia::*;
 use widgets::text::Label;
 
@@ -94,7 +96,80 @@ pub enum Backend<'b> {
 
 pub struct SyncContext<'c, M> {
     controller: &'c mut dyn Controller<M>,
-    pub(crate) font_cache: &'c mut FontCache,
+    font_cache: FontCacheRef<'c>,
+    // If the backend can show popups
+    popups: bool,
+    // The owner of the popup shown and where the backend placed it in the window
+    shown: Option<(PopupId, Region)>,
+    // The popups shown or hidden by their owner
+    requests: Vec<(PopupId, Option<Popup>)>,
+    // If a frame callback was requested
+    frame: bool,
+}
+
+// A shared FontCache is only borrowed while it's used
+// so labels can measure themselves during a sync.
+enum FontCacheRef<'c> {
+    // The global FontCache was in use when the context was created
+    Borrowed(&'c mut FontCache),
+    Shared(FontCacheHandle),
+    // Swapped with the global FontCache until the context is dropped
+    Lent(&'c mut FontCache, FontCacheHandle),
+}
+
+pub(crate) enum FontCacheGuard<'a> {
+    Borrowed(&'a mut FontCache),
+    Shared(RefMut<'a, FontCache>),
+}
+
+impl<'a> Deref for FontCacheGuard<'a> {
+    type Target = FontCache;
+    fn deref(&self) -> &Self::Target {
+        match self {
+            Self::Borrowed(font_cache) => font_cache,
+            Self::Shared(font_cache) => font_cache,
+        }
+    }
+}
+
+impl<'a> DerefMut for FontCacheGuard<'a> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        match self {
+            Self::Borrowed(font_cache) => font_cache,
+            Self::Shared(font_cache) => font_cache,
+        }
+    }
+}
+
+// Identifies the widget owning a popup.
+// A widget can only hide its own popup.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct PopupId(u32);
+
+impl PopupId {
+    pub fn new() -> Self {
+        static NEXT: AtomicU32 = AtomicU32::new(0);
+        PopupId(NEXT.fetch_add(1, Ordering::Relaxed))
+    }
+}
+
+impl Default for PopupId {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// A node shown in a surface of its own which can extend past the window.
+// Backends without popups leave it to the widget to draw it in the window.
+#[derive(Debug, PartialEq)]
+pub struct Popup {
+    // The region of the window the popup is attached to
+    pub anchor: Region,
+    // Where the popup is expected in the window.
+    // Pointer events over the popup are sent as if it was there.
+    pub region: Region,
+    // Drawn from the origin of the popup
+    pub node: RenderNode,
 }
 
 pub struct DrawContext<'c> {
@@ -138,10 +213,91 @@ impl<'c> DerefMut for Backend<'c> {
 }
 
 impl<'c, M> SyncContext<'c, M> {
+    // The FontCache takes the place of the global one until the context is dropped
+    // so labels measuring themselves with font::global() use the same cache.
     pub fn new(controller: &'c mut impl Controller<M>, font_cache: &'c mut FontCache) -> Self {
+        let global = font::global();
+        let lent = global
+            .try_borrow_mut()
+            .map(|mut shared| std::mem::swap(&mut *shared, font_cache))
+            .is_some();
         Self {
             controller,
-            font_cache,
+            font_cache: match lent {
+                true => FontCacheRef::Lent(font_cache, global),
+                false => FontCacheRef::Borrowed(font_cache),
+            },
+            popups: false,
+            shown: None,
+            requests: Vec::new(),
+            frame: false,
+        }
+    }
+    pub fn shared(controller: &'c mut impl Controller<M>, font_cache: FontCacheHandle) -> Self {
+        Self {
+            controller,
+            font_cache: FontCacheRef::Shared(font_cache),
+            popups: false,
+            shown: None,
+            requests: Vec::new(),
+            frame: false,
+        }
+    }
+    pub(crate) fn font_cache(&mut self) -> FontCacheGuard<'_> {
+        match &mut self.font_cache {
+            FontCacheRef::Borrowed(font_cache) => FontCacheGuard::Borrowed(font_cache),
+            FontCacheRef::Shared(font_cache) | FontCacheRef::Lent(_, font_cache) => {
+                FontCacheGuard::Shared(font_cache.borrow_mut())
+            }
+        }
+    }
+    pub(crate) fn with_popups(mut self, shown: Option<(PopupId, Region)>) -> Self {
+        self.popups = true;
+        self.shown = shown;
+        self
+    }
+    pub fn has_popups(&self) -> bool {
+        self.popups
+    }
+    // Shows the popup of the owner in place of the one shown.
+    // The owner of the replaced popup receives Event::PopupDone.
+    pub fn show_popup(&mut self, owner: PopupId, popup: Popup) {
+        if self.popups {
+            self.requests.push((owner, Some(popup)));
+        }
+    }
+    // Hides the popup if it's the one of the owner
+    pub fn hide_popup(&mut self, owner: PopupId) {
+        if self.popups {
+            self.requests.push((owner, None));
+        }
+    }
+    // Where the popup of the owner is in the window.
+    // It may not be where it was asked if it didn't fit on the output.
+    pub fn popup_region(&self, owner: PopupId) -> Option<Region> {
+        self.shown
+            .filter(|(shown, _)| *shown == owner)
+            .map(|(_, region)| region)
+    }
+    pub(crate) fn take_popups(&mut self) -> Vec<(PopupId, Option<Popup>)> {
+        std::mem::take(&mut self.requests)
+    }
+    // Asks for an Event::Callback on the next frame without damaging anything
+    pub fn request_frame(&mut self) {
+        self.frame = true;
+    }
+    pub(crate) fn take_frame(&mut self) -> bool {
+        std::mem::take(&mut self.frame)
+    }
+}
+
+impl<'c, M> Drop for SyncContext<'c, M> {
+    fn drop(&mut self) {
+        // Gives the global FontCache its own cache back
+        if let FontCacheRef::Lent(font_cache, global) = &mut self.font_cache {
+            if let Some(mut shared) = global.try_borrow_mut() {
+                std::mem::swap(&mut *shared, font_cache);
+            }
         }
     }
 }
@@ -298,46 +454,266 @@ impl<'c> DrawContext<'c> {
         self.pending_damage.clear();
     }
     pub fn draw_label(&mut self, label: &Label, x: f32, y: f32) {
-        let mut layout;
-        for gp in {
-            if let Some(layout) = label.get_layout() {
-                layout.as_ref()
-            } else {
-                layout = self.font_cache.layout(label);
-                layout.glyphs()
+        self.draw_label_with_clip(label, x, y, None);
+    }
+    pub fn draw_label_with_clip(
+        &mut self,
+        label: &Label,
+        x: f32,
+        y: f32,
+        clip: Option<&ClipMask>,
+    ) {
+        let layout;
+        let glyphs = if let Some(layout) = label.get_layout() {
+            layout.as_ref()
+        } else {
+            layout = self.font_cache.layout(label);
+            layout.as_slice()
+        };
+        if label.is_decorated() {
+            return self.draw_decorated_label(label, glyphs, x.round(), y.round(), clip);
+        }
+        if let Backend::Pixmap(dt) = &mut self.backend {
+            for gp in glyphs {
+                draw_glyph(
+                    self.font_cache,
+                    dt,
+                    gp,
+                    label.get_color(),
+                    x.round(),
+                    y.round(),
+                    &TEXT,
+                    clip,
+                );
+            }
+        }
+    }
+    // The decorated text is drawn from a layer kept by the label
+    // until its text or style changes.
+    fn draw_decorated_label(
+        &mut self,
+        label: &Label,
+        glyphs: &[GlyphPosition],
+        x: f32,
+        y: f32,
+        clip: Option<&ClipMask>,
+    ) {
+        let font_cache = &mut *self.font_cache;
+        if let Some(layer) = label.decorated_layer(|| decorate(font_cache, label, glyphs)) {
+            if let Backend::Pixmap(dt) = &mut self.backend {
+                dt.draw_pixmap(
+                    x as i32,
+                    y as i32,
+                    layer.as_ref(),
+                    &TEXT,
+                    Transform::identity(),
+                    clip,
+                );
             }
-        } {
-            if let Some(glyph_cache) = self.font_cache.fonts.get_mut(&label.fonts()[gp.font_index])
+        }
+    }
+}
+
+// The text and its decorations are drawn on a layer
+// from which the outline and the shadow are made.
+// They're composited within the margin the label keeps around its glyphs.
+fn decorate(font_cache: &mut FontCache, label: &Label, glyphs: &[GlyphPosition]) -> Option<Pixmap> {
+    let (underline, strikeout) = font_cache.decorations(label);
+    let lines = font_cache.lines(glyphs);
+    let margin = font_cache.margin(label);
+    let (width, height) = font_cache.decorated_size(label, glyphs);
+    let mut layer = Pixmap::new(width.ceil() as u32, height.ceil() as u32)?;
+    for gp in glyphs {
+        draw_glyph(
+            font_cache,
+            &mut layer.as_mut(),
+            gp,
+            label.get_color(),
+            margin,
+            margin,
+            &PIX_PAINT,
+            None,
+        );
+    }
+    let mut paint = Paint::default();
+    paint.set_color(label.get_color());
+    for (baseline, start, end) in lines {
+        for (enabled, metrics) in [
+            (label.is_underlined(), underline),
+            (label.is_strikethrough(), strikeout),
+        ] {
+            if let Some(rect) = Rect::from_xywh(
+                start + margin,
+                (baseline - metrics.position - metrics.thickness / 2. + margin).round(),
+                end - start,
+                metrics.thickness.round().max(1.),
+            )
+            .filter(|_| enabled)
             {
-                if let Some(pixmap) = glyph_cache.render_glyph(gp, label.get_color()) {
-                    if let Some(pixmap) = PixmapRef::from_bytes(
-                        unsafe {
-                            std::slice::from_raw_parts(
-                                pixmap.as_ptr() as *mut u8,
-                                pixmap.len() * std::mem::size_of::<u32>(),
-                            )
-                        },
-                        gp.width as u32,
-                        gp.height as u32,
-                    ) {
-                        match &mut self.backend {
-                            Backend::Pixmap(dt) => {
-                                dt.draw_pixmap(
-                                    (x.round() + gp.x) as i32,
-                                    (y.round() + gp.y) as i32,
-                                    pixmap,
-                                    &TEXT,
-                                    Transform::identity(),
-                                    None,
-                                );
-                            }
-                            _ => (),
-                        }
+                layer.fill_rect(rect, &paint, Transform::identity(), None);
+            }
+        }
+    }
+    let alpha: Vec<u8> = layer.data().chunks(4).map(|pixel| pixel[3]).collect();
+    let (w, h) = (layer.width() as usize, layer.height() as usize);
+    let mut composite = Pixmap::new(layer.width(), layer.height())?;
+    if let Some(shadow) = label.get_shadow() {
+        let mut alpha = alpha.clone();
+        blur(&mut alpha, w, h, shadow.blur);
+        if let Some(pixmap) = tint(&alpha, w, h, shadow.color) {
+            composite.draw_pixmap(
+                shadow.x.round() as i32,
+                shadow.y.round() as i32,
+                pixmap.as_ref(),
+                &PIX_PAINT,
+                Transform::identity(),
+                None,
+            );
+        }
+    }
+    if let Some(outline) = label.get_outline() {
+        let alpha = dilate(&alpha, w, h, outline.width);
+        if let Some(pixmap) = tint(&alpha, w, h, outline.color) {
+            composite.draw_pixmap(
+                0,
+                0,
+                pixmap.as_ref(),
+                &PIX_PAINT,
+                Transform::identity(),
+                None,
+            );
+        }
+    }
+    composite.draw_pixmap(
+        0,
+        0,
+        layer.as_ref(),
+        &PIX_PAINT,
+        Transform::identity(),
+        None,
+    );
+    Some(composite)
+}
+
+#[allow(clippy::too_many_arguments)]
+fn draw_glyph(
+    font_cache: &mut FontCache,
+    dt: &mut PixmapMut,
+    gp: &GlyphPosition,
+    color: Color,
+    x: f32,
+    y: f32,
+    paint: &PixmapPaint,
+    clip: Option<&ClipMask>,
+) {
+    match font_cache.render_glyph(gp, color) {
+        Some(GlyphImage::Mask(pixmap)) => {
+            if let Some(pixmap) = PixmapRef::from_bytes(
+                unsafe {
+                    std::slice::from_raw_parts(
+                        pixmap.as_ptr() as *mut u8,
+                        pixmap.len() * std::mem::size_of::<u32>(),
+                    )
+                },
+                gp.width as u32,
+                gp.height as u32,
+            ) {
+                dt.draw_pixmap(
+                    (x + gp.x) as i32,
+                    (y + gp.y) as i32,
+                    pixmap,
+                    paint,
+                    Transform::identity(),
+                    clip,
+                );
+            }
+        }
+        // Color glyphs only take the opacity of the text
+        Some(GlyphImage::Color(dx, dy, pixmap)) => {
+            dt.draw_pixmap(
+                (x + gp.x + dx).round() as i32,
+                (y + gp.y + dy).round() as i32,
+                pixmap.as_ref(),
+                &PixmapPaint {
+                    opacity: paint.opacity * color.alpha(),
+                    ..*paint
+                },
+                Transform::identity(),
+                clip,
+            );
+        }
+        None => {}
+    }
+}
+
+// Pixmap of the color with the opacity of the alpha mask
+fn tint(alpha: &[u8], width: usize, height: usize, color: Color) -> Option<Pixmap> {
+    let mut pixmap = Pixmap::new(width as u32, height as u32)?;
+    let color = color.premultiply().to_color_u8();
+    let channels = [color.red(), color.green(), color.blue(), color.alpha()];
+    for (pixel, a) in pixmap.data_mut().chunks_mut(4).zip(alpha) {
+        for (channel, value) in pixel.iter_mut().zip(channels) {
+            *channel = (value as u32 * *a as u32 / 255) as u8;
+        }
+    }
+    Some(pixmap)
+}
+
+// Approximates a gaussian blur with three box blurs
+fn blur(alpha: &mut [u8], width: usize, height: usize, radius: f32) {
+    let radius = (radius / 3.).ceil() as usize;
+    if radius == 0 {
+        return;
+    }
+    let mut line = Vec::new();
+    for _ in 0..3 {
+        for (len, stride, count, step) in [(width, 1, height, width), (height, width, width, 1)] {
+            for i in 0..count {
+                let start = i * step;
+                line.clear();
+                line.extend((0..len).map(|j| alpha[start + j * stride] as u32));
+                let mut sum: u32 = line[..radius.min(len)].iter().sum();
+                for j in 0..len {
+                    if j + radius < len {
+                        sum += line[j + radius];
+                    }
+                    if j > radius {
+                        sum -= line[j - radius - 1];
                     }
+                    alpha[start + j * stride] = (sum / (2 * radius as u32 + 1)) as u8;
+                }
+            }
+        }
+    }
+}
+
+// Grows the alpha mask by the radius
+fn dilate(alpha: &[u8], width: usize, height: usize, radius: f32) -> Vec<u8> {
+    let r = radius.ceil() as isize;
+    let mut kernel = Vec::new();
+    for dy in -r..=r {
+        for dx in -r..=r {
+            // Antialiases the edge of the stroke
+            let coverage = (radius + 0.5 - ((dx * dx + dy * dy) as f32).sqrt()).clamp(0., 1.);
+            if coverage > 0. {
+                kernel.push((dx, dy, coverage));
+            }
+        }
+    }
+    let mut dilated = vec![0; alpha.len()];
+    for y in 0..height as isize {
+        for x in 0..width as isize {
+            let mut value: f32 = 0.;
+            for (dx, dy, coverage) in &kernel {
+                let (sx, sy) = (x + dx, y + dy);
+                if sx >= 0 && sy >= 0 && sx < width as isize && sy < height as isize {
+                    value = value.max(alpha[(sy * width as isize + sx) as usize] as f32 * coverage);
                 }
             }
+            dilated[(y * width as isize + x) as usize] = value as u8;
         }
     }
+    dilated
 }
 
 impl<'c> Deref for DrawContext<'c> {
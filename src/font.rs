@@ -1,19 +1,30 @@
 use crate::widgets::text::Label;
-use fontconfig::Fontconfig;
+#[cfg(feature = "fontconfig")]
+use fontconfig::{Fontconfig, Pattern, FC_CHARSET, FC_FAMILY, FC_SLANT, FC_WEIGHT, FC_WIDTH};
+#[cfg(feature = "fontconfig")]
+use fontconfig_sys::fontconfig as sys;
 pub use fontdue::{
     layout,
-    layout::{
-        CoordinateSystem, GlyphPosition, GlyphRasterConfig, Layout, LayoutSettings, TextStyle,
-    },
+    layout::{CoordinateSystem, GlyphRasterConfig, LayoutSettings},
     Font, FontResult, FontSettings,
 };
+use std::cell::{RefCell, RefMut};
 use std::clone::Clone;
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap, HashSet};
+#[cfg(feature = "fontconfig")]
+use std::ffi::CString;
 use std::fs::read;
-use std::path::Path;
+use std::ops::{Add, AddAssign};
+use std::path::{Path, PathBuf};
+use std::rc::Rc;
+use std::time::{Duration, Instant};
 use tiny_skia::*;
+use ttf_parser::Tag;
+
+// Default amount of bytes the rasterized glyphs of a FontCache can take
+pub const CACHE_LIMIT: usize = 4 << 20;
 
-pub fn get_size<U: Copy + Clone>(glyphs: &Vec<GlyphPosition<U>>) -> (f32, f32) {
+pub fn get_size<U: Copy + Clone>(glyphs: &[layout::GlyphPosition<U>]) -> (f32, f32) {
     let mut width = 0;
     let mut height = 0;
     for gp in glyphs {
@@ -23,19 +34,237 @@ pub fn get_size<U: Copy + Clone>(glyphs: &Vec<GlyphPosition<U>>) -> (f32, f32) {
     (width as f32, height as f32)
 }
 
-#[derive(Debug, Clone, Hash, PartialEq, Eq)]
+// What the FontCache knows about the glyphs it lays out
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct GlyphData {
+    // A new line starts after the glyph
+    pub line_break: bool,
+    // The line break was inserted to wrap the text
+    pub wrapped: bool,
+    // Index of the face in the font collection,
+    // the faces of a collection share its file hash
+    pub face: u32,
+}
+
+pub type GlyphPosition = layout::GlyphPosition<GlyphData>;
+pub type Layout = layout::Layout<GlyphData>;
+pub type TextStyle<'a> = layout::TextStyle<'a, GlyphData>;
+
+// Characters after which fontdue starts a new line
+fn is_line_break(c: char) -> bool {
+    matches!(
+        c,
+        '\n' | '\r' | '\u{b}' | '\u{c}' | '\u{85}' | '\u{2028}' | '\u{2029}'
+    )
+}
+
+// The settings of the label with the line breaks the FontCache inserts
+fn settings(label: &Label) -> LayoutSettings {
+    LayoutSettings {
+        wrap_hard_breaks: true,
+        ..*label.get_settings()
+    }
+}
+
+// Indices of the characters the lines of the string are wrapped before.
+// The letter spacing is accounted for and lines are wrapped
+// at least where fontdue would, so it never wraps them itself.
+fn wrap(label: &Label, fonts: &[&Font], string: &str) -> Vec<usize> {
+    let settings = label.get_settings();
+    let max_width = match settings.max_width {
+        Some(max_width) if !fonts.is_empty() => max_width,
+        _ => return Vec::new(),
+    };
+    let letters = settings.wrap_style == layout::WrapStyle::Letter;
+    // Negative spacing doesn't stop fontdue from wrapping
+    let letter_spacing = label.get_letter_spacing().max(0.);
+    let mut wraps = Vec::new();
+    // Positions with the letter spacing and as fontdue sees them
+    let (mut position, mut start) = (0., 0.);
+    let (mut unspaced, mut unspaced_start) = (0., 0.);
+    let mut opportunity = None;
+    let mut previous: Option<char> = None;
+    for (i, c) in string.chars().enumerate() {
+        let advance = if c.is_control() {
+            0.
+        } else if let Some(font) = fonts.iter().find(|font| font.lookup_glyph_index(c) != 0) {
+            font.metrics(c, label.get_font_size()).advance_width.ceil()
+        } else {
+            continue;
+        };
+        let hard_break = previous
+            .filter(|previous| settings.wrap_hard_breaks && is_line_break(*previous))
+            .is_some_and(|previous| !(previous == '\r' && c == '\n'));
+        if hard_break {
+            start = position;
+            unspaced_start = unspaced;
+            opportunity = None;
+        } else {
+            if letters || previous.is_some_and(char::is_whitespace) {
+                opportunity = Some((i, position, unspaced));
+            }
+            // Whitespace can hang past the end of the line unless fontdue wraps it
+            let overflows = if c.is_whitespace() {
+                unspaced - unspaced_start + advance > max_width
+            } else {
+                position - start + advance > max_width
+            };
+            if overflows {
+                let (index, at, unspaced_at) =
+                    opportunity.take().unwrap_or((i, position, unspaced));
+                wraps.push(index);
+                start = at;
+                unspaced_start = unspaced_at;
+                // The glyph may still not fit after the wrapped word
+                if index != i && position - start + advance > max_width {
+                    wraps.push(i);
+                    start = position;
+                    unspaced_start = unspaced;
+                }
+            }
+        }
+        position += advance;
+        unspaced += advance;
+        if !c.is_control() {
+            position += letter_spacing;
+        }
+        previous = Some(c);
+    }
+    wraps
+}
+
+// Size of a text and position of its first baseline
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct TextMetrics {
+    pub width: f32,
+    pub height: f32,
+    pub baseline: f32,
+}
+
+thread_local! {
+    static FONT_CACHE: FontCacheHandle = FontCacheHandle::new(FontCache::new());
+}
+
+// The FontCache shared by the applications and the labels of the thread
+pub fn global() -> FontCacheHandle {
+    FONT_CACHE.with(Clone::clone)
+}
+
+// A FontCache which can be shared
+#[derive(Clone)]
+pub struct FontCacheHandle(Rc<RefCell<FontCache>>);
+
+impl FontCacheHandle {
+    pub fn new(font_cache: FontCache) -> Self {
+        FontCacheHandle(Rc::new(RefCell::new(font_cache)))
+    }
+    // Panics if the cache is in use, like while drawing
+    pub fn borrow_mut(&self) -> RefMut<'_, FontCache> {
+        self.0.borrow_mut()
+    }
+    pub fn try_borrow_mut(&self) -> Option<RefMut<'_, FontCache>> {
+        self.0.try_borrow_mut().ok()
+    }
+    // Returns None if the cache is in use
+    pub fn measure(&self, label: &Label) -> Option<TextMetrics> {
+        Some(self.try_borrow_mut()?.measure(label))
+    }
+    pub fn measure_str(&self, label: &Label, string: &str) -> Option<TextMetrics> {
+        Some(self.try_borrow_mut()?.measure_str(label, string))
+    }
+}
+
+impl From<FontCache> for FontCacheHandle {
+    fn from(font_cache: FontCache) -> Self {
+        FontCacheHandle::new(font_cache)
+    }
+}
+
+// Slant of the font
+#[derive(Debug, Clone, Copy, Hash, PartialEq, Eq)]
 pub enum FontStyle {
     Regular,
     Italic,
+    Oblique,
+    #[deprecated(note = "use FontWeight::BOLD")]
     Bold,
 }
 
-impl FontStyle {
-    fn as_str(&self) -> Option<&str> {
+// Weight of the font from 100 to 900 like OpenType and CSS
+#[derive(Debug, Clone, Copy, Hash, PartialEq, Eq, PartialOrd, Ord)]
+pub struct FontWeight(u16);
+
+impl FontWeight {
+    pub const THIN: Self = FontWeight(100);
+    pub const EXTRA_LIGHT: Self = FontWeight(200);
+    pub const LIGHT: Self = FontWeight(300);
+    pub const REGULAR: Self = FontWeight(400);
+    pub const MEDIUM: Self = FontWeight(500);
+    pub const SEMI_BOLD: Self = FontWeight(600);
+    pub const BOLD: Self = FontWeight(700);
+    pub const EXTRA_BOLD: Self = FontWeight(800);
+    pub const BLACK: Self = FontWeight(900);
+    pub fn new(weight: u16) -> Self {
+        FontWeight(weight.clamp(100, 900))
+    }
+    pub fn get(&self) -> u16 {
+        self.0
+    }
+    // The weight on fontconfig's scale
+    #[cfg(feature = "fontconfig")]
+    fn to_fontconfig(self) -> i32 {
+        const WEIGHTS: [(u16, i32); 9] = [
+            (100, 0),
+            (200, 40),
+            (300, 50),
+            (400, 80),
+            (500, 100),
+            (600, 180),
+            (700, 200),
+            (800, 205),
+            (900, 210),
+        ];
+        let i = (self.0 as usize / 100).clamp(1, 8) - 1;
+        let (low, fc_low) = WEIGHTS[i];
+        let (high, fc_high) = WEIGHTS[i + 1];
+        fc_low + (fc_high - fc_low) * (self.0.clamp(low, high) - low) as i32 / (high - low) as i32
+    }
+}
+
+impl Default for FontWeight {
+    fn default() -> Self {
+        Self::REGULAR
+    }
+}
+
+// Stretch of the font
+#[derive(Debug, Clone, Copy, Hash, PartialEq, Eq, PartialOrd, Ord)]
+pub enum FontWidth {
+    UltraCondensed,
+    ExtraCondensed,
+    Condensed,
+    SemiCondensed,
+    Normal,
+    SemiExpanded,
+    Expanded,
+    ExtraExpanded,
+    UltraExpanded,
+}
+
+impl FontWidth {
+    // The width on fontconfig's scale
+    #[cfg(feature = "fontconfig")]
+    fn to_fontconfig(self) -> i32 {
         match self {
-            Self::Regular => None,
-            Self::Italic => Some("italic"),
-            Self::Bold => Some("bold"),
+            Self::UltraCondensed => 50,
+            Self::ExtraCondensed => 63,
+            Self::Condensed => 75,
+            Self::SemiCondensed => 87,
+            Self::Normal => 100,
+            Self::SemiExpanded => 113,
+            Self::Expanded => 125,
+            Self::ExtraExpanded => 150,
+            Self::UltraExpanded => 200,
         }
     }
 }
@@ -44,14 +273,13 @@ impl FontStyle {
 pub struct FontProperty {
     pub name: String,
     pub style: FontStyle,
+    pub weight: FontWeight,
+    pub width: FontWidth,
 }
 
 impl From<&str> for FontProperty {
     fn from(name: &str) -> Self {
-        FontProperty {
-            name: name.to_string(),
-            style: FontStyle::Regular,
-        }
+        FontProperty::new(name)
     }
 }
 
@@ -60,22 +288,214 @@ impl FontProperty {
         FontProperty {
             name: name.to_string(),
             style: FontStyle::Regular,
+            weight: FontWeight::REGULAR,
+            width: FontWidth::Normal,
+        }
+    }
+    #[allow(deprecated)]
+    pub fn style(mut self, style: FontStyle) -> Self {
+        if style == FontStyle::Bold {
+            return self.style(FontStyle::Regular).bold();
+        }
+        self.style = style;
+        self
+    }
+    pub fn italic(self) -> Self {
+        self.style(FontStyle::Italic)
+    }
+    pub fn weight(mut self, weight: FontWeight) -> Self {
+        self.weight = weight;
+        self
+    }
+    pub fn bold(self) -> Self {
+        self.weight(FontWeight::BOLD)
+    }
+    pub fn width(mut self, width: FontWidth) -> Self {
+        self.width = width;
+        self
+    }
+    // The same style with another family
+    #[cfg(feature = "fontconfig")]
+    fn with_name(&self, name: &str) -> Self {
+        FontProperty {
+            name: name.to_string(),
+            ..self.clone()
+        }
+    }
+    // A fontconfig pattern matching the property
+    #[cfg(feature = "fontconfig")]
+    fn pattern<'fc>(&self, fc: &'fc Fontconfig) -> Option<Pattern<'fc>> {
+        let mut pattern = Pattern::new(fc);
+        if !self.name.is_empty() {
+            pattern.add_string(FC_FAMILY.as_cstr(), &CString::new(self.name.as_str()).ok()?);
+        }
+        let mut weight = self.weight;
+        #[allow(deprecated)]
+        let slant = match self.style {
+            FontStyle::Regular => 0,
+            FontStyle::Italic => 100,
+            FontStyle::Oblique => 110,
+            FontStyle::Bold => {
+                weight = FontWeight::BOLD;
+                0
+            }
+        };
+        unsafe {
+            sys::FcPatternAddInteger(pattern.pat, FC_SLANT.as_ptr(), slant);
+            sys::FcPatternAddInteger(pattern.pat, FC_WEIGHT.as_ptr(), weight.to_fontconfig());
+            sys::FcPatternAddInteger(pattern.pat, FC_WIDTH.as_ptr(), self.width.to_fontconfig());
+        }
+        Some(pattern)
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct CacheStats {
+    pub hits: u64,
+    pub misses: u64,
+    pub evictions: u64,
+}
+
+impl Add for CacheStats {
+    type Output = Self;
+    fn add(self, other: Self) -> Self {
+        CacheStats {
+            hits: self.hits + other.hits,
+            misses: self.misses + other.misses,
+            evictions: self.evictions + other.evictions,
         }
     }
 }
 
+impl AddAssign for CacheStats {
+    fn add_assign(&mut self, other: Self) {
+        *self = *self + other;
+    }
+}
+
 pub struct FontCache {
+    #[cfg(feature = "fontconfig")]
     fc: Option<Fontconfig>,
-    pub fonts: HashMap<FontProperty, GlyphCache>,
+    limit: usize,
+    // The amount of bytes taken by the rasterized glyphs of all the fonts
+    size: usize,
+    // The cached glyphs from the least to the most recently used
+    lru: BTreeMap<u64, (FontProperty, GlyphRasterConfig)>,
+    tick: u64,
+    // Statistics of the fonts which were purged
+    purged: CacheStats,
+    // Fonts found by fontconfig for characters missing from labels' fonts
+    fallbacks: Vec<FontProperty>,
+    // Characters no font on the system covers
+    missing: HashSet<char>,
+    // Fonts loaded from bytes or files which can't be found again once purged
+    registered: HashSet<FontProperty>,
+    fonts: HashMap<FontProperty, GlyphCache>,
 }
 
 impl FontCache {
     pub fn new() -> Self {
         FontCache {
+            #[cfg(feature = "fontconfig")]
             fc: Fontconfig::new(),
+            limit: CACHE_LIMIT,
+            size: 0,
+            lru: BTreeMap::new(),
+            tick: 0,
+            purged: CacheStats::default(),
+            fallbacks: Vec::new(),
+            missing: HashSet::new(),
+            registered: HashSet::new(),
             fonts: HashMap::new(),
         }
     }
+    // The amount of bytes the rasterized glyphs can take
+    pub fn limit(&self) -> usize {
+        self.limit
+    }
+    pub fn set_limit(&mut self, limit: usize) {
+        self.limit = limit;
+        if self.size() > self.limit {
+            self.evict();
+        }
+    }
+    // The amount of bytes taken by the rasterized glyphs
+    pub fn size(&self) -> usize {
+        self.size
+    }
+    pub fn stats(&self) -> CacheStats {
+        self.fonts
+            .values()
+            .fold(self.purged, |stats, glyph_cache| stats + glyph_cache.stats)
+    }
+    // Removes the glyphs and the fonts which weren't used since the given duration
+    pub fn purge(&mut self, age: Duration) {
+        let purged = &mut self.purged;
+        let registered = &self.registered;
+        self.fonts.retain(|font, glyph_cache| {
+            if !registered.contains(font) && glyph_cache.used.elapsed() > age {
+                *purged += glyph_cache.stats;
+                return false;
+            }
+            glyph_cache.purge(age);
+            true
+        });
+        let fonts = &self.fonts;
+        self.fallbacks.retain(|font| fonts.contains_key(font));
+        self.lru.retain(|_, (font, key)| {
+            fonts
+                .get(font)
+                .map(|glyph_cache| glyph_cache.glyphs.contains_key(key))
+                .unwrap_or(false)
+        });
+        self.size = self.fonts.values().map(GlyphCache::size).sum();
+    }
+    // Removes the rasterized glyphs of all the fonts
+    pub fn clear(&mut self) {
+        for glyph_cache in self.fonts.values_mut() {
+            glyph_cache.clear();
+        }
+        self.lru.clear();
+        self.size = 0;
+    }
+    // The glyph is rendered by the font it was laid out with
+    pub fn render_glyph(&mut self, glyph: &GlyphPosition, source: Color) -> Option<GlyphImage> {
+        let (font, glyph_cache) = self
+            .fonts
+            .iter_mut()
+            .find(|(_, glyph_cache)| glyph_cache.is_font_of(glyph))?;
+        let size = glyph_cache.size();
+        let pixmap = glyph_cache.render_glyph(glyph, source);
+        // The glyph moves to the end of the LRU
+        self.tick += 1;
+        if let Some(previous) = glyph_cache.touch(&glyph.key, self.tick) {
+            let entry = previous
+                .and_then(|previous| self.lru.remove(&previous))
+                .unwrap_or_else(|| (font.clone(), glyph.key));
+            self.lru.insert(self.tick, entry);
+        }
+        self.size = self.size + glyph_cache.size() - size;
+        if self.size > self.limit {
+            self.evict();
+        }
+        pixmap
+    }
+    // Removes the least recently used glyphs across all fonts.
+    // The cache is shrunk to three quarters of its limit so
+    // eviction doesn't happen again on the next glyph.
+    fn evict(&mut self) {
+        let target = self.limit / 4 * 3;
+        while self.size > target {
+            let (_, (font, key)) = match self.lru.pop_first() {
+                Some(entry) => entry,
+                None => break,
+            };
+            if let Some(glyph_cache) = self.fonts.get_mut(&font) {
+                self.size -= glyph_cache.remove(&key);
+                glyph_cache.stats.evictions += 1;
+            }
+        }
+    }
     pub fn get_fonts(&self, fonts: &[FontProperty]) -> Vec<&Font> {
         fonts
             .iter()
@@ -88,119 +508,791 @@ impl FontCache {
             .collect()
     }
     pub fn load_font(&mut self, config: &FontProperty) {
-        if self.fonts.get(&config).is_none() {
-            if let Some(fc) = self.fc.as_ref() {
-                if let Some(fc_font) = fc.find(&config.name, config.style.as_str()) {
-                    match GlyphCache::load(fc_font.path.as_path()) {
-                        Ok(glyph_cache) => {
-                            self.fonts.insert(config.clone(), glyph_cache);
-                        }
-                        Err(e) => {
-                            eprintln!("{}: {:?}", e, config);
-                        }
-                    }
+        if let Some(glyph_cache) = self.fonts.get_mut(config) {
+            glyph_cache.used = Instant::now();
+        } else if let Some((path, index)) = self.find_font(config) {
+            match GlyphCache::load_indexed(path.as_path(), index) {
+                Ok(glyph_cache) => {
+                    self.fonts.insert(config.clone(), glyph_cache);
+                }
+                Err(e) => {
+                    eprintln!("{}: {:?}", e, config);
                 }
             }
         }
     }
-    pub fn write(&mut self, layout: &mut Layout, label: &Label, string: &str) {
+    // Registers a font under the given property.
+    // It replaces the font the property would otherwise resolve to.
+    pub fn load_font_bytes<F: Into<FontProperty>>(
+        &mut self,
+        font: F,
+        bytes: &[u8],
+    ) -> FontResult<()> {
+        self.load_font_bytes_indexed(font, bytes, 0)
+    }
+    // Registers the font at the index of a font collection
+    pub fn load_font_bytes_indexed<F: Into<FontProperty>>(
+        &mut self,
+        font: F,
+        bytes: &[u8],
+        index: u32,
+    ) -> FontResult<()> {
+        let glyph_cache = GlyphCache::from_bytes(bytes, index)?;
+        self.register(font.into(), glyph_cache);
+        Ok(())
+    }
+    pub fn load_font_file<F: Into<FontProperty>, P: AsRef<Path>>(
+        &mut self,
+        font: F,
+        path: P,
+    ) -> FontResult<()> {
+        let glyph_cache = GlyphCache::load(path.as_ref())?;
+        self.register(font.into(), glyph_cache);
+        Ok(())
+    }
+    fn register(&mut self, font: FontProperty, glyph_cache: GlyphCache) {
+        if let Some(previous) = self.fonts.insert(font.clone(), glyph_cache) {
+            self.size -= previous.size();
+            self.lru.retain(|_, (lru_font, _)| lru_font != &font);
+        }
+        self.registered.insert(font);
+        // The new font may cover characters which were missing
+        self.missing.clear();
+    }
+    #[cfg(feature = "fontconfig")]
+    fn find_font(&self, config: &FontProperty) -> Option<(PathBuf, u32)> {
+        let mut pattern = config.pattern(self.fc.as_ref()?)?;
+        let font_match = pattern.font_match();
+        let index = font_match.face_index().unwrap_or(0).max(0) as u32;
+        Some((PathBuf::from(font_match.filename()?), index))
+    }
+    #[cfg(not(feature = "fontconfig"))]
+    fn find_font(&self, _config: &FontProperty) -> Option<(PathBuf, u32)> {
+        None
+    }
+    // Finds a font covering the character, preferably in the given style
+    fn fallback(&mut self, c: char, like: &FontProperty) -> Option<FontProperty> {
+        let same_style = |font: &FontProperty| {
+            font.style == like.style && font.weight == like.weight && font.width == like.width
+        };
+        if let Some(font) = self.fallbacks.iter().find(|font| {
+            same_style(font)
+                && self
+                    .fonts
+                    .get(font)
+                    .map(|glyph_cache| glyph_cache.font.lookup_glyph_index(c) != 0)
+                    .unwrap_or(false)
+        }) {
+            return Some(font.clone());
+        }
+        if self.missing.contains(&c) {
+            return None;
+        }
+        match self.find_fallback(c, like) {
+            Some(font) => {
+                if !self.fallbacks.contains(&font) {
+                    self.fallbacks.push(font.clone());
+                }
+                Some(font)
+            }
+            None => {
+                self.missing.insert(c);
+                None
+            }
+        }
+    }
+    // Queries fontconfig for a font whose charset contains the character and loads it
+    #[cfg(feature = "fontconfig")]
+    fn find_fallback(&mut self, c: char, like: &FontProperty) -> Option<FontProperty> {
+        let mut pattern = like.with_name("").pattern(self.fc.as_ref()?)?;
+        unsafe {
+            let charset = sys::FcCharSetCreate();
+            sys::FcCharSetAddChar(charset, c as u32);
+            sys::FcPatternAddCharSet(pattern.pat, FC_CHARSET.as_ptr(), charset);
+            sys::FcCharSetDestroy(charset);
+        }
+        let font_match = pattern.font_match();
+        let covered = unsafe {
+            let mut charset = std::ptr::null_mut();
+            sys::FcPatternGetCharSet(font_match.pat, FC_CHARSET.as_ptr(), 0, &mut charset)
+                == sys::FcResultMatch
+                && sys::FcCharSetHasChar(charset, c as u32) != 0
+        };
+        if !covered {
+            return None;
+        }
+        let font = like.with_name(font_match.get_string(FC_FAMILY.as_cstr())?);
+        if !self.fonts.contains_key(&font) {
+            let index = font_match.face_index().unwrap_or(0).max(0) as u32;
+            match GlyphCache::load_indexed(Path::new(font_match.filename()?), index) {
+                Ok(glyph_cache) => {
+                    self.fonts.insert(font.clone(), glyph_cache);
+                }
+                Err(e) => {
+                    eprintln!("{}: {:?}", e, font);
+                    return None;
+                }
+            }
+        }
+        if self.fonts[&font].font.lookup_glyph_index(c) == 0 {
+            return None;
+        }
+        Some(font)
+    }
+    #[cfg(not(feature = "fontconfig"))]
+    fn find_fallback(&mut self, _c: char, _like: &FontProperty) -> Option<FontProperty> {
+        None
+    }
+    // Loads the fonts of the label and the fallbacks needed to display the string.
+    // The label's fonts come first so they have the priority.
+    fn load_fonts(&mut self, label: &Label, string: &str) -> Vec<FontProperty> {
         for font in label.fonts() {
             self.load_font(font);
         }
-        let fonts = self.get_fonts(label.fonts());
+        let mut fonts: Vec<FontProperty> = label
+            .fonts()
+            .iter()
+            .filter(|font| self.fonts.contains_key(font))
+            .cloned()
+            .collect();
+        let like = label
+            .fonts()
+            .first()
+            .cloned()
+            .unwrap_or_else(|| FontProperty::new(""));
         for c in string.chars() {
-            for (i, font) in fonts.iter().enumerate() {
-                if font.lookup_glyph_index(c) != 0 {
-                    layout.append(
-                        &fonts,
-                        &TextStyle::new(&c.to_string(), label.get_font_size(), i),
-                    );
-                    break;
-                }
+            if c.is_control()
+                || fonts
+                    .iter()
+                    .any(|font| self.fonts[font].font.lookup_glyph_index(c) != 0)
+            {
+                continue;
+            }
+            if let Some(font) = self.fallback(c, &like) {
+                fonts.push(font);
+            }
+        }
+        fonts
+    }
+    // Position of the caret before each character of the string and after the last one
+    pub fn advances(&mut self, label: &Label, string: &str) -> Vec<f32> {
+        let fonts = self.load_fonts(label, string);
+        let fonts = self.get_fonts(&fonts);
+        let mut position = 0.;
+        let mut advances = Vec::with_capacity(string.len() + 1);
+        advances.push(position);
+        for c in string.chars() {
+            if let Some(font) = fonts.iter().find(|font| font.lookup_glyph_index(c) != 0) {
+                position += font.metrics(c, label.get_font_size()).advance_width.ceil()
+                    + label.get_letter_spacing();
             }
+            advances.push(position);
+        }
+        advances
+    }
+    pub fn measure(&mut self, label: &Label) -> TextMetrics {
+        self.measure_str(label, label.get_text())
+    }
+    // Measures the string as if it was the text of the label
+    pub fn measure_str(&mut self, label: &Label, string: &str) -> TextMetrics {
+        let mut layout = Layout::new(CoordinateSystem::PositiveYDown);
+        layout.reset(&settings(label));
+        let glyphs = self.write(&mut layout, label, string);
+        let (width, height) = self.decorated_size(label, &glyphs);
+        let baseline = self
+            .get_fonts(label.fonts())
+            .iter()
+            .filter_map(|font| font.horizontal_line_metrics(label.get_font_size()))
+            .map(|metrics| metrics.ascent.ceil())
+            .fold(0., f32::max);
+        TextMetrics {
+            width,
+            height,
+            baseline: label.get_settings().y + self.margin(label) + baseline,
         }
     }
-    pub fn layout(&mut self, label: &Label) -> Layout {
+    // The room the outline, shadow and underline of the label take around its glyphs
+    pub fn margin(&mut self, label: &Label) -> f32 {
+        if !label.is_decorated() {
+            return 0.;
+        }
+        let (underline, _) = self.decorations(label);
+        (label
+            .get_outline()
+            .map(|outline| outline.width)
+            .unwrap_or(0.)
+            + label
+                .get_shadow()
+                .map(|shadow| shadow.blur + shadow.x.abs().max(shadow.y.abs()))
+                .unwrap_or(0.)
+            + underline.thickness)
+            .ceil()
+            .max(0.)
+    }
+    // Size of the glyphs of the label with its decorations
+    pub fn decorated_size(&mut self, label: &Label, glyphs: &[GlyphPosition]) -> (f32, f32) {
+        let (width, mut height) = get_size(glyphs);
+        if !label.is_decorated() {
+            return (width, height);
+        }
+        if label.is_underlined() {
+            let (underline, _) = self.decorations(label);
+            height = self
+                .lines(glyphs)
+                .iter()
+                .map(|(baseline, _, _)| baseline - underline.position + underline.thickness)
+                .fold(height, f32::max);
+        }
+        let margin = self.margin(label);
+        (width + 2. * margin, height + 2. * margin)
+    }
+    // Height of a line of text using the label's fonts and line height
+    pub fn line_height(&mut self, label: &Label) -> f32 {
         for font in label.fonts() {
             self.load_font(font);
         }
-        let fonts = self.get_fonts(label.fonts());
+        let height = self
+            .get_fonts(label.fonts())
+            .iter()
+            .filter_map(|font| font.horizontal_line_metrics(label.get_font_size()))
+            .map(|metrics| metrics.new_line_size.ceil())
+            .fold(None, |height: Option<f32>, line| {
+                Some(height.map_or(line, |height| height.max(line)))
+            })
+            .unwrap_or(label.get_font_size().ceil());
+        (height * label.get_line_height()).round()
+    }
+    // Appends the string to the layout and returns the glyphs with the label's spacing.
+    // The lines are wrapped before the glyphs are appended.
+    pub fn write(
+        &mut self,
+        layout: &mut Layout,
+        label: &Label,
+        string: &str,
+    ) -> Vec<GlyphPosition> {
+        let fonts = self.load_fonts(label, string);
+        let faces: Vec<u32> = fonts.iter().map(|font| self.fonts[font].face).collect();
+        let fonts = self.get_fonts(&fonts);
+        let font_size = label.get_font_size();
+        let mut wraps = wrap(label, &fonts, string).into_iter().peekable();
+        let mut chars = string.chars().enumerate().peekable();
+        while let Some((i, c)) = chars.next() {
+            if wraps.next_if_eq(&i).is_some() {
+                let data = GlyphData {
+                    line_break: true,
+                    wrapped: true,
+                    face: faces[0],
+                };
+                layout.append(&fonts, &TextStyle::with_user_data("\n", font_size, 0, data));
+            }
+            if is_line_break(c) && !label.get_settings().wrap_hard_breaks {
+                continue;
+            }
+            let index = fonts
+                .iter()
+                .position(|font| font.lookup_glyph_index(c) != 0)
+                // Control characters are kept for line breaks
+                .or(if c.is_control() && !fonts.is_empty() {
+                    Some(0)
+                } else {
+                    None
+                });
+            if let Some(index) = index {
+                let data = GlyphData {
+                    line_break: is_line_break(c)
+                        && !(c == '\r' && matches!(chars.peek(), Some((_, '\n')))),
+                    wrapped: false,
+                    face: faces[index],
+                };
+                layout.append(
+                    &fonts,
+                    &TextStyle::with_user_data(&c.to_string(), font_size, index, data),
+                );
+            }
+        }
+        self.space(label, layout.glyphs())
+    }
+    pub fn layout(&mut self, label: &Label) -> Vec<GlyphPosition> {
         let mut layout = Layout::new(CoordinateSystem::PositiveYDown);
-        layout.reset(label.get_settings());
-        for c in label.get_text().chars() {
-            for (i, font) in fonts.iter().enumerate() {
-                if font.lookup_glyph_index(c) != 0 {
-                    layout.append(
-                        &fonts,
-                        &TextStyle::new(&c.to_string(), label.get_font_size(), i),
-                    );
-                    break;
+        layout.reset(&settings(label));
+        self.write(&mut layout, label, label.get_text())
+    }
+    // Glyphs of a line share the baseline fontdue placed them on
+    fn baseline(&self, glyph: &GlyphPosition) -> f32 {
+        match self
+            .fonts
+            .values()
+            .find(|glyph_cache| glyph_cache.is_font_of(glyph))
+        {
+            Some(glyph_cache) if !glyph.char_data.is_control() => {
+                let bounds = glyph_cache
+                    .font
+                    .metrics_indexed(glyph.key.glyph_index, glyph.key.px)
+                    .bounds;
+                glyph.y - (-bounds.height - bounds.ymin).floor()
+            }
+            _ => glyph.y,
+        }
+    }
+    // The baseline and horizontal extent of each line of glyphs
+    pub fn lines(&self, glyphs: &[GlyphPosition]) -> Vec<(f32, f32, f32)> {
+        let mut lines: Vec<Option<(f32, f32, f32)>> = vec![None];
+        for glyph in glyphs {
+            if !glyph.char_data.is_control() {
+                let start = glyph.x;
+                let end = glyph.x + glyph.width as f32;
+                match lines.last_mut() {
+                    Some(Some(line)) => {
+                        line.1 = line.1.min(start);
+                        line.2 = line.2.max(end);
+                    }
+                    Some(line) => *line = Some((self.baseline(glyph), start, end)),
+                    None => {}
                 }
             }
+            if glyph.user_data.line_break {
+                lines.push(None);
+            }
+        }
+        lines.into_iter().flatten().collect()
+    }
+    // The underline and strikeout of the label in pixels
+    pub fn decorations(&mut self, label: &Label) -> (DecorationMetrics, DecorationMetrics) {
+        for font in label.fonts() {
+            self.load_font(font);
+        }
+        let size = label.get_font_size();
+        let thickness = (size / 14.).max(1.);
+        let mut underline = DecorationMetrics {
+            position: -size / 10.,
+            thickness,
+        };
+        let mut strikeout = DecorationMetrics {
+            position: size * 0.3,
+            thickness,
+        };
+        if let Some(glyph_cache) = label.fonts().iter().find_map(|font| self.fonts.get(font)) {
+            if let Some(metrics) = glyph_cache.underline {
+                underline = metrics.scale(size);
+            }
+            if let Some(metrics) = glyph_cache.strikeout {
+                strikeout = metrics.scale(size);
+            }
+        }
+        (underline, strikeout)
+    }
+    // Applies the line height, letter spacing and paragraph spacing of the label
+    fn space(&self, label: &Label, glyphs: &[GlyphPosition]) -> Vec<GlyphPosition> {
+        let line_height = label.get_line_height();
+        let letter_spacing = label.get_letter_spacing();
+        let paragraph_spacing = label.get_paragraph_spacing();
+        if line_height == 1. && letter_spacing == 0. && paragraph_spacing == 0. {
+            return glyphs.to_vec();
+        }
+        let settings = label.get_settings();
+        let align = match settings.max_width.map(|_| settings.horizontal_align) {
+            Some(layout::HorizontalAlign::Center) => 0.5,
+            Some(layout::HorizontalAlign::Right) => 1.,
+            _ => 0.,
+        };
+        // How much the letter spacing widens each line
+        let mut widening = vec![0.];
+        let mut spaced = Vec::with_capacity(glyphs.len());
+        let (mut dx, mut dy) = (0., 0.);
+        for (i, glyph) in glyphs.iter().enumerate() {
+            if let Some(previous) = i
+                .checked_sub(1)
+                .map(|i| &glyphs[i])
+                .filter(|previous| previous.user_data.line_break)
+            {
+                dx = 0.;
+                dy += (line_height - 1.) * (self.baseline(glyph) - self.baseline(previous));
+                if !previous.user_data.wrapped {
+                    dy += paragraph_spacing;
+                }
+                widening.push(0.);
+            }
+            let mut glyph = *glyph;
+            glyph.x += dx;
+            glyph.y += dy.round();
+            if !glyph.char_data.is_control() {
+                if let Some(widening) = widening.last_mut() {
+                    *widening = dx;
+                }
+                dx += letter_spacing;
+            }
+            spaced.push((widening.len() - 1, glyph));
+        }
+        spaced
+            .into_iter()
+            .map(|(line, mut glyph)| {
+                glyph.x -= (widening[line] * align).floor();
+                glyph
+            })
+            .collect()
+    }
+}
+
+// Position above the baseline and thickness of a line decorating text
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct DecorationMetrics {
+    pub position: f32,
+    pub thickness: f32,
+}
+
+impl DecorationMetrics {
+    fn scale(self, size: f32) -> Self {
+        DecorationMetrics {
+            position: self.position * size,
+            thickness: (self.thickness * size).max(1.),
+        }
+    }
+}
+
+// A rasterized glyph
+#[derive(Debug, Clone)]
+pub enum GlyphImage {
+    // Pixels of the glyph in the color of the text
+    Mask(Vec<u32>),
+    // Full color pixmap offset from the position of the glyph
+    Color(f32, f32, Pixmap),
+}
+
+#[derive(Debug, Clone)]
+enum Bitmap {
+    Coverage(Vec<u8>),
+    // Glyphs with layers in the color of the text
+    // are rendered again when the color changes.
+    Color {
+        x: f32,
+        y: f32,
+        pixmap: Pixmap,
+        foreground: Option<Color>,
+    },
+}
+
+#[derive(Debug, Clone)]
+struct Glyph {
+    bitmap: Bitmap,
+    used: Instant,
+    // Position in the LRU of the FontCache
+    tick: Option<u64>,
+}
+
+impl Glyph {
+    fn size(&self) -> usize {
+        match &self.bitmap {
+            Bitmap::Coverage(coverage) => coverage.len(),
+            Bitmap::Color { pixmap, .. } => pixmap.data().len(),
         }
-        layout
     }
 }
 
 #[derive(Debug, Clone)]
 pub struct GlyphCache {
     pub font: Font,
-    glyphs: HashMap<GlyphRasterConfig, Vec<u8>>,
+    // Index of the font in its collection
+    face: u32,
+    glyphs: HashMap<GlyphRasterConfig, Glyph>,
+    size: usize,
+    used: Instant,
+    stats: CacheStats,
+    // Decorations in em read from the font's tables
+    underline: Option<DecorationMetrics>,
+    strikeout: Option<DecorationMetrics>,
+    // The font data is kept for fonts with bitmap or layered glyphs
+    colors: Option<(Rc<[u8]>, u32)>,
 }
 
 impl GlyphCache {
     pub fn new(font: Font) -> Self {
         Self {
             font,
+            face: 0,
             glyphs: HashMap::new(),
+            size: 0,
+            used: Instant::now(),
+            stats: CacheStats::default(),
+            underline: None,
+            strikeout: None,
+            colors: None,
         }
     }
     pub fn load(path: &Path) -> FontResult<Self> {
+        Self::load_indexed(path, 0)
+    }
+    // Loads the font at the index of a font collection
+    pub fn load_indexed(path: &Path, index: u32) -> FontResult<Self> {
         match read(path) {
-            Ok(bytes) => match Font::from_bytes(bytes, fontdue::FontSettings::default()) {
-                Ok(font) => Ok(Self {
-                    font,
-                    glyphs: HashMap::new(),
-                }),
-                Err(_) => FontResult::Err("Isn't a font"),
-            },
+            Ok(bytes) => Self::from_bytes(&bytes, index),
             Err(_) => FontResult::Err("Invalid path"),
         }
     }
-    pub fn render_glyph(&mut self, glyph: &GlyphPosition, source: Color) -> Option<Vec<u32>> {
+    // Loads the font at the index of a font collection from its data
+    pub fn from_bytes(bytes: &[u8], index: u32) -> FontResult<Self> {
+        let settings = FontSettings {
+            collection_index: index,
+            ..Default::default()
+        };
+        match Font::from_bytes(bytes, settings) {
+            Ok(font) => {
+                let mut glyph_cache = Self::new(font);
+                glyph_cache.face = index;
+                if let Ok(face) = ttf_parser::Face::from_slice(bytes, index) {
+                    let em = face.units_per_em().unwrap_or(1000) as f32;
+                    let metrics = |metrics: ttf_parser::LineMetrics| DecorationMetrics {
+                        position: metrics.position as f32 / em,
+                        thickness: metrics.thickness as f32 / em,
+                    };
+                    glyph_cache.underline = face.underline_metrics().map(metrics);
+                    glyph_cache.strikeout = face.strikeout_metrics().map(metrics);
+                    if [b"sbix", b"CBDT", b"COLR"]
+                        .iter()
+                        .any(|tag| face.table_data(Tag::from_bytes(tag)).is_some())
+                    {
+                        glyph_cache.colors = Some((bytes.into(), index));
+                    }
+                }
+                Ok(glyph_cache)
+            }
+            Err(_) => FontResult::Err("Isn't a font"),
+        }
+    }
+    fn is_font_of(&self, glyph: &GlyphPosition) -> bool {
+        self.font.file_hash() == glyph.key.font_hash && self.face == glyph.user_data.face
+    }
+    // The amount of bytes taken by the rasterized glyphs
+    pub fn size(&self) -> usize {
+        self.size
+    }
+    pub fn stats(&self) -> CacheStats {
+        self.stats
+    }
+    // Removes the glyphs which weren't used since the given duration
+    pub fn purge(&mut self, age: Duration) {
+        let size = &mut self.size;
+        self.glyphs.retain(|_, glyph| {
+            if glyph.used.elapsed() > age {
+                *size -= glyph.size();
+                return false;
+            }
+            true
+        });
+    }
+    fn clear(&mut self) {
+        self.glyphs.clear();
+        self.size = 0;
+    }
+    // Sets the position of a cached glyph in the LRU and returns the previous one
+    fn touch(&mut self, key: &GlyphRasterConfig, tick: u64) -> Option<Option<u64>> {
+        let glyph = self.glyphs.get_mut(key)?;
+        Some(glyph.tick.replace(tick))
+    }
+    fn remove(&mut self, key: &GlyphRasterConfig) -> usize {
+        let size = self
+            .glyphs
+            .remove(key)
+            .map(|glyph| glyph.size())
+            .unwrap_or(0);
+        self.size -= size;
+        size
+    }
+    pub fn render_glyph(&mut self, glyph: &GlyphPosition, source: Color) -> Option<GlyphImage> {
         if !glyph.char_data.is_missing() {
-            let pixmap: Vec<u32>;
-            if let Some(coverage) = self.glyphs.get(&glyph.key) {
-                pixmap = coverage
-                    .iter()
-                    .map(|a| {
-                        if a == &0 {
-                            0
-                        } else {
-                            let mut color = source;
-                            color.apply_opacity(*a as f32 / 255.);
-                            color.premultiply().to_color_u8().get()
-                        }
-                    })
-                    .collect();
+            let now = Instant::now();
+            self.used = now;
+            let cached = match self.glyphs.get(&glyph.key).map(|cached| &cached.bitmap) {
+                Some(Bitmap::Color {
+                    foreground: Some(foreground),
+                    ..
+                }) => foreground == &source,
+                Some(_) => true,
+                None => false,
+            };
+            if cached {
+                self.stats.hits += 1;
             } else {
-                let (_, coverage) = self.font.rasterize_config(glyph.key);
-                pixmap = coverage
-                    .iter()
-                    .map(|a| {
-                        if a == &0 {
-                            0
-                        } else {
-                            let mut color = source;
-                            color.apply_opacity(*a as f32 / 255.);
-                            color.premultiply().to_color_u8().get()
-                        }
-                    })
-                    .collect();
-                self.glyphs.insert(glyph.key, coverage);
-            }
-            return Some(pixmap);
+                let bitmap = self.render_color(glyph.key, source).unwrap_or_else(|| {
+                    let (_, coverage) = self.font.rasterize_config(glyph.key);
+                    Bitmap::Coverage(coverage)
+                });
+                // A glyph rendered again keeps its place in the LRU
+                let tick = self.glyphs.get(&glyph.key).and_then(|cached| cached.tick);
+                self.remove(&glyph.key);
+                self.stats.misses += 1;
+                let rendered = Glyph {
+                    bitmap,
+                    used: now,
+                    tick,
+                };
+                self.size += rendered.size();
+                self.glyphs.insert(glyph.key, rendered);
+            }
+            let cached = self.glyphs.get_mut(&glyph.key)?;
+            cached.used = now;
+            return Some(match &cached.bitmap {
+                Bitmap::Coverage(coverage) => GlyphImage::Mask(
+                    coverage
+                        .iter()
+                        .map(|a| {
+                            if a == &0 {
+                                0
+                            } else {
+                                let mut color = source;
+                                color.apply_opacity(*a as f32 / 255.);
+                                color.premultiply().to_color_u8().get()
+                            }
+                        })
+                        .collect(),
+                ),
+                Bitmap::Color { x, y, pixmap, .. } => GlyphImage::Color(*x, *y, pixmap.clone()),
+            });
         }
         None
     }
+    // Renders the bitmap or the color layers of a glyph.
+    // The offset is from the corner of the outline fontdue lays out.
+    fn render_color(&self, key: GlyphRasterConfig, source: Color) -> Option<Bitmap> {
+        let (data, index) = self.colors.as_ref()?;
+        let face = ttf_parser::Face::from_slice(data, *index).ok()?;
+        let metrics = self.font.metrics_indexed(key.glyph_index, key.px);
+        let (x, y, pixmap, foreground) = if let Some(image) =
+            face.glyph_raster_image(ttf_parser::GlyphId(key.glyph_index), key.px.round() as u16)
+        {
+            let (x, y, pixmap) = raster_image(image, key.px)?;
+            (x, y, pixmap, None)
+        } else {
+            let (x, y, pixmap, foreground) = self.render_layers(&face, key, source)?;
+            (x, y, pixmap, Some(source).filter(|_| foreground))
+        };
+        Some(Bitmap::Color {
+            x: x - metrics.xmin as f32,
+            y: (metrics.ymin + metrics.height as i32) as f32 - y,
+            pixmap,
+            foreground,
+        })
+    }
+    // Composites the layers of a glyph from the COLR table
+    // with the colors of the first palette of the CPAL table.
+    // Returns the left side and the top of the glyph from its origin
+    // and whether a layer is in the color of the text.
+    fn render_layers(
+        &self,
+        face: &ttf_parser::Face,
+        key: GlyphRasterConfig,
+        source: Color,
+    ) -> Option<(f32, f32, Pixmap, bool)> {
+        let colr = face.table_data(Tag::from_bytes(b"COLR"))?;
+        let cpal = face.table_data(Tag::from_bytes(b"CPAL"))?;
+        let base_glyphs = read_u32(colr, 4)? as usize;
+        let (first, count) = (0..read_u16(colr, 2)? as usize)
+            .map(|i| base_glyphs + i * 6)
+            .find(|offset| read_u16(colr, *offset) == Some(key.glyph_index))
+            .and_then(|offset| Some((read_u16(colr, offset + 2)?, read_u16(colr, offset + 4)?)))?;
+        let layer_records = read_u32(colr, 8)? as usize;
+        let color_records = read_u32(cpal, 8)? as usize;
+        let palette = read_u16(cpal, 12)? as usize;
+        let mut foreground = false;
+        let mut layers = Vec::new();
+        for i in first..first + count {
+            let offset = layer_records + i as usize * 4;
+            let glyph = read_u16(colr, offset)?;
+            let color = match read_u16(colr, offset + 2)? {
+                0xFFFF => {
+                    foreground = true;
+                    source
+                }
+                entry => {
+                    let offset = color_records + (palette + entry as usize) * 4;
+                    let bgra = cpal.get(offset..offset + 4)?;
+                    Color::from_rgba8(bgra[2], bgra[1], bgra[0], bgra[3])
+                }
+            };
+            let (metrics, coverage) = self.font.rasterize_indexed(glyph, key.px);
+            layers.push((metrics, coverage, color));
+        }
+        let left = layers.iter().map(|(m, _, _)| m.xmin).min()?;
+        let right = layers
+            .iter()
+            .map(|(m, _, _)| m.xmin + m.width as i32)
+            .max()?;
+        let top = layers
+            .iter()
+            .map(|(m, _, _)| m.ymin + m.height as i32)
+            .max()?;
+        let bottom = layers.iter().map(|(m, _, _)| m.ymin).min()?;
+        let mut pixmap = Pixmap::new((right - left) as u32, (top - bottom) as u32)?;
+        for (metrics, coverage, color) in layers {
+            let color = color.premultiply().to_color_u8();
+            let mut layer = match Pixmap::new(metrics.width as u32, metrics.height as u32) {
+                Some(layer) => layer,
+                None => continue,
+            };
+            for (pixel, a) in layer.pixels_mut().iter_mut().zip(coverage) {
+                let channel = |value: u8| (value as u32 * a as u32 / 255) as u8;
+                if let Some(color) = PremultipliedColorU8::from_rgba(
+                    channel(color.red()),
+                    channel(color.green()),
+                    channel(color.blue()),
+                    channel(color.alpha()),
+                ) {
+                    *pixel = color;
+                }
+            }
+            pixmap.draw_pixmap(
+                metrics.xmin - left,
+                top - metrics.ymin - metrics.height as i32,
+                layer.as_ref(),
+                &PixmapPaint::default(),
+                Transform::identity(),
+                None,
+            );
+        }
+        Some((left as f32, top as f32, pixmap, foreground))
+    }
+}
+
+// Decodes the image of a bitmap glyph and scales it to the font size.
+// Returns it with its left side and its top from the origin of the glyph.
+fn raster_image(image: ttf_parser::RasterGlyphImage, px: f32) -> Option<(f32, f32, Pixmap)> {
+    let decoded = image::load_from_memory_with_format(image.data, image::ImageFormat::Png)
+        .ok()?
+        .to_rgba8();
+    let (width, height) = decoded.dimensions();
+    let mut source = Pixmap::new(width, height)?;
+    for (pixel, rgba) in source.pixels_mut().iter_mut().zip(decoded.pixels()) {
+        let [r, g, b, a] = rgba.0;
+        *pixel = ColorU8::from_rgba(r, g, b, a).premultiply();
+    }
+    let scale = px / image.pixels_per_em as f32;
+    let mut pixmap = Pixmap::new(
+        (width as f32 * scale).ceil() as u32,
+        (height as f32 * scale).ceil() as u32,
+    )?;
+    pixmap.draw_pixmap(
+        0,
+        0,
+        source.as_ref(),
+        &PixmapPaint {
+            quality: FilterQuality::Bicubic,
+            ..PixmapPaint::default()
+        },
+        Transform::from_scale(scale, scale),
+        None,
+    );
+    Some((
+        image.x as f32 * scale,
+        (image.y + image.height as i16) as f32 * scale,
+        pixmap,
+    ))
+}
+
+fn read_u16(data: &[u8], offset: usize) -> Option<u16> {
+    let bytes = data.get(offset..offset + 2)?;
+    Some(u16::from_be_bytes([bytes[0], bytes[1]]))
+}
+
+fn read_u32(data: &[u8], offset: usize) -> Option<u32> {
+    let bytes = data.get(offset..offset + 4)?;
+    Some(u32::from_be_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
 }
@@ -7,12 +7,13 @@ pub mod wayland;
 pub mod widgets;
 
 use context::*;
+use controller::TryIntoMessage;
 use scene::RenderNode;
 pub use tiny_skia::*;
 use widgets::button::{Button, Proxy};
 use widgets::container::Child;
 use widgets::shapes::WidgetExt;
-use widgets::{Padding, WidgetBox};
+use widgets::{ContextMenu, Menu, Padding, Tooltip, WidgetBox};
 
 pub mod style {
     use crate::scene::Background;
@@ -32,6 +33,31 @@ pub mod style {
     pub const TRANSPARENT: Background = Background::Transparent;
 }
 
+// Keysyms of the keys handled by snui's widgets
+pub mod keys {
+    pub const BACKSPACE: u32 = 0xff08;
+    pub const TAB: u32 = 0xff09;
+    pub const RETURN: u32 = 0xff0d;
+    pub const ESCAPE: u32 = 0xff1b;
+    pub const HOME: u32 = 0xff50;
+    pub const LEFT: u32 = 0xff51;
+    pub const UP: u32 = 0xff52;
+    pub const RIGHT: u32 = 0xff53;
+    pub const DOWN: u32 = 0xff54;
+    pub const PAGE_UP: u32 = 0xff55;
+    pub const PAGE_DOWN: u32 = 0xff56;
+    pub const END: u32 = 0xff57;
+    pub const KP_ENTER: u32 = 0xff8d;
+    pub const DELETE: u32 = 0xffff;
+    pub const ISO_LEFT_TAB: u32 = 0xfe20;
+    pub const SPACE: u32 = 0x0020;
+    pub const A: u32 = 0x0061;
+    pub const Y: u32 = 0x0079;
+    pub const Z: u32 = 0x007a;
+    // Reported instead of Z while Shift is held
+    pub const Z_UPPER: u32 = 0x005a;
+}
+
 pub fn u32_to_source(color: u32) -> Color {
     let color = color.to_be_bytes();
     Color::from_rgba8(color[3], color[2], color[1], color[0])
@@ -126,6 +152,13 @@ pub struct Key<'k> {
     pub pressed: bool,
 }
 
+impl<'k> Key<'k> {
+    // Tells if the keysym is part of the key event
+    pub fn is(&self, keysym: u32) -> bool {
+        self.value.contains(&keysym)
+    }
+}
+
 #[derive(Copy, Clone, Debug, PartialEq)]
 pub enum Pointer {
     MouseClick {
@@ -192,6 +225,11 @@ pub enum Event<'d, M> {
     // Waiting for Wayland-rs 0.3.0 to implement it
     Keyboard(Key<'d>),
     Pointer(f32, f32, Pointer),
+    // The popup of the owner was closed without being hidden by it
+    PopupDone(PopupId),
+    // Sent by a widget to its children when their nodes have to be created again,
+    // like after they moved, without the full redraw of a Frame
+    Redraw,
 }
 
 impl<'d, M> Clone for Event<'d, M> {
@@ -203,6 +241,8 @@ impl<'d, M> Clone for Event<'d, M> {
             Self::Message(msg) => Self::Message(*msg),
             Self::Keyboard(key) => Self::Keyboard(*key),
             Self::Pointer(x, y, p) => Self::Pointer(*x, *y, *p),
+            Self::PopupDone(owner) => Self::PopupDone(*owner),
+            Self::Redraw => Self::Redraw,
         }
     }
 }
@@ -222,6 +262,10 @@ impl<'d, M> Event<'d, M> {
             _ => false,
         }
     }
+    // The nodes are created again on a Frame too
+    pub fn is_redraw(&self) -> bool {
+        matches!(self, Self::Frame | Self::Redraw)
+    }
 }
 
 pub trait Geometry {
@@ -319,6 +363,10 @@ pub trait WidgetUtil<M>: Widget<M> + Sized {
         self,
         cb: F,
     ) -> Button<M, Self, F>;
+    fn tooltip(self, text: &str) -> Tooltip<M, Self>;
+    fn context_menu(self, menu: Menu<M>) -> ContextMenu<M, Self>
+    where
+        M: TryIntoMessage<bool>;
 }
 
 impl<G> Flex<G> for G
@@ -361,4 +409,13 @@ where
     {
         Button::new(self, cb)
     }
+    fn tooltip(self, text: &str) -> Tooltip<M, Self> {
+        Tooltip::new(self, text)
+    }
+    fn context_menu(self, menu: Menu<M>) -> ContextMenu<M, Self>
+    where
+        M: TryIntoMessage<bool>,
+    {
+        ContextMenu::new(self, menu)
+    }
 }
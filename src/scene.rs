@@ -427,7 +427,7 @@ impl Primitive for PrimitiveType {
         match self {
             Self::Image(image) => image.draw_with_transform_clip(ctx, transform, clip),
             Self::Rectangle(rectangle) => rectangle.draw_with_transform_clip(ctx, transform, clip),
-            Self::Label(l) => ctx.draw_label(l, transform.tx, transform.ty),
+            Self::Label(l) => ctx.draw_label_with_clip(l, transform.tx, transform.ty, clip),
             Self::Other {
                 name: _,
                 id: _,
@@ -543,7 +543,7 @@ impl Instruction {
                 r.draw_with_transform_clip(ctx, self.transform, clip);
             }
             PrimitiveType::Label(l) => {
-                ctx.draw_label(l, x, y);
+                ctx.draw_label_with_clip(l, x, y, clip);
             }
         }
         ctx.commit(self.region());
@@ -612,6 +612,11 @@ pub enum RenderNode {
         region: Region,
         steps: Vec<Instruction>,
     },
+    // The node is only drawn inside of the region
+    Clip {
+        region: Region,
+        node: Box<RenderNode>,
+    },
 }
 
 impl Geometry for RenderNode {
@@ -619,6 +624,7 @@ impl Geometry for RenderNode {
         match self {
             RenderNode::Container { region, nodes: _ } => region.width,
             RenderNode::Draw { region, steps: _ } => region.width,
+            RenderNode::Clip { region, node: _ } => region.width,
             RenderNode::Instruction(instruction) => instruction.width(),
             RenderNode::Extension {
                 background,
@@ -638,6 +644,7 @@ impl Geometry for RenderNode {
         match self {
             RenderNode::Container { region, nodes: _ } => region.height,
             RenderNode::Draw { region, steps: _ } => region.height,
+            RenderNode::Clip { region, node: _ } => region.height,
             RenderNode::Instruction(instruction) => instruction.height(),
             RenderNode::Extension {
                 background,
@@ -721,19 +728,15 @@ impl RenderNode {
                 node.render(ctx, clip);
             }
             Self::Draw { region, steps } => {
-                // ClipMask expects the mask to be the size of the buffer
-                let mut clip = ClipMask::new();
-                clip.set_path(
-                    ctx.width() as u32,
-                    ctx.height() as u32,
-                    &PathBuilder::from_rect(region.into()),
-                    FillRule::EvenOdd,
-                    false,
-                );
+                let clip = clip_region(ctx, region, clip);
                 for n in steps {
                     n.render(ctx, Some(&clip));
                 }
             }
+            Self::Clip { region, node } => {
+                let clip = clip_region(ctx, region, clip);
+                node.render(ctx, Some(&clip));
+            }
             _ => {}
         }
     }
@@ -775,7 +778,7 @@ impl RenderNode {
                 };
                 ctx.damage_region(bg, region, false);
             }
-            RenderNode::Draw { region, steps: _ } => {
+            RenderNode::Draw { region, steps: _ } | RenderNode::Clip { region, node: _ } => {
                 let region = match other {
                     Some(other) => region.merge(other),
                     None => *region,
@@ -839,6 +842,20 @@ impl RenderNode {
                     }
                 }
             }
+            Self::Clip { region, node } => {
+                let this_node = node.as_mut();
+                let this_region = region;
+                match other {
+                    RenderNode::Clip { region, node } => {
+                        *this_region = region;
+                        this_node.merge(*node);
+                    }
+                    RenderNode::None => {}
+                    _ => {
+                        *self = other;
+                    }
+                }
+            }
             _ => match other {
                 Self::None => {}
                 _ => {
@@ -1000,7 +1017,7 @@ impl RenderNode {
                             *self = RenderNode::Draw { region, steps };
                             return Err(region);
                         } else {
-                            if steps.eq(this_steps) {
+                            if steps.ne(this_steps) {
                                 self.clear(ctx, &Background::from(shape), Some(&region));
                                 *self = RenderNode::Draw { region, steps };
                                 self.render(ctx, clip);
@@ -1015,11 +1032,54 @@ impl RenderNode {
                     }
                 }
             }
+            // The content of the region is drawn again entirely
+            RenderNode::Clip { region, node: _ } => {
+                let this_region = *region;
+                match other {
+                    RenderNode::Clip { region, node } => {
+                        self.merge(RenderNode::Clip { region, node });
+                        if !shape.contains(&region) {
+                            return Err(region);
+                        }
+                        self.clear(ctx, &Background::from(shape), Some(&this_region));
+                        self.render(ctx, clip);
+                    }
+                    RenderNode::None => {}
+                    _ => {
+                        self.clear(ctx, &Background::from(shape), Some(&this_region));
+                        self.merge(other);
+                        self.render(ctx, clip);
+                    }
+                }
+            }
         }
         Ok(())
     }
 }
 
+// ClipMask expects the mask to be the size of the buffer
+fn clip_region(ctx: &DrawContext, region: &Region, clip: Option<&ClipMask>) -> ClipMask {
+    let path = PathBuilder::from_rect(region.into());
+    match clip {
+        Some(clip) => {
+            let mut clip = clip.clone();
+            clip.intersect_path(&path, FillRule::EvenOdd, false);
+            clip
+        }
+        None => {
+            let mut clip = ClipMask::new();
+            clip.set_path(
+                ctx.width() as u32,
+                ctx.height() as u32,
+                &path,
+                FillRule::EvenOdd,
+                false,
+            );
+            clip
+        }
+    }
+}
+
 #[derive(Debug, Copy, Clone, PartialEq)]
 pub struct Region {
     pub x: f32,
@@ -20,9 +20,13 @@ pub use smithay_client_toolkit::reexports::protocols::wlr::unstable::layer_shell
     zwlr_layer_surface_v1::Anchor, zwlr_layer_surface_v1::KeyboardInteractivity,
     zwlr_layer_surface_v1::ZwlrLayerSurfaceV1,
 };
+pub use smithay_client_toolkit::reexports::protocols::xdg_shell::client::{
+    xdg_popup::XdgPopup, xdg_surface::XdgSurface, xdg_wm_base::XdgWmBase,
+};
 use smithay_client_toolkit::shm::AutoMemPool;
 
-use crate::context::Backend;
+use crate::context::{Backend, PopupId};
+use crate::scene::{Region, RenderNode};
 use smithay_client_toolkit::shm::Format;
 
 const FORMAT: Format = Format::Argb8888;
@@ -210,6 +214,24 @@ pub struct Surface {
     previous: Option<Box<Self>>,
 }
 
+// A surface shown over a layer surface, like the list of a dropdown
+pub struct PopupSurface {
+    configured: bool,
+    owner: PopupId,
+    // Where the widget expects the popup in the window
+    region: Region,
+    // Where the compositor placed the popup in the window
+    position: (f32, f32),
+    surface: Main<WlSurface>,
+    xdg_surface: Main<XdgSurface>,
+    xdg_popup: Main<XdgPopup>,
+    buffer: Option<WlBuffer>,
+    mempool: AutoMemPool,
+    render_node: Option<RenderNode>,
+    // The node drawn once the popup is configured
+    pending: Option<RenderNode>,
+}
+
 #[derive(Debug, Clone)]
 pub struct Output {
     pub width: i32,
@@ -235,4 +257,5 @@ pub struct Globals {
     pub shm: Option<Main<WlShm>>,
     pub compositor: Option<Main<WlCompositor>>,
     pub shell: Option<Main<ZwlrLayerShellV1>>,
+    pub wm_base: Option<Main<XdgWmBase>>,
 }
@@ -1,6 +1,6 @@
-use crate::context::DrawContext;
+use crate::context::{DrawContext, Popup, PopupId};
 use crate::controller::Controller;
-use crate::font::FontCache;
+use crate::font::{self, FontCacheHandle};
 use crate::scene::*;
 use crate::wayland::*;
 use crate::*;
@@ -30,6 +30,12 @@ use smithay_client_toolkit::reexports::protocols::wlr::unstable::layer_shell::v1
     zwlr_layer_shell_v1::ZwlrLayerShellV1, zwlr_layer_surface_v1,
     zwlr_layer_surface_v1::ZwlrLayerSurfaceV1,
 };
+use smithay_client_toolkit::reexports::protocols::xdg_shell::client::{
+    xdg_popup::{self, XdgPopup},
+    xdg_positioner::{self, ConstraintAdjustment},
+    xdg_surface::{self, XdgSurface},
+    xdg_wm_base::{self, XdgWmBase},
+};
 
 pub struct Application<M, C>
 where
@@ -45,8 +51,15 @@ where
 struct Context {
     pending_cb: bool,
     time: Option<u32>,
+    // Time of the last frame
+    last_frame: u32,
+    // Damage from events received between two frames
+    damaged: bool,
     render_node: Option<RenderNode>,
-    font_cache: FontCache,
+    font_cache: FontCacheHandle,
+    // The seat and serial of the button held.
+    // A popup opened by the press grabs the pointer with it.
+    grab: Option<(WlSeat, u32)>,
 }
 
 pub struct CoreApplication<M, C>
@@ -59,6 +72,7 @@ where
     mempool: AutoMemPool,
     widget: Box<dyn Widget<M>>,
     surface: Option<Surface>,
+    popup: Option<PopupSurface>,
 }
 
 pub struct InnerApplication<M, C>
@@ -125,6 +139,17 @@ impl Surface {
     }
 }
 
+impl PopupSurface {
+    fn destroy(&self) {
+        self.xdg_popup.destroy();
+        self.xdg_surface.destroy();
+        self.surface.destroy();
+        if let Some(buffer) = self.buffer.as_ref() {
+            buffer.destroy();
+        }
+    }
+}
+
 impl Globals {
     fn new() -> Self {
         Self {
@@ -133,6 +158,7 @@ impl Globals {
             shm: None,
             compositor: None,
             shell: None,
+            wm_base: None,
         }
     }
     pub fn create_shell_surface_from<M, C>(
@@ -239,6 +265,22 @@ where
                         }
                     }
                 ],
+                [
+                    XdgWmBase,
+                    1,
+                    |wm_base: Main<XdgWmBase>, mut application: DispatchData| {
+                        wm_base.quick_assign(|wm_base, event, _| {
+                            if let xdg_wm_base::Event::Ping { serial } = event {
+                                wm_base.pong(serial);
+                            }
+                        });
+                        if let Some(application) = application.get::<Application<M, C>>() {
+                            if let Ok(mut globals) = application.globals.try_borrow_mut() {
+                                globals.wm_base = Some(wm_base);
+                            }
+                        }
+                    }
+                ],
                 [
                     WlShm,
                     1,
@@ -270,7 +312,7 @@ where
                                     && capabilities & Capability::Pointer == Capability::Pointer
                                 {
                                     let pointer = wl_seat.get_pointer();
-                                    assign_pointer::<M, C>(&pointer);
+                                    assign_pointer::<M, C>(&pointer, wl_seat.detach());
                                 }
                                 if let Ok(mut globals) = application.globals.try_borrow_mut() {
                                     let mut found = None;
@@ -491,28 +533,211 @@ where
 {
     pub fn poll(&mut self, ev: Event<M>) -> C {
         let mut ctl = self.controller.clone();
-        let mut sync_ctx = SyncContext::new(&mut ctl, &mut self.ctx.font_cache);
-        self.widget.sync(&mut sync_ctx, ev);
+        {
+            let mut sync_ctx = SyncContext::shared(&mut ctl, self.ctx.font_cache.clone());
+            self.widget.sync(&mut sync_ctx, ev);
+        }
         ctl
     }
     pub fn sync(&mut self, ev: Event<M>) -> bool {
-        let mut sync_ctx = SyncContext::new(&mut self.controller, &mut self.ctx.font_cache);
-        let mut damage = self.widget.sync(&mut sync_ctx, ev);
-        while let Ok(msg) = sync_ctx.sync() {
-            damage = damage.max(self.widget.sync(&mut sync_ctx, Event::Message(&msg)));
+        let popups = self.surface.is_some() && self.globals.borrow().wm_base.is_some();
+        let (damage, popups, frame) = {
+            let font_cache = self.ctx.font_cache.clone();
+            let mut sync_ctx = SyncContext::shared(&mut self.controller, font_cache);
+            if popups {
+                let shown = self.popup.as_ref().map(|popup| {
+                    let (x, y) = popup.position;
+                    let region = Region::new(x, y, popup.region.width, popup.region.height);
+                    (popup.owner, region)
+                });
+                sync_ctx = sync_ctx.with_popups(shown);
+            }
+            let mut damage = self.widget.sync(&mut sync_ctx, ev);
+            while let Ok(msg) = sync_ctx.sync() {
+                damage = damage.max(self.widget.sync(&mut sync_ctx, Event::Message(&msg)));
+            }
+            (damage, sync_ctx.take_popups(), sync_ctx.take_frame())
+        };
+        let mut replaced = Vec::new();
+        for (owner, popup) in popups {
+            replaced.extend(self.update_popup(owner, popup));
         }
-        if damage == Damage::Frame {
+        if damage == Damage::Frame || (frame && damage.is_some()) {
             if self.ctx.time.is_none() {
                 self.ctx.time = Some(0);
             }
+        } else if frame && self.ctx.time.is_none() {
+            // Nothing is drawn so the frame callback is requested right away
+            if let Some(surface) = self.surface.as_ref() {
+                self.ctx.time = Some(self.ctx.last_frame);
+                frame_callback::<M, C>(self.ctx.last_frame, surface.surface.clone());
+            }
+        }
+        let mut redraw = damage.is_some() && !self.ctx.pending_cb;
+        for owner in replaced {
+            redraw |= self.sync(Event::PopupDone(owner));
         }
-        damage.is_some() && !self.ctx.pending_cb
+        redraw
     }
     pub fn destroy(&mut self) {
+        if let Some(popup) = self.popup.take() {
+            popup.destroy();
+        }
         if let Some(surface) = self.surface.as_mut() {
             surface.destroy();
         }
     }
+    // Returns the owner of the popup replaced by this one
+    fn update_popup(&mut self, owner: PopupId, popup: Option<Popup>) -> Option<PopupId> {
+        match popup {
+            Some(popup) => {
+                if let Some(current) = self.popup.as_mut() {
+                    // The popup is only placed when it's created
+                    if current.owner == owner
+                        && (current.region.x, current.region.y) == (popup.region.x, popup.region.y)
+                    {
+                        current.region = popup.region;
+                        current.pending = Some(popup.node);
+                        self.render_popup();
+                        return None;
+                    }
+                }
+                let replaced = self.popup.take().and_then(|current| {
+                    current.destroy();
+                    Some(current.owner).filter(|replaced| *replaced != owner)
+                });
+                self.create_popup(owner, popup);
+                replaced
+            }
+            None => {
+                if let Some(current) = self.popup.take() {
+                    if current.owner == owner {
+                        current.destroy();
+                    } else {
+                        self.popup = Some(current);
+                    }
+                }
+                None
+            }
+        }
+    }
+    fn create_popup(&mut self, owner: PopupId, popup: Popup) {
+        let globals = self.globals.clone();
+        let globals = globals.borrow();
+        if let (Some(compositor), Some(wm_base), Some(surface)) = (
+            globals.compositor.as_ref(),
+            globals.wm_base.as_ref(),
+            self.surface.as_ref(),
+        ) {
+            let Popup {
+                anchor,
+                region,
+                node,
+            } = popup;
+            let wl_surface = compositor.create_surface();
+            wl_surface.quick_assign(|_, _, _| {});
+            let xdg_surface = wm_base.get_xdg_surface(&wl_surface);
+            let positioner = wm_base.create_positioner();
+            positioner.set_size(region.width as i32, region.height as i32);
+            positioner.set_anchor_rect(
+                anchor.x as i32,
+                anchor.y as i32,
+                (anchor.width as i32).max(1),
+                (anchor.height as i32).max(1),
+            );
+            positioner.set_anchor(xdg_positioner::Anchor::BottomLeft);
+            positioner.set_gravity(xdg_positioner::Gravity::BottomRight);
+            positioner.set_offset(
+                (region.x - anchor.x) as i32,
+                (region.y - anchor.y - anchor.height) as i32,
+            );
+            // The popup moves above the anchor if it doesn't fit below
+            positioner.set_constraint_adjustment(
+                (ConstraintAdjustment::SlideX | ConstraintAdjustment::FlipY).bits(),
+            );
+            let xdg_popup = xdg_surface.get_popup(None, &positioner);
+            positioner.destroy();
+            match &surface.shell {
+                Shell::LayerShell { config: _, surface } => surface.get_popup(&xdg_popup),
+            }
+            // Clicks outside of the application dismiss the popup.
+            // The compositor only accepts the grab while the button is held.
+            if let Some((seat, serial)) = self.ctx.grab.as_ref() {
+                xdg_popup.grab(seat, *serial);
+            }
+            assign_popup::<M, C>(&xdg_surface, &xdg_popup, surface.surface.detach());
+            wl_surface.commit();
+            self.popup = Some(PopupSurface {
+                configured: false,
+                owner,
+                region,
+                position: (region.x, region.y),
+                surface: wl_surface,
+                xdg_surface,
+                xdg_popup,
+                buffer: None,
+                mempool: globals.create_mempool(),
+                render_node: None,
+                pending: Some(node),
+            });
+        }
+    }
+    fn render_popup(&mut self) {
+        if let Some(popup) = self.popup.as_mut() {
+            if !popup.configured {
+                return;
+            }
+            if let Some(recent_node) = popup.pending.take() {
+                let (width, height) = (popup.region.width, popup.region.height);
+                if let Ok((buffer, wl_buffer)) =
+                    Buffer::new(&mut popup.mempool, width as i32, height as i32)
+                {
+                    let mut v = Vec::new();
+                    let font_cache = self.ctx.font_cache.clone();
+                    let mut font_cache = font_cache.borrow_mut();
+                    let mut ctx = DrawContext::new(buffer.backend, &mut font_cache, &mut v);
+                    if let Some(render_node) = popup.render_node.as_mut() {
+                        if let Err(region) = render_node.draw_merge(
+                            recent_node,
+                            &mut ctx,
+                            &Instruction::empty(0., 0., width, height),
+                            None,
+                        ) {
+                            ctx.damage_region(&Background::Transparent, region, false);
+                        }
+                    } else {
+                        ctx.damage_region(
+                            &Background::Transparent,
+                            Region::new(0., 0., width, height),
+                            false,
+                        );
+                        recent_node.render(&mut ctx, None);
+                        popup.render_node = Some(recent_node);
+                    }
+                    popup.surface.attach(Some(&wl_buffer), 0, 0);
+                    for d in &v {
+                        popup.surface.damage(
+                            d.x as i32,
+                            d.y as i32,
+                            d.width as i32,
+                            d.height as i32,
+                        );
+                    }
+                    popup.surface.commit();
+                    popup.buffer = Some(wl_buffer);
+                }
+            }
+        }
+    }
+    // The position of the surface in the window
+    fn offset(&self, wl_surface: &WlSurface) -> (f64, f64) {
+        if let Some(popup) = self.popup.as_ref() {
+            if popup.surface.detach().eq(wl_surface) {
+                return (popup.position.0 as f64, popup.position.1 as f64);
+            }
+        }
+        (0., 0.)
+    }
     pub fn get_layer_surface(&self) -> ZwlrLayerSurfaceV1 {
         match &self.surface.as_ref().unwrap().shell {
             Shell::LayerShell { config: _, surface } => surface.detach(),
@@ -602,10 +827,14 @@ where
                 ctx: Context {
                     pending_cb: false,
                     time: None,
-                    font_cache: FontCache::new(),
+                    last_frame: 0,
+                    damaged: false,
+                    font_cache: font::global(),
                     render_node: None,
+                    grab: None,
                 },
                 surface: None,
+                popup: None,
                 widget: Box::new(widget),
                 mempool,
                 globals,
@@ -628,10 +857,14 @@ where
                 ctx: Context {
                     pending_cb: false,
                     time: None,
-                    font_cache: FontCache::new(),
+                    last_frame: 0,
+                    damaged: false,
+                    font_cache: font::global(),
                     render_node: None,
+                    grab: None,
                 },
                 surface: None,
+                popup: None,
                 widget: Box::new(widget),
                 mempool,
                 globals,
@@ -656,10 +889,14 @@ where
                 ctx: Context {
                     pending_cb: false,
                     time: None,
-                    font_cache: FontCache::new(),
+                    last_frame: 0,
+                    damaged: false,
+                    font_cache: font::global(),
                     render_node: None,
+                    grab: None,
                 },
                 surface: None,
+                popup: None,
                 widget: Box::new(widget),
                 mempool,
                 globals,
@@ -671,6 +908,11 @@ where
         new
     }
     fn eq(&self, wl_surface: &WlSurface) -> bool {
+        if let Some(popup) = &self.popup {
+            if popup.surface.detach().eq(wl_surface) {
+                return true;
+            }
+        }
         if let Some(surface) = &self.surface {
             return surface.surface.detach().eq(wl_surface);
         }
@@ -681,7 +923,8 @@ where
         let height = self.height();
 
         // Sending the event to the widget tree
-        if self.sync(ev) || ev.is_frame() {
+        let damaged = std::mem::take(&mut self.ctx.damaged);
+        if self.sync(ev) || ev.is_frame() || damaged {
             // Calling the application´s closure
             (self.cb)(&mut self.core, ev);
 
@@ -719,8 +962,9 @@ where
                 Buffer::new(&mut self.core.mempool, width as i32, height as i32)
             {
                 let mut v = Vec::new();
-                let mut ctx =
-                    DrawContext::new(buffer.backend, &mut self.core.ctx.font_cache, &mut v);
+                let font_cache = self.core.ctx.font_cache.clone();
+                let mut font_cache = font_cache.borrow_mut();
+                let mut ctx = DrawContext::new(buffer.backend, &mut font_cache, &mut v);
                 if let Some(render_node) = self.core.ctx.render_node.as_mut() {
                     if let Err(region) = render_node.draw_merge(
                         recent_node,
@@ -763,7 +1007,10 @@ where
             let width = self.width();
             let height = self.height();
 
-            self.sync(ev);
+            // The damage is drawn on the next frame
+            if self.sync(ev) {
+                self.ctx.damaged = true;
+            }
 
             let current_width = self.width();
             let current_height = self.height();
@@ -786,10 +1033,11 @@ where
         .frame()
         .quick_assign(move |_, event, mut application| match event {
             wl_callback::Event::Done { callback_data } => {
-                let timeout = (callback_data - time).min(50);
+                let timeout = callback_data.saturating_sub(time).min(50);
                 if let Some(application) = application.get::<Application<M, C>>() {
                     if let Some(inner_application) = application.get_application(&h) {
                         inner_application.ctx.time = None;
+                        inner_application.ctx.last_frame = callback_data;
                         inner_application.callback(Event::Callback(timeout));
                     }
                 }
@@ -832,7 +1080,7 @@ impl From<ModifiersState> for Modifiers {
     }
 }
 
-fn assign_pointer<M, C>(pointer: &Main<WlPointer>)
+fn assign_pointer<M, C>(pointer: &Main<WlPointer>, seat: WlSeat)
 where
     M: 'static,
     C: Controller<M> + Clone + 'static,
@@ -840,6 +1088,8 @@ where
     let mut index = 0;
     let mut input = Pointer::Enter;
     let (mut x, mut y) = (0., 0.);
+    // Popups are positioned relative to the window
+    let mut offset = (0., 0.);
     pointer.quick_assign(move |_, event, mut inner| match event {
         wl_pointer::Event::Leave { serial: _, surface } => {
             input = Pointer::Leave;
@@ -850,16 +1100,26 @@ where
             }
         }
         wl_pointer::Event::Button {
-            serial: _,
+            serial,
             time,
             button,
             state,
         } => {
+            let pressed = state == wl_pointer::ButtonState::Pressed;
             input = Pointer::MouseClick {
                 time,
                 button: MouseButton::new(button),
-                pressed: state == wl_pointer::ButtonState::Pressed,
+                pressed,
             };
+            if let Some(application) = inner.get::<Application<M, C>>() {
+                if let Some(inner_application) = application.inner.get_mut(index) {
+                    inner_application.ctx.grab = if pressed {
+                        Some((seat.clone(), serial))
+                    } else {
+                        None
+                    };
+                }
+            }
         }
         wl_pointer::Event::Frame => {
             if let Some(application) = inner.get::<Application<M, C>>() {
@@ -888,9 +1148,14 @@ where
             surface_y,
         } => {
             if let Some(application) = inner.get::<Application<M, C>>() {
-                x = surface_x;
-                y = surface_y;
                 index = application.get_index(&surface);
+                offset = application
+                    .inner
+                    .get(index)
+                    .map(|inner_application| inner_application.offset(&surface))
+                    .unwrap_or((0., 0.));
+                x = surface_x + offset.0;
+                y = surface_y + offset.1;
             }
         }
         wl_pointer::Event::Motion {
@@ -898,8 +1163,8 @@ where
             surface_x,
             surface_y,
         } => {
-            x = surface_x;
-            y = surface_y;
+            x = surface_x + offset.0;
+            y = surface_y + offset.1;
             input = Pointer::Hover;
         }
         _ => {}
@@ -959,3 +1224,54 @@ where
         _ => unreachable!(),
     });
 }
+
+fn assign_popup<M, C>(xdg_surface: &Main<XdgSurface>, xdg_popup: &Main<XdgPopup>, parent: WlSurface)
+where
+    M: 'static,
+    C: Controller<M> + Clone + 'static,
+{
+    let h = parent.clone();
+    xdg_surface.quick_assign(move |xdg_surface, event, mut application| {
+        if let xdg_surface::Event::Configure { serial } = event {
+            xdg_surface.ack_configure(serial);
+            if let Some(application) = application.get::<Application<M, C>>() {
+                if let Some(inner_application) = application.get_application(&h) {
+                    if let Some(popup) = inner_application.popup.as_mut() {
+                        if popup.xdg_surface.eq(&xdg_surface) {
+                            popup.configured = true;
+                            inner_application.render_popup();
+                        }
+                    }
+                }
+            }
+        }
+    });
+    xdg_popup.quick_assign(move |xdg_popup, event, mut application| {
+        if let Some(application) = application.get::<Application<M, C>>() {
+            if let Some(inner_application) = application.get_application(&parent) {
+                match event {
+                    // Where the popup was placed relative to the window
+                    xdg_popup::Event::Configure { x, y, .. } => {
+                        if let Some(popup) = inner_application.popup.as_mut() {
+                            if popup.xdg_popup.eq(&xdg_popup) {
+                                popup.position = (x as f32, y as f32);
+                            }
+                        }
+                    }
+                    // The compositor dismissed the popup
+                    xdg_popup::Event::PopupDone => {
+                        if let Some(popup) = inner_application.popup.take() {
+                            if popup.xdg_popup.eq(&xdg_popup) {
+                                popup.destroy();
+                                inner_application.callback(Event::PopupDone(popup.owner));
+                            } else {
+                                inner_application.popup = Some(popup);
+                            }
+                        }
+                    }
+                    _ => {}
+                }
+            }
+        }
+    });
+}
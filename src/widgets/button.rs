@@ -54,7 +54,7 @@ impl<M, W: Widget<M>> Widget<M> for Proxy<M, W> {
     }
     fn sync<'d>(&'d mut self, ctx: &mut SyncContext<M>, event: Event<'d, M>) -> Damage {
         self.damage = self.damage.max(self.child.sync(ctx, event));
-        self.queue_draw = self.damage.is_some() || event.is_frame();
+        self.queue_draw = self.damage.is_some() || event.is_redraw();
         self.damage
     }
 }
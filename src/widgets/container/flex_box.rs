@@ -0,0 +1,328 @@
+use crate::widgets::container::{Child, Container};
+use crate::widgets::Alignment;
+use crate::*;
+use scene::{Coords, Region, RenderNode};
+use std::ops::Range;
+
+// How the space left on a line is distributed around the children
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Justify {
+    Start,
+    Center,
+    End,
+    // Between the children
+    SpaceBetween,
+    // Around each child
+    SpaceAround,
+    // Evenly between the children and the edges
+    SpaceEvenly,
+}
+
+// Lays out its children on lines where they grow
+// or shrink following their flex properties.
+pub struct FlexBox<M> {
+    size: (Option<f32>, Option<f32>),
+    spacing: f32,
+    wrap: bool,
+    justify: Justify,
+    alignment: Alignment,
+    orientation: Orientation,
+    widgets: Vec<Child<M>>,
+}
+
+impl<M> FromIterator<Child<M>> for FlexBox<M> {
+    fn from_iter<T: IntoIterator<Item = Child<M>>>(iter: T) -> Self {
+        let mut flexbox = FlexBox::new();
+        for c in iter {
+            flexbox.widgets.push(c);
+        }
+        flexbox
+    }
+}
+
+impl<M: 'static> Container<M> for FlexBox<M> {
+    fn len(&self) -> usize {
+        self.widgets.len()
+    }
+    fn add(&mut self, widget: impl Widget<M> + 'static) {
+        self.widgets.push(Child::new(widget));
+    }
+    fn remove(&mut self, index: usize) -> Child<M> {
+        self.widgets.remove(index)
+    }
+}
+
+impl<M> Default for FlexBox<M> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<M> FlexBox<M> {
+    pub fn new() -> Self {
+        FlexBox {
+            size: (None, None),
+            spacing: 0.,
+            wrap: false,
+            justify: Justify::Start,
+            alignment: Alignment::Start,
+            orientation: Orientation::Horizontal,
+            widgets: Vec::new(),
+        }
+    }
+    pub fn orientation(mut self, orientation: Orientation) -> Self {
+        self.orientation = orientation;
+        self
+    }
+    pub fn spacing<S: Into<f32>>(mut self, spacing: S) -> Self {
+        self.spacing = spacing.into();
+        self
+    }
+    pub fn set_spacing<S: Into<f32>>(&mut self, spacing: S) {
+        self.spacing = spacing.into();
+    }
+    // Children which don't fit on a line are moved to the next one
+    pub fn wrap(mut self) -> Self {
+        self.wrap = true;
+        self
+    }
+    pub fn set_wrap(&mut self, wrap: bool) {
+        self.wrap = wrap;
+    }
+    pub fn justify(mut self, justify: Justify) -> Self {
+        self.justify = justify;
+        self
+    }
+    pub fn set_justify(&mut self, justify: Justify) {
+        self.justify = justify;
+    }
+    // Alignment of the children on the cross axis of their line
+    pub fn align(mut self, alignment: Alignment) -> Self {
+        self.alignment = alignment;
+        self
+    }
+    pub fn set_alignment(&mut self, alignment: Alignment) {
+        self.alignment = alignment;
+    }
+    pub fn clear(&mut self) {
+        self.widgets.clear();
+    }
+    fn main(&self, child: &Child<M>) -> f32 {
+        match self.orientation {
+            Orientation::Horizontal => child.width(),
+            Orientation::Vertical => child.height(),
+        }
+    }
+    fn cross(&self, child: &Child<M>) -> f32 {
+        match self.orientation {
+            Orientation::Horizontal => child.height(),
+            Orientation::Vertical => child.width(),
+        }
+    }
+    fn basis(&self, child: &Child<M>) -> f32 {
+        child.basis.unwrap_or_else(|| self.natural(child))
+    }
+    // The size of the child before the FlexBox resized it.
+    // A child resized since then has a new natural size.
+    fn natural(&self, child: &Child<M>) -> f32 {
+        let main = self.main(child);
+        match child.flex {
+            Some((natural, given)) if given == main => natural,
+            _ => main,
+        }
+    }
+    // The size set on the main axis or else the size of the children on a single line
+    fn main_size(&self) -> f32 {
+        let size = match self.orientation {
+            Orientation::Horizontal => self.size.0,
+            Orientation::Vertical => self.size.1,
+        };
+        size.unwrap_or_else(|| {
+            let bases: f32 = self.widgets.iter().map(|child| self.basis(child)).sum();
+            bases + self.spacing * self.widgets.len().saturating_sub(1) as f32
+        })
+    }
+    fn cross_size(&self) -> f32 {
+        let size = match self.orientation {
+            Orientation::Horizontal => self.size.1,
+            Orientation::Vertical => self.size.0,
+        };
+        size.unwrap_or_else(|| {
+            let lines = self.lines(self.main_size());
+            let crosses: f32 = lines
+                .iter()
+                .map(|line| {
+                    self.widgets[line.clone()]
+                        .iter()
+                        .map(|child| self.cross(child))
+                        .fold(0., f32::max)
+                })
+                .sum();
+            crosses + self.spacing * lines.len().saturating_sub(1) as f32
+        })
+    }
+    // Splits the children in lines fitting in the size
+    fn lines(&self, main: f32) -> Vec<Range<usize>> {
+        if !self.wrap {
+            return std::iter::once(0..self.widgets.len()).collect();
+        }
+        let mut lines = Vec::new();
+        let mut start = 0;
+        let mut length = 0.;
+        for (i, child) in self.widgets.iter().enumerate() {
+            let basis = self.basis(child);
+            if i > start && length + self.spacing + basis > main {
+                lines.push(start..i);
+                start = i;
+                length = basis;
+            } else if i > start {
+                length += self.spacing + basis;
+            } else {
+                length = basis;
+            }
+        }
+        lines.push(start..self.widgets.len());
+        lines
+    }
+    // Resizes the children and sets their position
+    fn layout(&mut self) {
+        let main = self.main_size();
+        let spacing = self.spacing;
+        let mut offset_cross = 0.;
+        for line in self.lines(main) {
+            let len = line.len();
+            let bases: Vec<f32> = self.widgets[line.clone()]
+                .iter()
+                .map(|child| self.basis(child))
+                .collect();
+            let free = main - bases.iter().sum::<f32>() - spacing * len.saturating_sub(1) as f32;
+            let grow: f32 = self.widgets[line.clone()].iter().map(|c| c.grow).sum();
+            // Children shrink in proportion to their basis
+            let shrink: f32 = self.widgets[line.clone()]
+                .iter()
+                .zip(bases.iter())
+                .map(|(c, basis)| c.shrink * basis)
+                .sum();
+            let naturals: Vec<f32> = self.widgets[line.clone()]
+                .iter()
+                .map(|child| self.natural(child))
+                .collect();
+            for ((child, basis), natural) in self.widgets[line.clone()]
+                .iter_mut()
+                .zip(bases)
+                .zip(naturals)
+            {
+                let size = if free > 0. && grow > 0. {
+                    basis + free * child.grow / grow
+                } else if free < 0. && shrink > 0. {
+                    basis + free * child.shrink * basis / shrink
+                } else {
+                    basis
+                };
+                let _ = match self.orientation {
+                    Orientation::Horizontal => child.set_width(size.floor().max(0.)),
+                    Orientation::Vertical => child.set_height(size.floor().max(0.)),
+                };
+                let given = match self.orientation {
+                    Orientation::Horizontal => child.width(),
+                    Orientation::Vertical => child.height(),
+                };
+                child.flex = Some((natural, given));
+            }
+            let used: f32 = self.widgets[line.clone()]
+                .iter()
+                .map(|child| self.main(child))
+                .sum();
+            let left = (main - used - spacing * len.saturating_sub(1) as f32).max(0.);
+            let (mut offset, between) = match self.justify {
+                Justify::Start => (0., 0.),
+                Justify::Center => (left / 2., 0.),
+                Justify::End => (left, 0.),
+                Justify::SpaceBetween if len > 1 => (0., left / (len - 1) as f32),
+                Justify::SpaceBetween => (0., 0.),
+                Justify::SpaceAround => (left / len as f32 / 2., left / len as f32),
+                Justify::SpaceEvenly => (left / (len + 1) as f32, left / (len + 1) as f32),
+            };
+            let line_cross = self.widgets[line.clone()]
+                .iter()
+                .map(|child| self.cross(child))
+                .fold(0., f32::max);
+            for i in line {
+                let (size, cross) = (self.main(&self.widgets[i]), self.cross(&self.widgets[i]));
+                let delta = match self.alignment {
+                    Alignment::Start => 0.,
+                    Alignment::Center => ((line_cross - cross) / 2.).floor(),
+                    Alignment::End => (line_cross - cross).floor(),
+                };
+                self.widgets[i].coords = match self.orientation {
+                    Orientation::Horizontal => Coords::new(offset.floor(), offset_cross + delta),
+                    Orientation::Vertical => Coords::new(offset_cross + delta, offset.floor()),
+                };
+                offset += size + spacing + between;
+            }
+            offset_cross += line_cross + spacing;
+        }
+    }
+}
+
+impl<M> Geometry for FlexBox<M> {
+    fn width(&self) -> f32 {
+        match self.orientation {
+            Orientation::Horizontal => self.main_size(),
+            Orientation::Vertical => self.cross_size(),
+        }
+    }
+    fn height(&self) -> f32 {
+        match self.orientation {
+            Orientation::Horizontal => self.cross_size(),
+            Orientation::Vertical => self.main_size(),
+        }
+    }
+    fn set_width(&mut self, width: f32) -> Result<(), f32> {
+        if width > 0. {
+            self.size.0 = Some(width);
+            return Ok(());
+        }
+        Err(self.width())
+    }
+    fn set_height(&mut self, height: f32) -> Result<(), f32> {
+        if height > 0. {
+            self.size.1 = Some(height);
+            return Ok(());
+        }
+        Err(self.height())
+    }
+}
+
+impl<M> Widget<M> for FlexBox<M> {
+    fn create_node(&mut self, x: f32, y: f32) -> RenderNode {
+        self.layout();
+        RenderNode::Container {
+            region: Region::new(x, y, self.width(), self.height()),
+            nodes: self
+                .widgets
+                .iter_mut()
+                .map(|child| {
+                    let (width, height) = (child.width(), child.height());
+                    let Coords { x: dx, y: dy } = child.coords;
+                    let node = child.create_node(x, y);
+                    if node.is_none() {
+                        return node;
+                    }
+                    RenderNode::Extension {
+                        background: scene::Instruction::empty(x + dx, y + dy, width, height),
+                        border: None,
+                        node: Box::new(node),
+                    }
+                })
+                .collect(),
+        }
+    }
+    fn sync<'d>(&'d mut self, ctx: &mut SyncContext<M>, event: Event<'d, M>) -> Damage {
+        let mut damage = Damage::None;
+        for child in self.widgets.iter_mut() {
+            damage = damage.max(child.sync(ctx, event));
+        }
+        damage
+    }
+}
@@ -0,0 +1,419 @@
+use crate::widgets::container::{Child, Container};
+use crate::widgets::Alignment;
+use crate::*;
+use scene::{Coords, Region, RenderNode};
+
+// How the size of a row or a column is decided
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Track {
+    // Size in pixels
+    Fixed(f32),
+    // Size of the largest widget in the track
+    Auto,
+    // Share of the space left by the other tracks
+    Fraction(f32),
+}
+
+// The place of a widget in a Grid
+#[derive(Copy, Clone, Debug)]
+pub struct Cell {
+    pub row: usize,
+    pub column: usize,
+    pub row_span: usize,
+    pub column_span: usize,
+    pub anchor: (Alignment, Alignment),
+}
+
+impl Cell {
+    pub fn new(row: usize, column: usize) -> Self {
+        Cell {
+            row,
+            column,
+            row_span: 1,
+            column_span: 1,
+            anchor: (Alignment::Center, Alignment::Center),
+        }
+    }
+    pub fn span(mut self, rows: usize, columns: usize) -> Self {
+        self.row_span = rows.max(1);
+        self.column_span = columns.max(1);
+        self
+    }
+    pub fn anchor(mut self, x: Alignment, y: Alignment) -> Self {
+        self.anchor = (x, y);
+        self
+    }
+    fn contains(&self, row: usize, column: usize) -> bool {
+        (self.row..self.row + self.row_span).contains(&row)
+            && (self.column..self.column + self.column_span).contains(&column)
+    }
+}
+
+// Places widgets in rows and columns.
+// Pointer events are only sent to the widget of the cell under the pointer.
+pub struct Grid<M> {
+    rows: Vec<Track>,
+    columns: Vec<Track>,
+    // Space between the columns and between the rows
+    gap: (f32, f32),
+    size: (Option<f32>, Option<f32>),
+    widgets: Vec<(Cell, Child<M>)>,
+    // The widget receiving pointer events
+    hovered: Option<usize>,
+    pressed: bool,
+}
+
+// The offset and the size of each track
+struct Tracks {
+    columns: Vec<(f32, f32)>,
+    rows: Vec<(f32, f32)>,
+}
+
+impl Tracks {
+    fn region(&self, cell: &Cell) -> Region {
+        let span = |tracks: &[(f32, f32)], start: usize, span: usize| {
+            let (offset, _) = tracks[start];
+            let (end, size) = tracks[start + span - 1];
+            (offset, end + size - offset)
+        };
+        let (x, width) = span(&self.columns, cell.column, cell.column_span);
+        let (y, height) = span(&self.rows, cell.row, cell.row_span);
+        Region::new(x, y, width, height)
+    }
+}
+
+// Sizes the tracks from the size of the widgets in them.
+// Each widget is given by its first track, its span and its size.
+fn track_sizes(
+    tracks: &[Track],
+    widgets: &[(usize, usize, f32)],
+    gap: f32,
+    size: Option<f32>,
+) -> Vec<f32> {
+    let len = widgets
+        .iter()
+        .map(|(start, span, _)| start + span)
+        .fold(tracks.len(), usize::max);
+    let track = |i: usize| tracks.get(i).copied().unwrap_or(Track::Auto);
+    let mut sizes: Vec<f32> = (0..len)
+        .map(|i| match track(i) {
+            Track::Fixed(size) => size,
+            _ => 0.,
+        })
+        .collect();
+    for (start, _, size) in widgets.iter().filter(|(_, span, _)| *span == 1) {
+        if let Track::Auto | Track::Fraction(_) = track(*start) {
+            sizes[*start] = sizes[*start].max(*size);
+        }
+    }
+    // The space missing for widgets spanning several tracks
+    // is shared by their auto tracks, or else their fractional tracks.
+    for (start, span, size) in widgets.iter().filter(|(_, span, _)| *span > 1) {
+        let range = *start..start + span;
+        let total: f32 = sizes[range.clone()].iter().sum::<f32>() + gap * (span - 1) as f32;
+        if *size > total {
+            let auto: Vec<usize> = range.clone().filter(|i| track(*i) == Track::Auto).collect();
+            let growing = if auto.is_empty() {
+                range
+                    .filter(|i| matches!(track(*i), Track::Fraction(_)))
+                    .collect()
+            } else {
+                auto
+            };
+            for i in growing.iter() {
+                sizes[*i] += (size - total) / growing.len() as f32;
+            }
+        }
+    }
+    let fractions: f32 = (0..len)
+        .filter_map(|i| match track(i) {
+            Track::Fraction(fraction) => Some(fraction),
+            _ => None,
+        })
+        .sum();
+    if fractions > 0. {
+        // Without a size, the fractions are made large enough for their widgets
+        let unit = match size {
+            Some(size) => {
+                let taken: f32 = (0..len)
+                    .filter(|i| !matches!(track(*i), Track::Fraction(_)))
+                    .map(|i| sizes[i])
+                    .sum();
+                (size - taken - gap * len.saturating_sub(1) as f32).max(0.) / fractions
+            }
+            None => (0..len)
+                .filter_map(|i| match track(i) {
+                    Track::Fraction(fraction) if fraction > 0. => Some(sizes[i] / fraction),
+                    _ => None,
+                })
+                .fold(0., f32::max),
+        };
+        for (i, size) in sizes.iter_mut().enumerate() {
+            if let Track::Fraction(fraction) = track(i) {
+                *size = (unit * fraction).floor();
+            }
+        }
+    }
+    sizes
+}
+
+impl<M> FromIterator<Child<M>> for Grid<M> {
+    fn from_iter<T: IntoIterator<Item = Child<M>>>(iter: T) -> Self {
+        let mut grid = Grid::new();
+        for c in iter {
+            let cell = grid.next_cell();
+            grid.widgets.push((cell, c));
+        }
+        grid
+    }
+}
+
+impl<M: 'static> Container<M> for Grid<M> {
+    fn len(&self) -> usize {
+        self.widgets.len()
+    }
+    // The widget is put in the next free cell
+    fn add(&mut self, widget: impl Widget<M> + 'static) {
+        let cell = self.next_cell();
+        self.widgets.push((cell, Child::new(widget)));
+    }
+    fn remove(&mut self, index: usize) -> Child<M> {
+        self.hovered = None;
+        self.widgets.remove(index).1
+    }
+}
+
+impl<M> Grid<M> {
+    pub fn new() -> Self {
+        Grid {
+            rows: Vec::new(),
+            columns: Vec::new(),
+            gap: (0., 0.),
+            size: (None, None),
+            widgets: Vec::new(),
+            hovered: None,
+            pressed: false,
+        }
+    }
+    pub fn rows(mut self, rows: &[Track]) -> Self {
+        self.rows = rows.to_vec();
+        self
+    }
+    pub fn columns(mut self, columns: &[Track]) -> Self {
+        self.columns = columns.to_vec();
+        self
+    }
+    pub fn set_rows(&mut self, rows: &[Track]) {
+        self.rows = rows.to_vec();
+    }
+    pub fn set_columns(&mut self, columns: &[Track]) {
+        self.columns = columns.to_vec();
+    }
+    pub fn gap(mut self, column: f32, row: f32) -> Self {
+        self.gap = (column, row);
+        self
+    }
+    pub fn set_gap(&mut self, column: f32, row: f32) {
+        self.gap = (column, row);
+    }
+    pub fn attach(&mut self, widget: impl Widget<M> + 'static, cell: Cell) {
+        self.widgets.push((cell, Child::new(widget)));
+    }
+    pub fn with(mut self, widget: impl Widget<M> + 'static, cell: Cell) -> Self {
+        self.attach(widget, cell);
+        self
+    }
+    pub fn get_cell(&self, index: usize) -> Option<Cell> {
+        self.widgets.get(index).map(|(cell, _)| *cell)
+    }
+    pub fn set_cell(&mut self, index: usize, cell: Cell) {
+        if let Some((this, _)) = self.widgets.get_mut(index) {
+            *this = cell;
+        }
+    }
+    pub fn clear(&mut self) {
+        self.hovered = None;
+        self.widgets.clear();
+    }
+    // The first cell in reading order not covered by a widget
+    fn next_cell(&self) -> Cell {
+        let columns = self.columns.len().max(1);
+        (0..)
+            .map(|i| Cell::new(i / columns, i % columns))
+            .find(|cell| {
+                !self
+                    .widgets
+                    .iter()
+                    .any(|(other, _)| other.contains(cell.row, cell.column))
+            })
+            .unwrap()
+    }
+    fn tracks(&self) -> Tracks {
+        let offsets = |sizes: Vec<f32>, gap: f32| {
+            let mut offset = 0.;
+            sizes
+                .into_iter()
+                .map(|size| {
+                    let track = (offset, size);
+                    offset += size + gap;
+                    track
+                })
+                .collect()
+        };
+        let columns: Vec<(usize, usize, f32)> = self
+            .widgets
+            .iter()
+            .map(|(cell, child)| (cell.column, cell.column_span, child.width()))
+            .collect();
+        let rows: Vec<(usize, usize, f32)> = self
+            .widgets
+            .iter()
+            .map(|(cell, child)| (cell.row, cell.row_span, child.height()))
+            .collect();
+        Tracks {
+            columns: offsets(
+                track_sizes(&self.columns, &columns, self.gap.0, self.size.0),
+                self.gap.0,
+            ),
+            rows: offsets(
+                track_sizes(&self.rows, &rows, self.gap.1, self.size.1),
+                self.gap.1,
+            ),
+        }
+    }
+    // The widget whose cell is under the pointer
+    fn widget_at(&self, tracks: &Tracks, x: f32, y: f32) -> Option<usize> {
+        self.widgets
+            .iter()
+            .position(|(cell, _)| tracks.region(cell).contains(x, y))
+    }
+}
+
+impl<M> Geometry for Grid<M> {
+    fn width(&self) -> f32 {
+        self.tracks()
+            .columns
+            .last()
+            .map(|(offset, size)| offset + size)
+            .unwrap_or(0.)
+    }
+    fn height(&self) -> f32 {
+        self.tracks()
+            .rows
+            .last()
+            .map(|(offset, size)| offset + size)
+            .unwrap_or(0.)
+    }
+    fn set_width(&mut self, width: f32) -> Result<(), f32> {
+        self.size.0 = Some(width);
+        let this = self.width();
+        if this == width {
+            return Ok(());
+        }
+        Err(this)
+    }
+    fn set_height(&mut self, height: f32) -> Result<(), f32> {
+        self.size.1 = Some(height);
+        let this = self.height();
+        if this == height {
+            return Ok(());
+        }
+        Err(this)
+    }
+}
+
+impl<M> Default for Grid<M> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<M> Widget<M> for Grid<M> {
+    fn create_node(&mut self, x: f32, y: f32) -> RenderNode {
+        let tracks = self.tracks();
+        let region = Region::new(
+            x,
+            y,
+            tracks
+                .columns
+                .last()
+                .map(|(offset, size)| offset + size)
+                .unwrap_or(0.),
+            tracks
+                .rows
+                .last()
+                .map(|(offset, size)| offset + size)
+                .unwrap_or(0.),
+        );
+        RenderNode::Container {
+            region,
+            nodes: self
+                .widgets
+                .iter_mut()
+                .map(|(cell, child)| {
+                    let cell_region = tracks.region(cell);
+                    let (horizontal, vertical) = cell.anchor;
+                    let dx = match horizontal {
+                        Alignment::Start => 0.,
+                        Alignment::Center => ((cell_region.width - child.width()) / 2.).floor(),
+                        Alignment::End => (cell_region.width - child.width()).floor(),
+                    };
+                    let dy = match vertical {
+                        Alignment::Start => 0.,
+                        Alignment::Center => ((cell_region.height - child.height()) / 2.).floor(),
+                        Alignment::End => (cell_region.height - child.height()).floor(),
+                    };
+                    child.coords = Coords::new(cell_region.x + dx, cell_region.y + dy);
+                    let node = child.create_node(x, y);
+                    if node.is_none() {
+                        return node;
+                    }
+                    RenderNode::Extension {
+                        background: scene::Instruction::empty(
+                            x + cell_region.x,
+                            y + cell_region.y,
+                            cell_region.width,
+                            cell_region.height,
+                        ),
+                        border: None,
+                        node: Box::new(node),
+                    }
+                })
+                .collect(),
+        }
+    }
+    fn sync<'d>(&'d mut self, ctx: &mut SyncContext<M>, event: Event<'d, M>) -> Damage {
+        let mut damage = Damage::None;
+        match event {
+            Event::Pointer(x, y, pointer) => {
+                // The widget pressed keeps the pointer until it's released
+                let hovered = if self.pressed {
+                    self.hovered
+                } else {
+                    match pointer {
+                        Pointer::Leave => None,
+                        _ => self.widget_at(&self.tracks(), x, y),
+                    }
+                };
+                if let Pointer::MouseClick { pressed, .. } = pointer {
+                    self.pressed = pressed && hovered.is_some();
+                }
+                if hovered != self.hovered {
+                    if let Some((_, child)) = self.hovered.and_then(|i| self.widgets.get_mut(i)) {
+                        damage = damage.max(child.sync(ctx, Event::Pointer(x, y, Pointer::Leave)));
+                    }
+                    self.hovered = hovered;
+                }
+                if let Some((_, child)) = hovered.and_then(|i| self.widgets.get_mut(i)) {
+                    damage = damage.max(child.sync(ctx, event));
+                }
+            }
+            _ => {
+                for (_, child) in self.widgets.iter_mut() {
+                    damage = damage.max(child.sync(ctx, event));
+                }
+            }
+        }
+        damage
+    }
+}
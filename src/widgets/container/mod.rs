@@ -1,10 +1,16 @@
 pub mod center_box;
+pub mod flex_box;
+pub mod grid;
 pub mod layout_box;
+pub mod overlay;
 pub mod widget_layout;
 
 use crate::*;
 pub use center_box::CenterBox;
+pub use flex_box::{FlexBox, Justify};
+pub use grid::{Cell, Grid, Track};
 pub use layout_box::LayoutBox;
+pub use overlay::{Layer, Overlay};
 use scene::Coords;
 pub use widget_layout::WidgetLayout;
 
@@ -72,6 +78,13 @@ pub struct Child<M> {
     coords: Coords,
     damage: Damage,
     queue_draw: bool,
+    // How the child is sized in a FlexBox
+    grow: f32,
+    shrink: f32,
+    basis: Option<f32>,
+    // The size of the child on the main axis of a FlexBox
+    // before it was resized and the size it was given
+    flex: Option<(f32, f32)>,
     widget: Box<dyn Widget<M>>,
 }
 
@@ -81,12 +94,31 @@ impl<M> Child<M> {
             queue_draw: false,
             damage: Damage::None,
             coords: Coords::new(0., 0.),
+            grow: 0.,
+            shrink: 1.,
+            basis: None,
+            flex: None,
             widget: Box::new(widget),
         }
     }
     pub fn set_coords(&mut self, x: f32, y: f32) {
         self.coords = Coords::new(x, y);
     }
+    // Share of the free space of a FlexBox taken by the child
+    pub fn grow(mut self, grow: f32) -> Self {
+        self.grow = grow.max(0.);
+        self
+    }
+    // Share of the missing space of a FlexBox given up by the child
+    pub fn shrink(mut self, shrink: f32) -> Self {
+        self.shrink = shrink.max(0.);
+        self
+    }
+    // Size of the child before it grows or shrinks
+    pub fn basis(mut self, basis: f32) -> Self {
+        self.basis = Some(basis);
+        self
+    }
 }
 
 impl<M> Geometry for Child<M> {
@@ -110,6 +142,10 @@ impl<M> From<Box<dyn Widget<M>>> for Child<M> {
             queue_draw: false,
             damage: Damage::None,
             coords: Coords::new(0., 0.),
+            grow: 0.,
+            shrink: 1.,
+            basis: None,
+            flex: None,
             widget,
         }
     }
@@ -136,10 +172,12 @@ impl<M> Widget<M> for Child<M> {
             Event::Frame => self.widget.sync(ctx, event),
             _ => self.widget.sync(ctx, event),
         });
-        self.queue_draw = self.damage.is_some() || event.is_frame();
+        self.queue_draw = self.damage.is_some() || event.is_redraw();
         self.damage
     }
+    // The point is relative to the parent,
+    // the widget is at the coords of the child in it
     fn contains(&self, x: f32, y: f32) -> bool {
-        self.widget.contains(x + self.coords.x, y + self.coords.y)
+        self.widget.contains(x - self.coords.x, y - self.coords.y)
     }
 }
@@ -0,0 +1,196 @@
+use crate::widgets::container::{Child, Container};
+use crate::widgets::Alignment;
+use crate::*;
+use scene::{Coords, Region, RenderNode};
+
+// The place of a widget in an Overlay
+#[derive(Copy, Clone, Debug)]
+pub struct Layer {
+    pub anchor: (Alignment, Alignment),
+    pub offset: (f32, f32),
+    // Pointer events are also sent to the layers below
+    pub fall_through: bool,
+}
+
+impl Default for Layer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Layer {
+    pub fn new() -> Self {
+        Layer {
+            anchor: (Alignment::Center, Alignment::Center),
+            offset: (0., 0.),
+            fall_through: false,
+        }
+    }
+    pub fn anchor(mut self, x: Alignment, y: Alignment) -> Self {
+        self.anchor = (x, y);
+        self
+    }
+    pub fn offset(mut self, x: f32, y: f32) -> Self {
+        self.offset = (x, y);
+        self
+    }
+    pub fn fall_through(mut self) -> Self {
+        self.fall_through = true;
+        self
+    }
+}
+
+// Stacks widgets in the same box.
+// The last widget is drawn on top and gets the pointer events first.
+pub struct Overlay<M> {
+    size: (Option<f32>, Option<f32>),
+    // The layers with whether the pointer is over them
+    widgets: Vec<(Layer, bool, Child<M>)>,
+}
+
+impl<M> FromIterator<Child<M>> for Overlay<M> {
+    fn from_iter<T: IntoIterator<Item = Child<M>>>(iter: T) -> Self {
+        let mut overlay = Overlay::new();
+        for c in iter {
+            overlay.widgets.push((Layer::new(), false, c));
+        }
+        overlay
+    }
+}
+
+impl<M: 'static> Container<M> for Overlay<M> {
+    fn len(&self) -> usize {
+        self.widgets.len()
+    }
+    fn add(&mut self, widget: impl Widget<M> + 'static) {
+        self.widgets.push((Layer::new(), false, Child::new(widget)));
+    }
+    fn remove(&mut self, index: usize) -> Child<M> {
+        self.widgets.remove(index).2
+    }
+}
+
+impl<M> Default for Overlay<M> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<M> Overlay<M> {
+    pub fn new() -> Self {
+        Overlay {
+            size: (None, None),
+            widgets: Vec::new(),
+        }
+    }
+    pub fn push(&mut self, widget: impl Widget<M> + 'static, layer: Layer) {
+        self.widgets.push((layer, false, Child::new(widget)));
+    }
+    pub fn with(mut self, widget: impl Widget<M> + 'static, layer: Layer) -> Self {
+        self.push(widget, layer);
+        self
+    }
+    pub fn get_layer(&self, index: usize) -> Option<Layer> {
+        self.widgets.get(index).map(|(layer, _, _)| *layer)
+    }
+    pub fn set_layer(&mut self, index: usize, layer: Layer) {
+        if let Some((this, _, _)) = self.widgets.get_mut(index) {
+            *this = layer;
+        }
+    }
+    pub fn clear(&mut self) {
+        self.widgets.clear();
+    }
+}
+
+impl<M> Geometry for Overlay<M> {
+    fn width(&self) -> f32 {
+        self.size.0.unwrap_or_else(|| {
+            self.widgets
+                .iter()
+                .map(|(_, _, child)| child.width())
+                .fold(0., f32::max)
+        })
+    }
+    fn height(&self) -> f32 {
+        self.size.1.unwrap_or_else(|| {
+            self.widgets
+                .iter()
+                .map(|(_, _, child)| child.height())
+                .fold(0., f32::max)
+        })
+    }
+    fn set_width(&mut self, width: f32) -> Result<(), f32> {
+        if width > 0. {
+            self.size.0 = Some(width);
+            return Ok(());
+        }
+        Err(self.width())
+    }
+    fn set_height(&mut self, height: f32) -> Result<(), f32> {
+        if height > 0. {
+            self.size.1 = Some(height);
+            return Ok(());
+        }
+        Err(self.height())
+    }
+}
+
+impl<M> Widget<M> for Overlay<M> {
+    // The layers overlap so they're all drawn again when one changes
+    fn create_node(&mut self, x: f32, y: f32) -> RenderNode {
+        let (width, height) = (self.width(), self.height());
+        let region = Region::new(x, y, width, height);
+        let nodes = self
+            .widgets
+            .iter_mut()
+            .map(|(layer, _, child)| {
+                let (horizontal, vertical) = layer.anchor;
+                let (dx, dy) = layer.offset;
+                let dx = dx
+                    + match horizontal {
+                        Alignment::Start => 0.,
+                        Alignment::Center => ((width - child.width()) / 2.).floor(),
+                        Alignment::End => (width - child.width()).floor(),
+                    };
+                let dy = dy
+                    + match vertical {
+                        Alignment::Start => 0.,
+                        Alignment::Center => ((height - child.height()) / 2.).floor(),
+                        Alignment::End => (height - child.height()).floor(),
+                    };
+                child.coords = Coords::new(dx, dy);
+                child.create_node(x, y)
+            })
+            .collect();
+        RenderNode::Clip {
+            region,
+            node: Box::new(RenderNode::Container { region, nodes }),
+        }
+    }
+    fn sync<'d>(&'d mut self, ctx: &mut SyncContext<M>, event: Event<'d, M>) -> Damage {
+        let mut damage = Damage::None;
+        match event {
+            Event::Pointer(x, y, pointer) => {
+                // The event goes down the layers until one doesn't let it through
+                let mut blocked = !self.contains(x, y);
+                for (layer, hovered, child) in self.widgets.iter_mut().rev() {
+                    let over = !blocked && child.contains(x, y);
+                    if over || (*hovered && !matches!(pointer, Pointer::Hover)) {
+                        damage = damage.max(child.sync(ctx, event));
+                    } else if *hovered {
+                        damage = damage.max(child.sync(ctx, Event::Pointer(x, y, Pointer::Leave)));
+                    }
+                    *hovered = over;
+                    blocked |= over && !layer.fall_through;
+                }
+            }
+            _ => {
+                for (_, _, child) in self.widgets.iter_mut() {
+                    damage = damage.max(child.sync(ctx, event));
+                }
+            }
+        }
+        damage
+    }
+}
@@ -0,0 +1,341 @@
+use crate::controller::*;
+use crate::scene::{Instruction, Region};
+use crate::widgets::shapes::{Rectangle, ShapeStyle, Style};
+use crate::widgets::text::Label;
+use crate::*;
+use std::ops::{Deref, DerefMut};
+
+// Space around the content of the box
+const PADDING: f32 = 12.;
+// Space around the text of the buttons
+const BUTTON_PADDING: (f32, f32) = (6., 12.);
+// Space between the title, body and buttons and between the buttons
+const GAP: f32 = 8.;
+
+// Shows a box with a title, a body and a row of buttons over the content.
+// While open, the content is dimmed and receives neither pointer or keyboard events.
+// The message is sent with the index of the chosen button.
+pub struct Dialog<M: TryIntoMessage<usize>, W: Widget<M>> {
+    content: W,
+    title: Label,
+    body: Label,
+    buttons: Vec<Label>,
+    scrim: u32,
+    background: u32,
+    // The button chosen with Escape
+    cancel: Option<usize>,
+    focused: usize,
+    open: bool,
+    // Tells if the content was last drawn with the dialog
+    shown: bool,
+    message: Option<M>,
+}
+
+impl<M: TryIntoMessage<usize>, W: Widget<M>> Dialog<M, W> {
+    pub fn new(content: W) -> Self {
+        Self {
+            content,
+            title: Label::default("", 18.),
+            body: Label::default("", 14.),
+            buttons: Vec::new(),
+            scrim: 0x80_00_00_00,
+            background: style::BG0,
+            cancel: None,
+            focused: 0,
+            open: false,
+            shown: false,
+            message: None,
+        }
+    }
+    pub fn title(mut self, title: &str) -> Self {
+        self.set_title(title);
+        self
+    }
+    pub fn set_title(&mut self, title: &str) {
+        self.title = Label::default(title, self.title.get_font_size());
+    }
+    pub fn body(mut self, body: &str) -> Self {
+        self.set_body(body);
+        self
+    }
+    pub fn set_body(&mut self, body: &str) {
+        self.body = Label::default(body, self.body.get_font_size());
+        let _ = self.body.set_width(360.);
+    }
+    pub fn button(mut self, label: &str) -> Self {
+        self.buttons.push(Label::default(label, 14.));
+        self
+    }
+    // Escape chooses this button
+    pub fn cancel(mut self, index: usize) -> Self {
+        self.cancel = Some(index);
+        self
+    }
+    pub fn message(mut self, message: M) -> Self {
+        self.message = Some(message);
+        self
+    }
+    // Color drawn over the content
+    pub fn scrim(mut self, color: u32) -> Self {
+        self.scrim = color;
+        self
+    }
+    pub fn is_open(&self) -> bool {
+        self.open
+    }
+    // The first button has the focus
+    pub fn open(&mut self) {
+        self.open = true;
+        self.focused = 0;
+    }
+    pub fn close(&mut self) {
+        self.open = false;
+    }
+    fn button_size(label: &Label) -> (f32, f32) {
+        let (vertical, horizontal) = BUTTON_PADDING;
+        (
+            label.width().ceil() + 2. * horizontal,
+            label.height().ceil() + 2. * vertical,
+        )
+    }
+    fn buttons_size(&self) -> (f32, f32) {
+        self.buttons
+            .iter()
+            .map(Self::button_size)
+            .fold((0., 0.), |(width, height), (w, h)| {
+                (width + w + if width > 0. { GAP } else { 0. }, height.max(h))
+            })
+    }
+    fn box_size(&self) -> (f32, f32) {
+        let (buttons_width, buttons_height) = self.buttons_size();
+        let width = self
+            .title
+            .width()
+            .max(self.body.width())
+            .max(buttons_width)
+            .ceil();
+        let height = [self.title.height(), self.body.height(), buttons_height]
+            .iter()
+            .filter(|height| **height > 0.)
+            .map(|height| height.ceil())
+            .reduce(|a, b| a + GAP + b)
+            .unwrap_or(0.);
+        (width + 2. * PADDING, height + 2. * PADDING)
+    }
+    // The region of the box relative to the content
+    fn box_region(&self) -> Region {
+        let (width, height) = self.box_size();
+        Region::new(
+            ((self.content.width() - width) / 2.).round(),
+            ((self.content.height() - height) / 2.).round(),
+            width,
+            height,
+        )
+    }
+    // The regions of the buttons relative to the content, aligned to the right of the box
+    fn button_regions(&self) -> impl Iterator<Item = Region> + '_ {
+        let region = self.box_region();
+        let (width, height) = self.buttons_size();
+        let mut x = region.x + region.width - PADDING - width;
+        let y = region.y + region.height - PADDING - height;
+        self.buttons.iter().map(move |label| {
+            let (w, h) = Self::button_size(label);
+            let button = Region::new(x, y, w, h);
+            x += w + GAP;
+            button
+        })
+    }
+    fn choose(&mut self, ctx: &mut SyncContext<M>, index: usize) {
+        if let Some(message) = self.message.as_ref() {
+            if let Ok(msg) = TryIntoMessage::try_into(message, index) {
+                let _ = ctx.send(msg);
+            }
+        }
+        self.close();
+    }
+    fn keyboard(&mut self, ctx: &mut SyncContext<M>, key: Key) {
+        if key.is(keys::ESCAPE) {
+            if let Some(cancel) = self.cancel {
+                self.choose(ctx, cancel);
+            }
+            return;
+        }
+        let len = self.buttons.len();
+        if len == 0 {
+            return;
+        }
+        if key.is(keys::ISO_LEFT_TAB)
+            || (key.modifiers.shift && key.is(keys::TAB))
+            || key.is(keys::LEFT)
+        {
+            self.focused = (self.focused + len - 1) % len;
+        } else if key.is(keys::TAB) || key.is(keys::RIGHT) {
+            self.focused = (self.focused + 1) % len;
+        } else if key.is(keys::RETURN) || key.is(keys::KP_ENTER) || key.is(keys::SPACE) {
+            self.choose(ctx, self.focused);
+        }
+    }
+    fn box_node(&self, x: f32, y: f32) -> RenderNode {
+        let region = self.box_region();
+        let (bx, by) = (x + region.x, y + region.y);
+        let mut nodes = Vec::new();
+        let mut ty = by + PADDING;
+        for label in [&self.title, &self.body] {
+            if label.height() > 0. {
+                nodes.push(Instruction::new(bx + PADDING, ty, label.clone()).into());
+                ty += label.height().ceil() + GAP;
+            }
+        }
+        for (i, (button, label)) in self.button_regions().zip(self.buttons.iter()).enumerate() {
+            let color = if i == self.focused {
+                style::BLU
+            } else {
+                style::BG2
+            };
+            nodes.push(RenderNode::Extension {
+                background: Instruction::new(
+                    x + button.x,
+                    y + button.y,
+                    Rectangle::new(button.width, button.height, ShapeStyle::solid(color))
+                        .radius(4., 4., 4., 4.),
+                ),
+                border: None,
+                node: Box::new(
+                    Instruction::new(
+                        x + button.x + BUTTON_PADDING.1,
+                        y + button.y + BUTTON_PADDING.0,
+                        label.clone(),
+                    )
+                    .into(),
+                ),
+            });
+        }
+        RenderNode::Extension {
+            background: Instruction::new(
+                bx,
+                by,
+                Rectangle::new(
+                    region.width,
+                    region.height,
+                    ShapeStyle::solid(self.background),
+                )
+                .radius(6., 6., 6., 6.),
+            ),
+            border: None,
+            node: Box::new(RenderNode::Container {
+                region: Region::new(bx, by, region.width, region.height),
+                nodes,
+            }),
+        }
+    }
+}
+
+impl<M: TryIntoMessage<usize>, W: Widget<M>> Geometry for Dialog<M, W> {
+    fn width(&self) -> f32 {
+        self.content.width()
+    }
+    fn height(&self) -> f32 {
+        self.content.height()
+    }
+    fn set_width(&mut self, width: f32) -> Result<(), f32> {
+        self.content.set_width(width)
+    }
+    fn set_height(&mut self, height: f32) -> Result<(), f32> {
+        self.content.set_height(height)
+    }
+}
+
+impl<M: TryIntoMessage<usize>, W: Widget<M>> Widget<M> for Dialog<M, W> {
+    fn create_node(&mut self, x: f32, y: f32) -> RenderNode {
+        let node = self.content.create_node(x, y);
+        if !self.open {
+            return node;
+        }
+        let (width, height) = (self.width(), self.height());
+        let region = Region::new(x, y, width, height);
+        // The content is drawn again with the dialog on each change
+        RenderNode::Clip {
+            region,
+            node: Box::new(RenderNode::Container {
+                region,
+                nodes: vec![
+                    node,
+                    Instruction::new(
+                        x,
+                        y,
+                        Rectangle::new(width, height, ShapeStyle::solid(self.scrim)),
+                    )
+                    .into(),
+                    self.box_node(x, y),
+                ],
+            }),
+        }
+    }
+    fn sync<'d>(&'d mut self, ctx: &mut SyncContext<M>, event: Event<'d, M>) -> Damage {
+        let mut damage = Damage::None;
+        for label in [&mut self.title, &mut self.body]
+            .into_iter()
+            .chain(self.buttons.iter_mut())
+        {
+            damage = damage.max(Widget::<M>::sync(label, ctx, event));
+        }
+        let focused = self.focused;
+        if self.open {
+            match event {
+                Event::Pointer(
+                    x,
+                    y,
+                    Pointer::MouseClick {
+                        button,
+                        pressed: true,
+                        ..
+                    },
+                ) if button.is_left() => {
+                    let index = self
+                        .button_regions()
+                        .position(|region| region.contains(x, y));
+                    if let Some(index) = index {
+                        self.focused = index;
+                        self.choose(ctx, index);
+                    }
+                }
+                Event::Pointer(..) => {}
+                Event::Keyboard(key) => {
+                    if key.pressed {
+                        self.keyboard(ctx, key);
+                    }
+                }
+                _ => damage = damage.max(self.content.sync(ctx, event)),
+            }
+        } else {
+            damage = damage.max(self.content.sync(ctx, event));
+        }
+        if self.open != self.shown {
+            if self.open {
+                // The content shouldn't stay hovered under the dialog
+                self.content
+                    .sync(ctx, Event::Pointer(0., 0., Pointer::Leave));
+            }
+            // The whole content is drawn again with or without the dialog
+            self.content.sync(ctx, Event::Redraw);
+            self.shown = self.open;
+            damage = damage.max(Damage::Some);
+        } else if focused != self.focused {
+            damage = damage.max(Damage::Some);
+        }
+        damage
+    }
+}
+
+impl<M: TryIntoMessage<usize>, W: Widget<M>> Deref for Dialog<M, W> {
+    type Target = W;
+    fn deref(&self) -> &Self::Target {
+        &self.content
+    }
+}
+
+impl<M: TryIntoMessage<usize>, W: Widget<M>> DerefMut for Dialog<M, W> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.content
+    }
+}
@@ -0,0 +1,480 @@
+use crate::context::{Popup, PopupId};
+use crate::controller::*;
+use crate::scene::{Instruction, Region};
+use crate::widgets::shapes::{Rectangle, ShapeStyle, Style};
+use crate::widgets::text::Label;
+use crate::*;
+
+// Space around the labels
+const PADDING: f32 = 4.;
+
+// Shows the selected option and opens the list of options on click.
+// The list is shown in a popup if the backend has them,
+// otherwise the dropdown grows to show it below the selected option.
+// The message is sent with the index of the selected option.
+pub struct Dropdown<M: TryIntoMessage<usize>> {
+    options: Vec<Label>,
+    frame: Rectangle,
+    list: u32,
+    highlight: u32,
+    selection: u32,
+    width: Option<f32>,
+    // Rows shown before the list scrolls
+    rows: usize,
+    selected: Option<usize>,
+    // The option under the pointer or reached with the keyboard
+    highlighted: Option<usize>,
+    scroll: f32,
+    open: bool,
+    focused: bool,
+    popups: bool,
+    popup: PopupId,
+    // Position of the dropdown in the window
+    origin: (f32, f32),
+    // Text typed to find an option
+    search: String,
+    message: Option<M>,
+}
+
+impl<M: TryIntoMessage<usize>> Default for Dropdown<M> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<M: TryIntoMessage<usize>> Dropdown<M> {
+    pub fn new() -> Self {
+        Self {
+            options: Vec::new(),
+            frame: Rectangle::empty(0., 0.).background(style::BG2),
+            list: style::BG0,
+            highlight: style::BG2,
+            selection: style::BLU,
+            width: None,
+            rows: 8,
+            selected: None,
+            highlighted: None,
+            scroll: 0.,
+            open: false,
+            focused: false,
+            popups: false,
+            popup: PopupId::new(),
+            origin: (0., 0.),
+            search: String::new(),
+            message: None,
+        }
+    }
+    pub fn option<L: Into<Label>>(mut self, label: L) -> Self {
+        self.push(label);
+        self
+    }
+    pub fn push<L: Into<Label>>(&mut self, label: L) {
+        self.options.push(label.into());
+    }
+    pub fn message(mut self, message: M) -> Self {
+        self.message = Some(message);
+        self
+    }
+    pub fn rows(mut self, rows: usize) -> Self {
+        self.rows = rows.max(1);
+        self
+    }
+    pub fn list_background(mut self, color: u32) -> Self {
+        self.list = color;
+        self
+    }
+    pub fn selection_color(mut self, color: u32) -> Self {
+        self.selection = color;
+        self
+    }
+    pub fn len(&self) -> usize {
+        self.options.len()
+    }
+    pub fn is_empty(&self) -> bool {
+        self.options.is_empty()
+    }
+    pub fn is_open(&self) -> bool {
+        self.open
+    }
+    pub fn selected(&self) -> Option<usize> {
+        self.selected
+    }
+    pub fn selected_option(&self) -> Option<&str> {
+        self.options
+            .get(self.selected?)
+            .map(|label| label.get_text())
+    }
+    // Selects the option without sending the message
+    pub fn set_selected(&mut self, selected: Option<usize>) {
+        self.selected = selected.filter(|index| *index < self.options.len());
+    }
+    pub fn is_focused(&self) -> bool {
+        self.focused
+    }
+    pub fn set_focus(&mut self, focused: bool) {
+        self.focused = focused;
+    }
+    fn row_height(&self) -> f32 {
+        self.options
+            .iter()
+            .map(|label| label.height())
+            .fold(0., f32::max)
+            .ceil()
+            + 2. * PADDING
+    }
+    fn list_height(&self) -> f32 {
+        self.options.len().min(self.rows) as f32 * self.row_height()
+    }
+    fn set_scroll(&mut self, scroll: f32) {
+        let max = self.options.len() as f32 * self.row_height() - self.list_height();
+        self.scroll = scroll.min(max).max(0.).round();
+    }
+    // Scrolls the least to show the option
+    fn scroll_to(&mut self, index: usize) {
+        let row_height = self.row_height();
+        let y = index as f32 * row_height;
+        let scroll = self.scroll.min(y).max(y + row_height - self.list_height());
+        self.set_scroll(scroll);
+    }
+    // The region of the list relative to the dropdown
+    fn list_region(&self, ctx: &SyncContext<M>) -> Region {
+        let (width, height) = (self.width(), self.list_height());
+        match ctx.popup_region(self.popup) {
+            // The compositor moves the popup above the dropdown if it doesn't fit below
+            Some(region) => Region::new(
+                region.x - self.origin.0,
+                region.y - self.origin.1,
+                width,
+                height,
+            ),
+            None => Region::new(0., self.row_height(), width, height),
+        }
+    }
+    fn index_at(&self, y: f32) -> Option<usize> {
+        let index = ((y + self.scroll) / self.row_height()).floor();
+        if index >= 0. && (index as usize) < self.options.len() {
+            return Some(index as usize);
+        }
+        None
+    }
+    fn open(&mut self) {
+        self.open = true;
+        self.highlighted = self.selected;
+        self.search.clear();
+        if let Some(index) = self.selected {
+            self.scroll_to(index);
+        }
+    }
+    fn close(&mut self) {
+        self.open = false;
+        self.search.clear();
+    }
+    fn select(&mut self, ctx: &mut SyncContext<M>, index: usize) {
+        if self.selected != Some(index) {
+            self.selected = Some(index);
+            if let Some(msg) = self.message.as_ref() {
+                if let Ok(msg) = TryIntoMessage::try_into(msg, index) {
+                    let _ = ctx.send(msg);
+                }
+            }
+        }
+    }
+    // Finds the option starting with the typed text.
+    // Typing the same letter goes through the options starting with it.
+    fn find(&mut self, text: &str, current: Option<usize>) -> Option<usize> {
+        let len = self.options.len();
+        self.search.push_str(&text.to_lowercase());
+        let matches =
+            |label: &Label, prefix: &str| label.get_text().to_lowercase().starts_with(prefix);
+        let first = self.search.chars().next()?;
+        let start = if self.search.chars().all(|c| c == first) {
+            self.search = first.to_string();
+            current.map(|index| index + 1).unwrap_or(0)
+        } else {
+            current.unwrap_or(0)
+        };
+        let found = (0..len)
+            .map(|n| (start + n) % len)
+            .find(|index| matches(&self.options[*index], &self.search));
+        if found.is_none() && self.search != text.to_lowercase() {
+            // Starts a new search with the text
+            self.search.clear();
+            return self.find(text, current);
+        }
+        found
+    }
+    fn keyboard(&mut self, ctx: &mut SyncContext<M>, key: Key) {
+        if self.options.is_empty() {
+            return;
+        }
+        if key.is(keys::ESCAPE) {
+            self.close();
+            return;
+        }
+        if key.is(keys::RETURN)
+            || key.is(keys::KP_ENTER)
+            || (key.is(keys::SPACE) && self.search.is_empty())
+        {
+            if !self.open {
+                self.open();
+            } else {
+                if let Some(index) = self.highlighted {
+                    self.select(ctx, index);
+                }
+                self.close();
+            }
+            return;
+        }
+        let last = self.options.len() - 1;
+        let current = if self.open {
+            self.highlighted
+        } else {
+            self.selected
+        };
+        let index = match current {
+            _ if key.is(keys::HOME) => Some(0),
+            _ if key.is(keys::END) => Some(last),
+            Some(index) if key.is(keys::UP) => Some(index.saturating_sub(1)),
+            Some(index) if key.is(keys::DOWN) => Some((index + 1).min(last)),
+            Some(index) if key.is(keys::PAGE_UP) => Some(index.saturating_sub(self.rows)),
+            Some(index) if key.is(keys::PAGE_DOWN) => Some((index + self.rows).min(last)),
+            None if [keys::UP, keys::DOWN, keys::PAGE_UP, keys::PAGE_DOWN]
+                .iter()
+                .any(|keysym| key.is(*keysym)) =>
+            {
+                Some(0)
+            }
+            _ => match key.utf8 {
+                Some(text) if !text.is_empty() && !text.chars().any(char::is_control) => {
+                    self.find(text, current)
+                }
+                _ => None,
+            },
+        };
+        if let Some(index) = index {
+            if self.open {
+                self.highlighted = Some(index);
+                self.scroll_to(index);
+            } else {
+                self.select(ctx, index);
+            }
+        }
+    }
+    fn list_node(&self, x: f32, y: f32) -> RenderNode {
+        let (width, height) = (self.width(), self.list_height());
+        let row_height = self.row_height();
+        let region = Region::new(x, y, width, height);
+        let first = (self.scroll / row_height).floor() as usize;
+        let nodes = self
+            .options
+            .iter()
+            .enumerate()
+            .skip(first)
+            .take(self.rows + 1)
+            .map(|(i, label)| {
+                let ry = y + i as f32 * row_height - self.scroll;
+                let background = if self.highlighted == Some(i) {
+                    Rectangle::new(width, row_height, ShapeStyle::solid(self.highlight))
+                } else if self.selected == Some(i) {
+                    Rectangle::new(width, row_height, ShapeStyle::solid(self.selection))
+                } else {
+                    Rectangle::empty(width, row_height)
+                };
+                RenderNode::Extension {
+                    background: Instruction::new(x, ry, background),
+                    border: None,
+                    node: Box::new(
+                        Instruction::new(
+                            x + PADDING,
+                            ry + ((row_height - label.height()) / 2.).floor(),
+                            label.clone(),
+                        )
+                        .into(),
+                    ),
+                }
+            })
+            .collect();
+        RenderNode::Extension {
+            background: Instruction::new(
+                x,
+                y,
+                Rectangle::new(width, height, ShapeStyle::solid(self.list)),
+            ),
+            border: None,
+            node: Box::new(RenderNode::Clip {
+                region,
+                node: Box::new(RenderNode::Container { region, nodes }),
+            }),
+        }
+    }
+}
+
+impl<M: TryIntoMessage<usize>> Geometry for Dropdown<M> {
+    fn width(&self) -> f32 {
+        self.width.unwrap_or_else(|| {
+            self.options
+                .iter()
+                .map(|label| label.width())
+                .fold(0., f32::max)
+                .ceil()
+                + 2. * PADDING
+        })
+    }
+    // The list is part of the dropdown while it's open without popups
+    fn height(&self) -> f32 {
+        match self.open && !self.popups {
+            true => self.row_height() + self.list_height(),
+            false => self.row_height(),
+        }
+    }
+    fn set_width(&mut self, width: f32) -> Result<(), f32> {
+        if width > 0. {
+            self.width = Some(width);
+            return Ok(());
+        }
+        Err(self.width())
+    }
+}
+
+impl<M: TryIntoMessage<usize>> Widget<M> for Dropdown<M> {
+    fn create_node(&mut self, x: f32, y: f32) -> RenderNode {
+        self.origin = (x, y);
+        let (width, height) = (self.width(), self.row_height());
+        let mut frame = self.frame.clone();
+        let _ = frame.set_size(width, height);
+        let header = RenderNode::Extension {
+            background: Instruction::new(x, y, frame),
+            border: None,
+            node: Box::new(match self.selected.and_then(|i| self.options.get(i)) {
+                Some(label) => Instruction::new(
+                    x + PADDING,
+                    y + ((height - label.height()) / 2.).floor(),
+                    label.clone(),
+                )
+                .into(),
+                None => Instruction::empty(x, y, width, height).into(),
+            }),
+        };
+        if self.popups || !self.open {
+            return header;
+        }
+        RenderNode::Container {
+            region: Region::new(x, y, width, self.height()),
+            nodes: vec![header, self.list_node(x, y + height)],
+        }
+    }
+    fn sync<'d>(&'d mut self, ctx: &mut SyncContext<M>, event: Event<'d, M>) -> Damage {
+        let mut damage = Damage::None;
+        for label in self.options.iter_mut() {
+            damage = damage.max(Widget::<M>::sync(label, ctx, event));
+        }
+        self.popups = ctx.has_popups();
+        let state = (self.open, self.selected, self.highlighted, self.scroll);
+        match event {
+            Event::Pointer(x, y, pointer) => {
+                let list = self.list_region(ctx);
+                let over_list = self.open && list.contains(x, y);
+                match pointer {
+                    Pointer::MouseClick {
+                        button,
+                        pressed: true,
+                        ..
+                    } if button.is_left() => {
+                        if over_list {
+                            if let Some(index) = self.index_at(y - list.y) {
+                                self.select(ctx, index);
+                            }
+                            self.close();
+                        } else if self.contains(x, y) {
+                            self.focused = true;
+                            if self.open {
+                                self.close();
+                            } else {
+                                self.open();
+                            }
+                        } else {
+                            self.focused = false;
+                            self.close();
+                        }
+                    }
+                    Pointer::Hover if over_list => {
+                        self.highlighted = self.index_at(y - list.y);
+                    }
+                    Pointer::Scroll {
+                        orientation: Orientation::Vertical,
+                        value,
+                    } if over_list => {
+                        self.set_scroll(self.scroll + value);
+                        self.highlighted = self.index_at(y - list.y);
+                    }
+                    _ => {}
+                }
+            }
+            Event::Keyboard(key) if self.focused && key.pressed => self.keyboard(ctx, key),
+            // The compositor closed the list, like after a click outside of the window
+            Event::PopupDone(owner) if owner == self.popup => self.close(),
+            _ => {}
+        }
+        let changed = state != (self.open, self.selected, self.highlighted, self.scroll);
+        if self.popups {
+            if self.open && (changed || damage.is_some()) {
+                let (x, y) = self.origin;
+                let (width, height) = (self.width(), self.row_height());
+                ctx.show_popup(
+                    self.popup,
+                    Popup {
+                        anchor: Region::new(x, y, width, height),
+                        region: Region::new(x, y + height, width, self.list_height()),
+                        node: self.list_node(0., 0.),
+                    },
+                );
+            } else if !self.open && state.0 {
+                ctx.hide_popup(self.popup);
+            }
+            if state.1 != self.selected {
+                damage = damage.max(Damage::Some);
+            }
+        } else if changed {
+            damage = damage.max(Damage::Some);
+        }
+        damage
+    }
+}
+
+impl<M: TryIntoMessage<usize>> Style for Dropdown<M> {
+    fn set_background<B: Into<scene::Background>>(&mut self, background: B) {
+        self.frame.set_background(background);
+    }
+    fn set_border(&mut self, color: u32, width: f32) {
+        self.frame.set_border(color, width);
+    }
+    fn set_border_color(&mut self, color: u32) {
+        self.frame.set_border_color(color);
+    }
+    fn set_radius(&mut self, tl: f32, tr: f32, br: f32, bl: f32) {
+        self.frame.set_radius(tl, tr, br, bl);
+    }
+    fn set_border_size(&mut self, size: f32) {
+        self.frame.set_border_size(size);
+    }
+    fn background<B: Into<scene::Background>>(mut self, background: B) -> Self {
+        self.set_background(background);
+        self
+    }
+    fn border(mut self, color: u32, size: f32) -> Self {
+        self.set_border(color, size);
+        self
+    }
+    fn border_color(mut self, color: u32) -> Self {
+        self.set_border_color(color);
+        self
+    }
+    fn border_size(mut self, size: f32) -> Self {
+        self.set_border_size(size);
+        self
+    }
+    fn radius(mut self, tl: f32, tr: f32, br: f32, bl: f32) -> Self {
+        self.set_radius(tl, tr, br, bl);
+        self
+    }
+}
@@ -0,0 +1,651 @@
+use crate::controller::*;
+use crate::widgets::entry::{
+    byte_index, next_word, previous_word, BLINK, CARET_WIDTH, DOUBLE_CLICK,
+};
+use crate::widgets::text::{Label, Text};
+use crate::*;
+use std::ops::Deref;
+
+// Maximum number of edits that can be undone
+const HISTORY: usize = 100;
+
+// A line as it is displayed after wrapping.
+// start and end are char indices in the text of the editor.
+struct Line {
+    start: usize,
+    end: usize,
+    text: Text,
+    advances: Vec<f32>,
+}
+
+// A change to the text which can be undone.
+// cursor and anchor are the positions before the edit.
+struct Edit {
+    index: usize,
+    deleted: String,
+    inserted: String,
+    cursor: usize,
+    anchor: usize,
+}
+
+// Multi line text input.
+// Lines are wrapped to the width of the editor.
+// The message is sent with the content of the TextEditor when it's edited.
+pub struct TextEditor<M: TryIntoMessage<String>> {
+    text: String,
+    label: Label,
+    lines: Vec<Line>,
+    line_height: f32,
+    cursor: usize,
+    anchor: usize,
+    // The x position vertical movements try to keep
+    column: Option<f32>,
+    scroll: f32,
+    width: f32,
+    height: f32,
+    focused: bool,
+    pressed: bool,
+    caret: bool,
+    blink: u32,
+    click: Option<u32>,
+    edited: bool,
+    undo: Vec<Edit>,
+    redo: Vec<Edit>,
+    selection: u32,
+    message: Option<M>,
+}
+
+impl<M: TryIntoMessage<String>> From<Label> for TextEditor<M> {
+    fn from(label: Label) -> Self {
+        let text = label.get_text().to_string();
+        let cursor = text.chars().count();
+        TextEditor {
+            line_height: label.get_font_size().ceil(),
+            label,
+            lines: Vec::new(),
+            cursor,
+            anchor: cursor,
+            column: None,
+            scroll: 0.,
+            width: 300.,
+            height: 200.,
+            focused: false,
+            pressed: false,
+            caret: true,
+            blink: 0,
+            click: None,
+            edited: true,
+            undo: Vec::new(),
+            redo: Vec::new(),
+            selection: style::BLU,
+            message: None,
+            text,
+        }
+    }
+}
+
+impl<M: TryIntoMessage<String>> From<&str> for TextEditor<M> {
+    fn from(text: &str) -> Self {
+        Label::default(text, 15.).into()
+    }
+}
+
+impl<M: TryIntoMessage<String>> TextEditor<M> {
+    pub fn new(width: f32, height: f32) -> Self {
+        let mut editor: Self = "".into();
+        editor.width = width;
+        editor.height = height;
+        editor
+    }
+    pub fn selection_color(mut self, color: u32) -> Self {
+        self.selection = color;
+        self
+    }
+    pub fn message(mut self, message: M) -> Self {
+        self.message = Some(message);
+        self
+    }
+    pub fn text(&self) -> &str {
+        self.text.as_str()
+    }
+    // Replaces the content and clears the history
+    pub fn set_text(&mut self, text: &str) {
+        self.text = text.to_string();
+        self.cursor = self.len();
+        self.anchor = self.cursor;
+        self.column = None;
+        self.undo.clear();
+        self.redo.clear();
+        self.edited = true;
+    }
+    pub fn selected_text(&self) -> &str {
+        let (start, end) = self.selection();
+        &self.text[self.byte(start)..self.byte(end)]
+    }
+    // The index of the character after the caret
+    pub fn cursor(&self) -> usize {
+        self.cursor
+    }
+    pub fn is_focused(&self) -> bool {
+        self.focused
+    }
+    pub fn set_focus(&mut self, focused: bool) {
+        self.focused = focused;
+        self.anchor = self.cursor;
+        self.reset_caret();
+    }
+    pub fn can_undo(&self) -> bool {
+        !self.undo.is_empty()
+    }
+    pub fn can_redo(&self) -> bool {
+        !self.redo.is_empty()
+    }
+    pub fn undo(&mut self) -> bool {
+        if let Some(edit) = self.undo.pop() {
+            let end = edit.index + edit.inserted.chars().count();
+            self.apply(edit.index, end, &edit.deleted);
+            self.cursor = edit.cursor;
+            self.anchor = edit.anchor;
+            self.redo.push(edit);
+            return true;
+        }
+        false
+    }
+    pub fn redo(&mut self) -> bool {
+        if let Some(edit) = self.redo.pop() {
+            let end = edit.index + edit.deleted.chars().count();
+            self.apply(edit.index, end, &edit.inserted);
+            self.undo.push(edit);
+            return true;
+        }
+        false
+    }
+    // The vertical scroll position
+    pub fn scroll(&self) -> f32 {
+        self.scroll
+    }
+    pub fn set_scroll(&mut self, scroll: f32) {
+        self.scroll = scroll.min(self.max_scroll()).max(0.);
+    }
+    fn len(&self) -> usize {
+        self.text.chars().count()
+    }
+    fn byte(&self, index: usize) -> usize {
+        byte_index(&self.text, index)
+    }
+    fn selection(&self) -> (usize, usize) {
+        (self.cursor.min(self.anchor), self.cursor.max(self.anchor))
+    }
+    fn reset_caret(&mut self) {
+        self.caret = true;
+        self.blink = 0;
+    }
+    fn max_scroll(&self) -> f32 {
+        (self.lines.len() as f32 * self.line_height - self.height).max(0.)
+    }
+    fn move_to(&mut self, index: usize, select: bool) {
+        self.cursor = index.min(self.len());
+        if !select {
+            self.anchor = self.cursor;
+        }
+    }
+    // Replaces the text between start and end without recording it
+    fn apply(&mut self, start: usize, end: usize, s: &str) {
+        let range = self.byte(start)..self.byte(end);
+        self.text.replace_range(range, s);
+        self.cursor = start + s.chars().count();
+        self.anchor = self.cursor;
+        self.column = None;
+        self.edited = true;
+    }
+    fn replace(&mut self, start: usize, end: usize, s: &str) {
+        if start == end && s.is_empty() {
+            return;
+        }
+        let deleted = self.text[self.byte(start)..self.byte(end)].to_string();
+        let edit = Edit {
+            index: start,
+            deleted,
+            inserted: s.to_string(),
+            cursor: self.cursor,
+            anchor: self.anchor,
+        };
+        self.apply(start, end, s);
+        self.redo.clear();
+        if let Some(last) = self.undo.last_mut() {
+            // Typing and deleting characters one by one is undone word by word
+            let word = |s: &str| s.chars().count() == 1 && !s.contains(char::is_whitespace);
+            if edit.deleted.is_empty()
+                && last.deleted.is_empty()
+                && word(&edit.inserted)
+                && last.index + last.inserted.chars().count() == edit.index
+                && !last.inserted.ends_with(char::is_whitespace)
+            {
+                last.inserted.push_str(&edit.inserted);
+                return;
+            }
+            if edit.inserted.is_empty() && last.inserted.is_empty() && word(&edit.deleted) {
+                if end == last.index {
+                    last.deleted.insert_str(0, &edit.deleted);
+                    last.index = start;
+                    return;
+                } else if start == last.index {
+                    last.deleted.push_str(&edit.deleted);
+                    return;
+                }
+            }
+        }
+        if self.undo.len() == HISTORY {
+            self.undo.remove(0);
+        }
+        self.undo.push(edit);
+    }
+    fn delete_selection(&mut self) -> bool {
+        let (start, end) = self.selection();
+        if start < end {
+            self.replace(start, end, "");
+            return true;
+        }
+        false
+    }
+    fn insert(&mut self, s: &str) {
+        let (start, end) = self.selection();
+        self.replace(start, end, s);
+    }
+    // Breaks the text into lines fitting the width of the editor
+    fn wrap<'d>(&mut self, ctx: &mut SyncContext<M>, event: Event<'d, M>) {
+        let width = self.width - CARET_WIDTH;
+        let mut lines = Vec::new();
+        let mut offset = 0;
+        for paragraph in self.text.split('\n') {
+            let chars: Vec<char> = paragraph.chars().collect();
+            let advances = ctx.font_cache().advances(&self.label, paragraph);
+            let mut start = 0;
+            loop {
+                let mut end = start;
+                let mut brk = None;
+                while end < chars.len() && advances[end + 1] - advances[start] <= width {
+                    if chars[end].is_whitespace() {
+                        brk = Some(end + 1);
+                    }
+                    end += 1;
+                }
+                if end < chars.len() {
+                    if chars[end].is_whitespace() {
+                        end += 1;
+                    } else if let Some(brk) = brk {
+                        end = brk;
+                    } else if end == start {
+                        end += 1;
+                    }
+                }
+                let mut text: Text = self.label.clone().into();
+                text.edit(&chars[start..end].iter().collect::<String>());
+                text.sync(ctx, event);
+                lines.push(Line {
+                    start: offset + start,
+                    end: offset + end,
+                    text,
+                    advances: advances[start..=end]
+                        .iter()
+                        .map(|advance| advance - advances[start])
+                        .collect(),
+                });
+                start = end;
+                if start >= chars.len() {
+                    break;
+                }
+            }
+            offset += chars.len() + 1;
+        }
+        self.lines = lines;
+    }
+    // A wrapped line doesn't end with a newline so its end is at the start of the next line
+    fn is_wrapped(&self, row: usize) -> bool {
+        self.lines
+            .get(row + 1)
+            .map(|next| next.start == self.lines[row].end)
+            .unwrap_or(false)
+    }
+    fn row_of(&self, index: usize) -> usize {
+        self.lines
+            .iter()
+            .enumerate()
+            .position(|(row, line)| {
+                index >= line.start
+                    && (index < line.end || (index == line.end && !self.is_wrapped(row)))
+            })
+            .unwrap_or(self.lines.len().saturating_sub(1))
+    }
+    fn column_of(&self, index: usize) -> f32 {
+        self.lines
+            .get(self.row_of(index))
+            .and_then(|line| line.advances.get(index - line.start))
+            .copied()
+            .unwrap_or(0.)
+    }
+    // The index of the caret position in the row the closest to x
+    fn index_at(&self, row: usize, x: f32) -> usize {
+        if let Some(line) = self.lines.get(row) {
+            let mut last = line.advances.len() - 1;
+            if self.is_wrapped(row) && last > 0 {
+                last -= 1;
+            }
+            let mut index = 0;
+            for (i, advance) in line.advances[..=last].iter().enumerate() {
+                if (advance - x).abs() < (line.advances[index] - x).abs() {
+                    index = i;
+                }
+            }
+            return line.start + index;
+        }
+        self.len()
+    }
+    fn row_at(&self, y: f32) -> usize {
+        (((y + self.scroll) / self.line_height).max(0.) as usize)
+            .min(self.lines.len().saturating_sub(1))
+    }
+    // Moves the caret by a number of rows while keeping its column
+    fn move_rows(&mut self, rows: isize, select: bool) {
+        let row = self.row_of(self.cursor);
+        let column = match self.column {
+            Some(column) => column,
+            None => self.column_of(self.cursor),
+        };
+        let target = row as isize + rows;
+        let index = if target < 0 {
+            0
+        } else if target as usize >= self.lines.len() {
+            self.len()
+        } else {
+            self.index_at(target as usize, column)
+        };
+        self.move_to(index, select);
+        self.column = Some(column);
+    }
+    fn scroll_to_cursor(&mut self) {
+        let top = self.row_of(self.cursor) as f32 * self.line_height;
+        if top < self.scroll {
+            self.scroll = top;
+        }
+        if top + self.line_height > self.scroll + self.height {
+            self.scroll = top + self.line_height - self.height;
+        }
+        self.scroll = self.scroll.min(self.max_scroll()).max(0.);
+    }
+    fn send(&self, ctx: &mut SyncContext<M>) {
+        if let Some(message) = self.message.as_ref() {
+            if let Ok(msg) = TryIntoMessage::try_into(message, self.text.clone()) {
+                let _ = ctx.send(msg);
+            }
+        }
+    }
+    fn pointer(&mut self, x: f32, y: f32, pointer: Pointer) -> Damage {
+        match pointer {
+            Pointer::MouseClick {
+                time,
+                button,
+                pressed,
+            } if button.is_left() => {
+                if pressed && self.contains(x, y) {
+                    let index = self.index_at(self.row_at(y), x);
+                    if self.focused
+                        && index == self.cursor
+                        && self
+                            .click
+                            .map(|click| time.wrapping_sub(click) < DOUBLE_CLICK)
+                            .unwrap_or(false)
+                    {
+                        self.anchor = previous_word(&self.text, index);
+                        self.cursor = next_word(&self.text, self.anchor);
+                        self.click = None;
+                    } else {
+                        self.move_to(index, false);
+                        self.click = Some(time);
+                        self.pressed = true;
+                    }
+                    self.column = None;
+                    self.focused = true;
+                    self.reset_caret();
+                    return Damage::Frame;
+                } else if pressed && self.focused {
+                    self.set_focus(false);
+                    return Damage::Some;
+                }
+                self.pressed = false;
+            }
+            Pointer::Hover if self.pressed => {
+                let index = self.index_at(self.row_at(y), x);
+                if index != self.cursor {
+                    self.move_to(index, true);
+                    self.column = None;
+                    self.reset_caret();
+                    self.scroll_to_cursor();
+                    return Damage::Some;
+                }
+            }
+            Pointer::Scroll {
+                orientation: Orientation::Vertical,
+                value,
+            } if self.contains(x, y) => {
+                let scroll = self.scroll;
+                self.set_scroll(scroll + value);
+                if scroll != self.scroll {
+                    return Damage::Some;
+                }
+            }
+            Pointer::Leave => self.pressed = false,
+            _ => {}
+        }
+        Damage::None
+    }
+    fn keyboard(&mut self, ctx: &mut SyncContext<M>, key: Key) -> Damage {
+        if !self.focused || !key.pressed {
+            return Damage::None;
+        }
+        if self.edited {
+            // The key may come before the first sync laid out the lines
+            self.edited = false;
+            self.line_height = ctx.font_cache().line_height(&self.label);
+            self.wrap(ctx, Event::Keyboard(key));
+        }
+        let Modifiers { ctrl, shift, .. } = key.modifiers;
+        let (start, end) = self.selection();
+        let column = self.column.take();
+        if key.is(keys::UP) || key.is(keys::DOWN) {
+            self.column = column;
+            let rows = if key.is(keys::UP) { -1 } else { 1 };
+            self.move_rows(rows, shift);
+        } else if key.is(keys::PAGE_UP) || key.is(keys::PAGE_DOWN) {
+            self.column = column;
+            let page = ((self.height / self.line_height) as isize).max(1);
+            let rows = if key.is(keys::PAGE_UP) { -page } else { page };
+            self.move_rows(rows, shift);
+        } else if key.is(keys::LEFT) {
+            let index = if ctrl {
+                previous_word(&self.text, self.cursor)
+            } else if start != end && !shift {
+                start
+            } else {
+                self.cursor.saturating_sub(1)
+            };
+            self.move_to(index, shift);
+        } else if key.is(keys::RIGHT) {
+            let index = if ctrl {
+                next_word(&self.text, self.cursor)
+            } else if start != end && !shift {
+                end
+            } else {
+                self.cursor + 1
+            };
+            self.move_to(index, shift);
+        } else if key.is(keys::HOME) {
+            let index = match ctrl {
+                true => 0,
+                false => self
+                    .lines
+                    .get(self.row_of(self.cursor))
+                    .map(|line| line.start)
+                    .unwrap_or(0),
+            };
+            self.move_to(index, shift);
+        } else if key.is(keys::END) {
+            let index = match ctrl {
+                true => self.len(),
+                false => {
+                    // The caret stays on a wrapped line before the character it was broken at
+                    let row = self.row_of(self.cursor);
+                    match self.lines.get(row) {
+                        Some(line) if self.is_wrapped(row) && line.end > line.start => line.end - 1,
+                        Some(line) => line.end,
+                        None => self.len(),
+                    }
+                }
+            };
+            self.move_to(index, shift);
+        } else if key.is(keys::BACKSPACE) {
+            if !self.delete_selection() {
+                let start = if ctrl {
+                    previous_word(&self.text, self.cursor)
+                } else {
+                    self.cursor.saturating_sub(1)
+                };
+                self.replace(start, self.cursor, "");
+            }
+        } else if key.is(keys::DELETE) {
+            if !self.delete_selection() {
+                let end = if ctrl {
+                    next_word(&self.text, self.cursor)
+                } else {
+                    (self.cursor + 1).min(self.len())
+                };
+                self.replace(self.cursor, end, "");
+            }
+        } else if key.is(keys::RETURN) || key.is(keys::KP_ENTER) {
+            self.insert("\n");
+        } else if key.is(keys::ESCAPE) {
+            self.set_focus(false);
+        } else if ctrl && key.is(keys::A) {
+            self.anchor = 0;
+            self.cursor = self.len();
+        } else if ctrl && (key.is(keys::Y) || key.is(keys::Z_UPPER) || (shift && key.is(keys::Z))) {
+            self.redo();
+        } else if ctrl && key.is(keys::Z) {
+            self.undo();
+        } else if let Some(utf8) = key.utf8 {
+            if ctrl || utf8.is_empty() || utf8.chars().any(char::is_control) {
+                self.column = column;
+                return Damage::None;
+            }
+            self.insert(utf8);
+        } else {
+            self.column = column;
+            return Damage::None;
+        }
+        self.reset_caret();
+        if self.edited {
+            self.send(ctx);
+        } else {
+            self.scroll_to_cursor();
+        }
+        Damage::Some
+    }
+}
+
+impl<M: TryIntoMessage<String>> Geometry for TextEditor<M> {
+    fn width(&self) -> f32 {
+        self.width
+    }
+    fn height(&self) -> f32 {
+        self.height
+    }
+    fn set_width(&mut self, width: f32) -> Result<(), f32> {
+        if width > 0. {
+            if width != self.width {
+                self.width = width;
+                self.edited = true;
+            }
+            return Ok(());
+        }
+        Err(self.width)
+    }
+    fn set_height(&mut self, height: f32) -> Result<(), f32> {
+        if height > 0. {
+            self.height = height;
+            self.set_scroll(self.scroll);
+            return Ok(());
+        }
+        Err(self.height)
+    }
+}
+
+impl<M: TryIntoMessage<String>> Widget<M> for TextEditor<M> {
+    fn create_node(&mut self, x: f32, y: f32) -> RenderNode {
+        let mut canvas = self.create_canvas(x, y);
+        let (start, end) = self.selection();
+        let first = (self.scroll / self.line_height) as usize;
+        let last = ((self.scroll + self.height) / self.line_height).ceil() as usize;
+        for (row, line) in self.lines.iter().enumerate().take(last).skip(first) {
+            let top = row as f32 * self.line_height - self.scroll;
+            if self.focused && start < end && start <= line.end && end > line.start {
+                let left = line.advances[start.max(line.start) - line.start];
+                let mut right = line.advances[end.min(line.end) - line.start];
+                // Shows the newline is part of the selection
+                if end > line.end && !self.is_wrapped(row) {
+                    right += self.line_height / 4.;
+                }
+                canvas.draw_rectangle(left, top, right - left, self.line_height, self.selection);
+            }
+            canvas.draw(0., top, line.text.deref().clone());
+        }
+        if self.focused && self.caret {
+            let row = self.row_of(self.cursor);
+            canvas.draw_rectangle(
+                self.column_of(self.cursor),
+                row as f32 * self.line_height - self.scroll,
+                CARET_WIDTH,
+                self.line_height,
+                self.label.get_color(),
+            );
+        }
+        canvas.finish()
+    }
+    fn sync<'d>(&'d mut self, ctx: &mut SyncContext<M>, event: Event<'d, M>) -> Damage {
+        let mut damage = match event {
+            Event::Pointer(x, y, pointer) => self.pointer(x, y, pointer),
+            Event::Keyboard(key) => self.keyboard(ctx, key),
+            Event::Callback(frame_time) => {
+                if self.focused {
+                    self.blink += frame_time;
+                    if self.blink >= BLINK {
+                        self.blink = 0;
+                        self.caret = !self.caret;
+                        // Keeps receiving frame callbacks for the caret to blink
+                        Damage::Frame
+                    } else {
+                        ctx.request_frame();
+                        Damage::None
+                    }
+                } else {
+                    Damage::None
+                }
+            }
+            _ => Damage::None,
+        };
+        if self.edited {
+            self.edited = false;
+            self.line_height = ctx.font_cache().line_height(&self.label);
+            self.wrap(ctx, event);
+            self.scroll_to_cursor();
+            damage = damage.max(Damage::Some);
+        }
+        damage
+    }
+}
+
+impl<M: TryIntoMessage<String>> Deref for TextEditor<M> {
+    type Target = Label;
+    fn deref(&self) -> &Self::Target {
+        &self.label
+    }
+}
@@ -0,0 +1,470 @@
+use crate::controller::*;
+use crate::widgets::text::{Label, Text};
+use crate::*;
+use std::ops::Deref;
+
+// Time in ms the caret stays visible or hidden
+pub(crate) const BLINK: u32 = 500;
+// Maximum delay in ms between the two clicks of a double click
+pub(crate) const DOUBLE_CLICK: u32 = 400;
+pub(crate) const CARET_WIDTH: f32 = 1.;
+
+// Byte offset of the character at the given index
+pub(crate) fn byte_index(text: &str, index: usize) -> usize {
+    text.char_indices()
+        .nth(index)
+        .map(|(byte, _)| byte)
+        .unwrap_or(text.len())
+}
+
+// Index of the start of the word before the given index
+pub(crate) fn previous_word(text: &str, index: usize) -> usize {
+    let chars: Vec<char> = text.chars().collect();
+    let mut index = index.min(chars.len());
+    while index > 0 && !chars[index - 1].is_alphanumeric() {
+        index -= 1;
+    }
+    while index > 0 && chars[index - 1].is_alphanumeric() {
+        index -= 1;
+    }
+    index
+}
+
+// Index of the end of the word after the given index
+pub(crate) fn next_word(text: &str, index: usize) -> usize {
+    let chars: Vec<char> = text.chars().collect();
+    let mut index = index.min(chars.len());
+    while index < chars.len() && !chars[index].is_alphanumeric() {
+        index += 1;
+    }
+    while index < chars.len() && chars[index].is_alphanumeric() {
+        index += 1;
+    }
+    index
+}
+
+// Single line text input.
+// The message is sent with the content of the Entry when it's edited
+// and the activate message when Return is pressed.
+pub struct Entry<M: TryIntoMessage<String>> {
+    text: String,
+    display: Text,
+    placeholder: Option<Text>,
+    mask: Option<char>,
+    advances: Vec<f32>,
+    cursor: usize,
+    anchor: usize,
+    scroll: f32,
+    width: f32,
+    height: f32,
+    focused: bool,
+    pressed: bool,
+    caret: bool,
+    blink: u32,
+    click: Option<u32>,
+    edited: bool,
+    selection: u32,
+    message: Option<M>,
+    activate: Option<M>,
+}
+
+impl<M: TryIntoMessage<String>> From<Label> for Entry<M> {
+    fn from(label: Label) -> Self {
+        let text = label.get_text().to_string();
+        let cursor = text.chars().count();
+        Entry {
+            height: label.get_font_size().ceil(),
+            display: label.into(),
+            placeholder: None,
+            mask: None,
+            advances: Vec::new(),
+            cursor,
+            anchor: cursor,
+            scroll: 0.,
+            width: 200.,
+            focused: false,
+            pressed: false,
+            caret: true,
+            blink: 0,
+            click: None,
+            edited: true,
+            selection: style::BLU,
+            message: None,
+            activate: None,
+            text,
+        }
+    }
+}
+
+impl<M: TryIntoMessage<String>> From<&str> for Entry<M> {
+    fn from(text: &str) -> Self {
+        Label::default(text, 15.).into()
+    }
+}
+
+impl<M: TryIntoMessage<String>> Entry<M> {
+    pub fn new(width: f32) -> Self {
+        let mut entry: Self = "".into();
+        entry.width = width;
+        entry
+    }
+    pub fn placeholder(mut self, placeholder: &str) -> Self {
+        let mut label = self.display.deref().clone();
+        let mut source = label.get_color();
+        source.apply_opacity(0.5);
+        label.set_source(source);
+        let mut text: Text = label.into();
+        text.edit(placeholder);
+        self.placeholder = Some(text);
+        self
+    }
+    // Hides the characters behind the mask
+    pub fn mask(mut self, mask: char) -> Self {
+        self.mask = Some(mask);
+        self.edited = true;
+        self
+    }
+    pub fn password(self) -> Self {
+        self.mask('•')
+    }
+    pub fn selection_color(mut self, color: u32) -> Self {
+        self.selection = color;
+        self
+    }
+    pub fn message(mut self, message: M) -> Self {
+        self.message = Some(message);
+        self
+    }
+    pub fn activate(mut self, message: M) -> Self {
+        self.activate = Some(message);
+        self
+    }
+    pub fn text(&self) -> &str {
+        self.text.as_str()
+    }
+    pub fn set_text(&mut self, text: &str) {
+        self.text = text.to_string();
+        self.cursor = self.len();
+        self.anchor = self.cursor;
+        self.edited = true;
+    }
+    pub fn selected_text(&self) -> &str {
+        let (start, end) = self.selection();
+        &self.text[self.byte(start)..self.byte(end)]
+    }
+    // The index of the character after the caret
+    pub fn cursor(&self) -> usize {
+        self.cursor
+    }
+    pub fn is_focused(&self) -> bool {
+        self.focused
+    }
+    pub fn set_focus(&mut self, focused: bool) {
+        self.focused = focused;
+        self.anchor = self.cursor;
+        self.reset_caret();
+    }
+    fn len(&self) -> usize {
+        self.text.chars().count()
+    }
+    fn byte(&self, index: usize) -> usize {
+        byte_index(&self.text, index)
+    }
+    fn selection(&self) -> (usize, usize) {
+        (self.cursor.min(self.anchor), self.cursor.max(self.anchor))
+    }
+    fn reset_caret(&mut self) {
+        self.caret = true;
+        self.blink = 0;
+    }
+    fn move_to(&mut self, index: usize, select: bool) {
+        self.cursor = index.min(self.len());
+        if !select {
+            self.anchor = self.cursor;
+        }
+    }
+    // Word boundaries aren't revealed when the text is masked
+    fn previous_word(&self, index: usize) -> usize {
+        match self.mask {
+            Some(_) => 0,
+            None => previous_word(&self.text, index),
+        }
+    }
+    fn next_word(&self, index: usize) -> usize {
+        match self.mask {
+            Some(_) => self.len(),
+            None => next_word(&self.text, index),
+        }
+    }
+    fn delete(&mut self, start: usize, end: usize) -> bool {
+        if start < end {
+            let range = self.byte(start)..self.byte(end);
+            self.text.replace_range(range, "");
+            self.cursor = start;
+            self.anchor = start;
+            self.edited = true;
+            return true;
+        }
+        false
+    }
+    fn delete_selection(&mut self) -> bool {
+        let (start, end) = self.selection();
+        self.delete(start, end)
+    }
+    fn insert(&mut self, s: &str) {
+        self.delete_selection();
+        let byte = self.byte(self.cursor);
+        self.text.insert_str(byte, s);
+        self.cursor += s.chars().count();
+        self.anchor = self.cursor;
+        self.edited = true;
+    }
+    // The index of the caret position the closest to x
+    fn index_at(&self, x: f32) -> usize {
+        let x = x + self.scroll;
+        let mut index = 0;
+        for (i, advance) in self.advances.iter().enumerate() {
+            if (advance - x).abs() < (self.advances[index] - x).abs() {
+                index = i;
+            }
+        }
+        index
+    }
+    fn scroll_to_cursor(&mut self) {
+        let caret = self.advances.get(self.cursor).copied().unwrap_or(0.);
+        let total = self.advances.last().copied().unwrap_or(0.);
+        if caret - self.scroll > self.width - CARET_WIDTH {
+            self.scroll = caret - self.width + CARET_WIDTH;
+        }
+        if caret < self.scroll {
+            self.scroll = caret;
+        }
+        self.scroll = self
+            .scroll
+            .min((total + CARET_WIDTH - self.width).max(0.))
+            .max(0.);
+    }
+    fn send(&self, ctx: &mut SyncContext<M>, message: Option<&M>) {
+        if let Some(message) = message {
+            if let Ok(msg) = TryIntoMessage::try_into(message, self.text.clone()) {
+                let _ = ctx.send(msg);
+            }
+        }
+    }
+    fn pointer(&mut self, x: f32, y: f32, pointer: Pointer) -> Damage {
+        match pointer {
+            Pointer::MouseClick {
+                time,
+                button,
+                pressed,
+            } if button.is_left() => {
+                if pressed && self.contains(x, y) {
+                    let index = self.index_at(x);
+                    if self.focused
+                        && index == self.cursor
+                        && self
+                            .click
+                            .map(|click| time.wrapping_sub(click) < DOUBLE_CLICK)
+                            .unwrap_or(false)
+                    {
+                        self.anchor = self.previous_word(index);
+                        self.cursor = self.next_word(self.anchor);
+                        self.click = None;
+                    } else {
+                        self.move_to(index, false);
+                        self.click = Some(time);
+                        self.pressed = true;
+                    }
+                    self.focused = true;
+                    self.reset_caret();
+                    return Damage::Frame;
+                } else if pressed && self.focused {
+                    self.set_focus(false);
+                    return Damage::Some;
+                }
+                self.pressed = false;
+            }
+            Pointer::Hover if self.pressed => {
+                let index = self.index_at(x);
+                if index != self.cursor {
+                    self.move_to(index, true);
+                    self.reset_caret();
+                    return Damage::Some;
+                }
+            }
+            Pointer::Leave => self.pressed = false,
+            _ => {}
+        }
+        Damage::None
+    }
+    fn keyboard(&mut self, ctx: &mut SyncContext<M>, key: Key) -> Damage {
+        if !self.focused || !key.pressed {
+            return Damage::None;
+        }
+        let Modifiers { ctrl, shift, .. } = key.modifiers;
+        let (start, end) = self.selection();
+        if key.is(keys::LEFT) {
+            let index = if ctrl {
+                self.previous_word(self.cursor)
+            } else if start != end && !shift {
+                start
+            } else {
+                self.cursor.saturating_sub(1)
+            };
+            self.move_to(index, shift);
+        } else if key.is(keys::RIGHT) {
+            let index = if ctrl {
+                self.next_word(self.cursor)
+            } else if start != end && !shift {
+                end
+            } else {
+                self.cursor + 1
+            };
+            self.move_to(index, shift);
+        } else if key.is(keys::HOME) {
+            self.move_to(0, shift);
+        } else if key.is(keys::END) {
+            self.move_to(self.len(), shift);
+        } else if key.is(keys::BACKSPACE) {
+            if !self.delete_selection() {
+                let start = if ctrl {
+                    self.previous_word(self.cursor)
+                } else {
+                    self.cursor.saturating_sub(1)
+                };
+                self.delete(start, self.cursor);
+            }
+        } else if key.is(keys::DELETE) {
+            if !self.delete_selection() {
+                let end = if ctrl {
+                    self.next_word(self.cursor)
+                } else {
+                    (self.cursor + 1).min(self.len())
+                };
+                self.delete(self.cursor, end);
+            }
+        } else if key.is(keys::RETURN) || key.is(keys::KP_ENTER) {
+            self.send(ctx, self.activate.as_ref());
+            return Damage::None;
+        } else if key.is(keys::ESCAPE) {
+            self.set_focus(false);
+        } else if ctrl && key.is(keys::A) {
+            self.anchor = 0;
+            self.cursor = self.len();
+        } else if let Some(utf8) = key.utf8 {
+            if ctrl || utf8.is_empty() || utf8.chars().any(char::is_control) {
+                return Damage::None;
+            }
+            self.insert(utf8);
+        } else {
+            return Damage::None;
+        }
+        self.reset_caret();
+        if self.edited {
+            self.send(ctx, self.message.as_ref());
+        }
+        Damage::Some
+    }
+}
+
+impl<M: TryIntoMessage<String>> Geometry for Entry<M> {
+    fn width(&self) -> f32 {
+        self.width
+    }
+    fn height(&self) -> f32 {
+        self.height
+    }
+    fn set_width(&mut self, width: f32) -> Result<(), f32> {
+        if width > 0. {
+            self.width = width;
+            return Ok(());
+        }
+        Err(self.width)
+    }
+    fn set_height(&mut self, _height: f32) -> Result<(), f32> {
+        Err(self.height)
+    }
+}
+
+impl<M: TryIntoMessage<String>> Widget<M> for Entry<M> {
+    fn create_node(&mut self, x: f32, y: f32) -> RenderNode {
+        let mut canvas = self.create_canvas(x, y);
+        let (start, end) = self.selection();
+        if self.focused && start != end {
+            let left = self.advances.get(start).copied().unwrap_or(0.);
+            let right = self.advances.get(end).copied().unwrap_or(0.);
+            canvas.draw_rectangle(
+                left - self.scroll,
+                0.,
+                right - left,
+                self.height,
+                self.selection,
+            );
+        }
+        if self.text.is_empty() {
+            if let Some(placeholder) = self.placeholder.as_ref() {
+                canvas.draw(0., 0., placeholder.deref().clone());
+            }
+        } else {
+            canvas.draw(-self.scroll, 0., self.display.deref().clone());
+        }
+        if self.focused && self.caret {
+            let caret = self.advances.get(self.cursor).copied().unwrap_or(0.);
+            canvas.draw_rectangle(
+                caret - self.scroll,
+                0.,
+                CARET_WIDTH,
+                self.height,
+                self.display.get_color(),
+            );
+        }
+        canvas.finish()
+    }
+    fn sync<'d>(&'d mut self, ctx: &mut SyncContext<M>, event: Event<'d, M>) -> Damage {
+        let mut damage = match event {
+            Event::Pointer(x, y, pointer) => self.pointer(x, y, pointer),
+            Event::Keyboard(key) => self.keyboard(ctx, key),
+            Event::Callback(frame_time) => {
+                if self.focused {
+                    self.blink += frame_time;
+                    if self.blink >= BLINK {
+                        self.blink = 0;
+                        self.caret = !self.caret;
+                        // Keeps receiving frame callbacks for the caret to blink
+                        Damage::Frame
+                    } else {
+                        ctx.request_frame();
+                        Damage::None
+                    }
+                } else {
+                    Damage::None
+                }
+            }
+            _ => Damage::None,
+        };
+        if self.edited {
+            self.edited = false;
+            let display = match self.mask {
+                Some(mask) => std::iter::repeat_n(mask, self.len()).collect(),
+                None => self.text.clone(),
+            };
+            self.display.edit(&display);
+            self.advances = ctx.font_cache().advances(&self.display, &display);
+            self.height = ctx.font_cache().line_height(&self.display);
+            damage = damage.max(Damage::Some);
+        }
+        self.scroll_to_cursor();
+        damage = damage.max(self.display.sync(ctx, event));
+        if let Some(placeholder) = self.placeholder.as_mut() {
+            damage = damage.max(placeholder.sync(ctx, event));
+        }
+        damage
+    }
+}
+
+impl<M: TryIntoMessage<String>> Deref for Entry<M> {
+    type Target = Label;
+    fn deref(&self) -> &Self::Target {
+        self.display.deref()
+    }
+}
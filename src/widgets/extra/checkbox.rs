@@ -0,0 +1,197 @@
+use crate::controller::*;
+use crate::scene::Instruction;
+use crate::widgets::shapes::{Rectangle, ShapeStyle, Style};
+use crate::*;
+
+#[derive(Clone, Debug, Copy, PartialEq, Eq)]
+pub enum CheckState {
+    Checked,
+    Unchecked,
+    // Neither checked or unchecked, like a group partially checked
+    Indeterminate,
+}
+
+pub struct Checkbox<M: TryIntoMessage<CheckState>> {
+    frame: Rectangle,
+    mark: u32,
+    state: CheckState,
+    focused: bool,
+    message: Option<M>,
+}
+
+impl<M: TryIntoMessage<CheckState>> Geometry for Checkbox<M> {
+    fn width(&self) -> f32 {
+        self.frame.width()
+    }
+    fn height(&self) -> f32 {
+        self.frame.height()
+    }
+    fn set_width(&mut self, width: f32) -> Result<(), f32> {
+        self.frame.set_width(width)
+    }
+    fn set_height(&mut self, height: f32) -> Result<(), f32> {
+        self.frame.set_height(height)
+    }
+}
+
+impl<M: TryIntoMessage<CheckState>> Widget<M> for Checkbox<M> {
+    fn create_node(&mut self, x: f32, y: f32) -> RenderNode {
+        let (width, height) = (self.width(), self.height());
+        let (dx, dy) = ((width / 4.).floor(), (height / 4.).floor());
+        let mark = match self.state {
+            CheckState::Checked => {
+                let radius = self.frame.get_radius().0 / 2.;
+                Instruction::new(
+                    x + dx,
+                    y + dy,
+                    Rectangle::new(
+                        width - 2. * dx,
+                        height - 2. * dy,
+                        ShapeStyle::solid(self.mark),
+                    )
+                    .radius(radius, radius, radius, radius),
+                )
+            }
+            CheckState::Indeterminate => {
+                let thickness = (height / 6.).round().max(1.);
+                Instruction::new(
+                    x + dx,
+                    y + ((height - thickness) / 2.).floor(),
+                    Rectangle::new(width - 2. * dx, thickness, ShapeStyle::solid(self.mark)),
+                )
+            }
+            CheckState::Unchecked => {
+                Instruction::empty(x + dx, y + dy, width - 2. * dx, height - 2. * dy)
+            }
+        };
+        RenderNode::Extension {
+            background: Instruction::new(x, y, self.frame.clone()),
+            border: None,
+            node: Box::new(mark.into()),
+        }
+    }
+    fn sync<'d>(&'d mut self, ctx: &mut SyncContext<M>, event: Event<'d, M>) -> Damage {
+        match event {
+            Event::Pointer(
+                x,
+                y,
+                Pointer::MouseClick {
+                    button, pressed, ..
+                },
+            ) if button.is_left() && pressed => {
+                self.focused = self.contains(x, y);
+                if self.focused {
+                    return self.toggle(ctx);
+                }
+            }
+            Event::Keyboard(key)
+                if self.focused
+                    && key.pressed
+                    && (key.is(keys::SPACE) || key.is(keys::RETURN) || key.is(keys::KP_ENTER)) =>
+            {
+                return self.toggle(ctx);
+            }
+            _ => {}
+        }
+        Damage::None
+    }
+}
+
+impl<M: TryIntoMessage<CheckState>> Default for Checkbox<M> {
+    fn default() -> Self {
+        Self {
+            frame: Rectangle::empty(16., 16.)
+                .background(style::BG2)
+                .radius(3., 3., 3., 3.),
+            mark: style::FG0,
+            state: CheckState::Unchecked,
+            focused: false,
+            message: None,
+        }
+    }
+}
+
+impl<M: TryIntoMessage<CheckState>> Checkbox<M> {
+    pub fn new(state: CheckState) -> Self {
+        Self {
+            state,
+            ..Default::default()
+        }
+    }
+    pub fn message(mut self, message: M) -> Self {
+        self.message = Some(message);
+        self
+    }
+    // Color of the check mark
+    pub fn mark(mut self, color: u32) -> Self {
+        self.mark = color;
+        self
+    }
+    pub fn set_mark(&mut self, color: u32) {
+        self.mark = color;
+    }
+    pub fn state(&self) -> CheckState {
+        self.state
+    }
+    // Changes the state without sending the message
+    pub fn set_state(&mut self, state: CheckState) {
+        self.state = state;
+    }
+    pub fn is_focused(&self) -> bool {
+        self.focused
+    }
+    pub fn set_focus(&mut self, focused: bool) {
+        self.focused = focused;
+    }
+    // An indeterminate checkbox becomes checked
+    fn toggle(&mut self, ctx: &mut SyncContext<M>) -> Damage {
+        self.state = match self.state {
+            CheckState::Checked => CheckState::Unchecked,
+            CheckState::Unchecked | CheckState::Indeterminate => CheckState::Checked,
+        };
+        if let Some(msg) = self.message.as_ref() {
+            if let Ok(msg) = TryIntoMessage::try_into(msg, self.state) {
+                let _ = ctx.send(msg);
+            }
+        }
+        Damage::Some
+    }
+}
+
+impl<M: TryIntoMessage<CheckState>> Style for Checkbox<M> {
+    fn set_background<B: Into<scene::Background>>(&mut self, background: B) {
+        self.frame.set_background(background);
+    }
+    fn set_border(&mut self, color: u32, width: f32) {
+        self.frame.set_border(color, width);
+    }
+    fn set_border_color(&mut self, color: u32) {
+        self.frame.set_border_color(color);
+    }
+    fn set_radius(&mut self, tl: f32, tr: f32, br: f32, bl: f32) {
+        self.frame.set_radius(tl, tr, br, bl);
+    }
+    fn set_border_size(&mut self, size: f32) {
+        self.frame.set_border_size(size);
+    }
+    fn background<B: Into<scene::Background>>(mut self, background: B) -> Self {
+        self.set_background(background);
+        self
+    }
+    fn border(mut self, color: u32, size: f32) -> Self {
+        self.set_border(color, size);
+        self
+    }
+    fn border_color(mut self, color: u32) -> Self {
+        self.set_border_color(color);
+        self
+    }
+    fn border_size(mut self, size: f32) -> Self {
+        self.set_border_size(size);
+        self
+    }
+    fn radius(mut self, tl: f32, tr: f32, br: f32, bl: f32) -> Self {
+        self.set_radius(tl, tr, br, bl);
+        self
+    }
+}
@@ -1,3 +1,5 @@
+pub mod checkbox;
+pub mod radio;
 pub mod switch;
 
 use std::f32::consts::{FRAC_PI_2, PI};
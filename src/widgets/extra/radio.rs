@@ -0,0 +1,271 @@
+use crate::controller::*;
+use crate::scene::{Instruction, Region};
+use crate::widgets::shapes::{Rectangle, ShapeStyle, Style};
+use crate::widgets::text::Label;
+use crate::*;
+
+// Space between a button and its label
+const GAP: f32 = 6.;
+
+// A group of labeled radio buttons where only one option can be selected.
+// The message is sent with the index of the selected option.
+pub struct RadioGroup<M: TryIntoMessage<usize>> {
+    options: Vec<Label>,
+    button: Rectangle,
+    dot: u32,
+    spacing: f32,
+    orientation: Orientation,
+    selected: Option<usize>,
+    focused: bool,
+    message: Option<M>,
+}
+
+impl<M: TryIntoMessage<usize>> Default for RadioGroup<M> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<M: TryIntoMessage<usize>> RadioGroup<M> {
+    pub fn new() -> Self {
+        Self {
+            options: Vec::new(),
+            button: Rectangle::empty(16., 16.)
+                .background(style::BG2)
+                .radius(8., 8., 8., 8.),
+            dot: style::FG0,
+            spacing: 4.,
+            orientation: Orientation::Vertical,
+            selected: None,
+            focused: false,
+            message: None,
+        }
+    }
+    pub fn option<L: Into<Label>>(mut self, label: L) -> Self {
+        self.push(label);
+        self
+    }
+    pub fn push<L: Into<Label>>(&mut self, label: L) {
+        self.options.push(label.into());
+    }
+    pub fn message(mut self, message: M) -> Self {
+        self.message = Some(message);
+        self
+    }
+    pub fn orientation(mut self, orientation: Orientation) -> Self {
+        self.orientation = orientation;
+        self
+    }
+    pub fn spacing(mut self, spacing: f32) -> Self {
+        self.spacing = spacing;
+        self
+    }
+    pub fn set_spacing(&mut self, spacing: f32) {
+        self.spacing = spacing;
+    }
+    // Color of the dot of the selected option
+    pub fn dot(mut self, color: u32) -> Self {
+        self.dot = color;
+        self
+    }
+    pub fn set_dot(&mut self, color: u32) {
+        self.dot = color;
+    }
+    pub fn len(&self) -> usize {
+        self.options.len()
+    }
+    pub fn is_empty(&self) -> bool {
+        self.options.is_empty()
+    }
+    pub fn selected(&self) -> Option<usize> {
+        self.selected
+    }
+    // Selects the option without sending the message
+    pub fn set_selected(&mut self, selected: Option<usize>) {
+        self.selected = selected.filter(|index| *index < self.options.len());
+    }
+    pub fn is_focused(&self) -> bool {
+        self.focused
+    }
+    pub fn set_focus(&mut self, focused: bool) {
+        self.focused = focused;
+    }
+    fn option_size(&self, label: &Label) -> (f32, f32) {
+        (
+            self.button.width() + GAP + label.width(),
+            self.button.height().max(label.height()),
+        )
+    }
+    // The region of each option
+    fn regions(&self) -> impl Iterator<Item = Region> + '_ {
+        let mut offset = 0.;
+        self.options.iter().map(move |label| {
+            let (width, height) = self.option_size(label);
+            let region = match self.orientation {
+                Orientation::Horizontal => Region::new(offset, 0., width, height),
+                Orientation::Vertical => Region::new(0., offset, width, height),
+            };
+            offset += self.spacing
+                + match self.orientation {
+                    Orientation::Horizontal => width,
+                    Orientation::Vertical => height,
+                };
+            region
+        })
+    }
+    fn select(&mut self, ctx: &mut SyncContext<M>, index: usize) -> Damage {
+        if index >= self.options.len() || self.selected == Some(index) {
+            return Damage::None;
+        }
+        self.selected = Some(index);
+        if let Some(msg) = self.message.as_ref() {
+            if let Ok(msg) = TryIntoMessage::try_into(msg, index) {
+                let _ = ctx.send(msg);
+            }
+        }
+        Damage::Some
+    }
+}
+
+impl<M: TryIntoMessage<usize>> Geometry for RadioGroup<M> {
+    fn width(&self) -> f32 {
+        self.regions()
+            .map(|region| region.x + region.width)
+            .fold(0., f32::max)
+    }
+    fn height(&self) -> f32 {
+        self.regions()
+            .map(|region| region.y + region.height)
+            .fold(0., f32::max)
+    }
+}
+
+impl<M: TryIntoMessage<usize>> Widget<M> for RadioGroup<M> {
+    fn create_node(&mut self, x: f32, y: f32) -> RenderNode {
+        let (width, height) = (self.button.width(), self.button.height());
+        let (dx, dy) = ((width / 4.).floor(), (height / 4.).floor());
+        let radius = (width - 2. * dx).min(height - 2. * dy) / 2.;
+        let nodes = self
+            .regions()
+            .zip(self.options.iter())
+            .enumerate()
+            .flat_map(|(i, (region, label))| {
+                let bx = x + region.x;
+                let by = y + region.y + ((region.height - height) / 2.).floor();
+                let dot = if self.selected == Some(i) {
+                    Instruction::new(
+                        bx + dx,
+                        by + dy,
+                        Rectangle::new(
+                            width - 2. * dx,
+                            height - 2. * dy,
+                            ShapeStyle::solid(self.dot),
+                        )
+                        .radius(radius, radius, radius, radius),
+                    )
+                } else {
+                    Instruction::empty(bx + dx, by + dy, width - 2. * dx, height - 2. * dy)
+                };
+                [
+                    RenderNode::Extension {
+                        background: Instruction::new(bx, by, self.button.clone()),
+                        border: None,
+                        node: Box::new(dot.into()),
+                    },
+                    Instruction::new(
+                        bx + width + GAP,
+                        y + region.y + ((region.height - label.height()) / 2.).floor(),
+                        label.clone(),
+                    )
+                    .into(),
+                ]
+            })
+            .collect();
+        RenderNode::Container {
+            region: Region::new(x, y, self.width(), self.height()),
+            nodes,
+        }
+    }
+    fn sync<'d>(&'d mut self, ctx: &mut SyncContext<M>, event: Event<'d, M>) -> Damage {
+        let mut damage = Damage::None;
+        for label in self.options.iter_mut() {
+            damage = damage.max(Widget::<M>::sync(label, ctx, event));
+        }
+        match event {
+            Event::Pointer(
+                x,
+                y,
+                Pointer::MouseClick {
+                    button, pressed, ..
+                },
+            ) if button.is_left() && pressed => {
+                let index = self.regions().position(|region| region.contains(x, y));
+                self.focused = index.is_some();
+                if let Some(index) = index {
+                    damage = damage.max(self.select(ctx, index));
+                }
+            }
+            Event::Keyboard(key) if self.focused && key.pressed && !self.options.is_empty() => {
+                let last = self.options.len() - 1;
+                let index = match self.selected {
+                    _ if key.is(keys::HOME) => 0,
+                    _ if key.is(keys::END) => last,
+                    Some(index) if key.is(keys::UP) || key.is(keys::LEFT) => {
+                        index.saturating_sub(1)
+                    }
+                    Some(index) if key.is(keys::DOWN) || key.is(keys::RIGHT) => {
+                        (index + 1).min(last)
+                    }
+                    None if [keys::UP, keys::DOWN, keys::LEFT, keys::RIGHT, keys::SPACE]
+                        .iter()
+                        .any(|keysym| key.is(*keysym)) =>
+                    {
+                        0
+                    }
+                    _ => return damage,
+                };
+                damage = damage.max(self.select(ctx, index));
+            }
+            _ => {}
+        }
+        damage
+    }
+}
+
+impl<M: TryIntoMessage<usize>> Style for RadioGroup<M> {
+    fn set_background<B: Into<scene::Background>>(&mut self, background: B) {
+        self.button.set_background(background);
+    }
+    fn set_border(&mut self, color: u32, width: f32) {
+        self.button.set_border(color, width);
+    }
+    fn set_border_color(&mut self, color: u32) {
+        self.button.set_border_color(color);
+    }
+    fn set_radius(&mut self, tl: f32, tr: f32, br: f32, bl: f32) {
+        self.button.set_radius(tl, tr, br, bl);
+    }
+    fn set_border_size(&mut self, size: f32) {
+        self.button.set_border_size(size);
+    }
+    fn background<B: Into<scene::Background>>(mut self, background: B) -> Self {
+        self.set_background(background);
+        self
+    }
+    fn border(mut self, color: u32, size: f32) -> Self {
+        self.set_border(color, size);
+        self
+    }
+    fn border_color(mut self, color: u32) -> Self {
+        self.set_border_color(color);
+        self
+    }
+    fn border_size(mut self, size: f32) -> Self {
+        self.set_border_size(size);
+        self
+    }
+    fn radius(mut self, tl: f32, tr: f32, br: f32, bl: f32) -> Self {
+        self.set_radius(tl, tr, br, bl);
+        self
+    }
+}
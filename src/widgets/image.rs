@@ -1,5 +1,7 @@
+use crate::widgets::Alignment;
 use crate::*;
 use image::io::Reader as ImageReader;
+use image::AnimationDecoder;
 use std::ops::DerefMut;
 use tiny_skia::*;
 
@@ -7,10 +9,32 @@ use scene::Instruction;
 use std::path::Path;
 use std::sync::Arc;
 
+// How the image is drawn in its box.
+// Images are stretched to their box unless told otherwise.
 #[derive(Clone, PartialEq, Debug)]
 pub enum Scale {
+    // Same as Cover
     Fill,
+    // The image takes the size of the box, same as Stretch
     Fit,
+    // The whole image fits in the box with its aspect ratio
+    Contain,
+    // The image covers the box with its aspect ratio and the rest is cropped
+    Cover,
+    // The image takes the size of the box
+    Stretch,
+    // The image keeps its size and is cropped to the box
+    None,
+    // The image is repeated over the box
+    Tile,
+    // The borders keep their size and the center is stretched to the box.
+    // The insets are in pixels of the image.
+    NinePatch {
+        top: f32,
+        right: f32,
+        bottom: f32,
+        left: f32,
+    },
 }
 
 #[derive(Clone)]
@@ -19,6 +43,7 @@ pub struct Image {
     width: u32,
     height: u32,
     scale: Scale,
+    align: (Alignment, Alignment),
     size: (u32, u32),
 }
 
@@ -26,6 +51,7 @@ impl PartialEq for Image {
     fn eq(&self, other: &Self) -> bool {
         self.image.as_ptr() == other.image.as_ptr()
             && self.scale == other.scale
+            && self.align == other.align
             && self.width == other.width
             && self.height == other.height
     }
@@ -39,6 +65,7 @@ impl Image {
             width,
             height,
             scale: Scale::Fit,
+            align: (Alignment::Center, Alignment::Center),
             size: (width, height),
         }
     }
@@ -53,6 +80,7 @@ impl Image {
             width,
             height,
             scale: Scale::Fit,
+            align: (Alignment::Center, Alignment::Center),
             size: (width, height),
         })
     }
@@ -72,22 +100,121 @@ impl Image {
             height,
             size,
             scale: Scale::Fit,
+            align: (Alignment::Center, Alignment::Center),
         })
     }
     pub fn fit(mut self, scale: Scale) -> Self {
         self.scale = scale;
         self
     }
+    pub fn set_scale(&mut self, scale: Scale) {
+        self.scale = scale;
+    }
+    // Where the image goes in its box when it doesn't fill it
+    pub fn align(mut self, horizontal: Alignment, vertical: Alignment) -> Self {
+        self.align = (horizontal, vertical);
+        self
+    }
+    pub fn set_align(&mut self, horizontal: Alignment, vertical: Alignment) {
+        self.align = (horizontal, vertical);
+    }
     pub fn scale(&self) -> (f32, f32) {
+        let (rx, ry) = (
+            self.width as f32 / self.size.0 as f32,
+            self.height as f32 / self.size.1 as f32,
+        );
         match &self.scale {
-            Scale::Fit => (
-                self.width as f32 / self.size.0 as f32,
-                self.height as f32 / self.size.1 as f32,
+            Scale::Contain => (rx.min(ry), rx.min(ry)),
+            Scale::Fill | Scale::Cover => (rx.max(ry), rx.max(ry)),
+            Scale::Fit | Scale::Stretch | Scale::NinePatch { .. } => (rx, ry),
+            Scale::None | Scale::Tile => (1., 1.),
+        }
+    }
+    // The position of the scaled image in its box
+    fn offset(&self) -> (f32, f32) {
+        let (sx, sy) = self.scale();
+        let offset = |align: &Alignment, space: f32| match align {
+            Alignment::Start => 0.,
+            Alignment::Center => (space / 2.).round(),
+            Alignment::End => space.round(),
+        };
+        (
+            offset(&self.align.0, self.width as f32 - self.size.0 as f32 * sx),
+            offset(&self.align.1, self.height as f32 - self.size.1 as f32 * sy),
+        )
+    }
+    // Draws the part of the image in the source region over the destination region
+    fn draw_patch(
+        &self,
+        dt: &mut PixmapMut,
+        source: (f32, f32, f32, f32),
+        dest: (f32, f32, f32, f32),
+        transform: Transform,
+        clip: Option<&ClipMask>,
+    ) {
+        let (x, y, width, height) = source;
+        let (dx, dy, dw, dh) = dest;
+        if width <= 0. || height <= 0. {
+            return;
+        }
+        if let Some(rect) = Rect::from_xywh(dx, dy, dw, dh) {
+            dt.fill_rect(
+                rect,
+                &Paint {
+                    shader: Pattern::new(
+                        self.pixmap(),
+                        SpreadMode::Pad,
+                        FilterQuality::Bilinear,
+                        1.0,
+                        Transform::from_translate(-x, -y)
+                            .post_scale(dw / width, dh / height)
+                            .post_translate(dx, dy),
+                    ),
+                    blend_mode: BlendMode::SourceOver,
+                    anti_alias: false,
+                    force_hq_pipeline: true,
+                },
+                transform,
+                clip,
+            );
+        }
+    }
+    fn draw_nine_patch(
+        &self,
+        dt: &mut PixmapMut,
+        insets: (f32, f32, f32, f32),
+        transform: Transform,
+        clip: Option<&ClipMask>,
+    ) {
+        let (top, right, bottom, left) = insets;
+        let (iw, ih) = (self.size.0 as f32, self.size.1 as f32);
+        let (width, height) = (self.width as f32, self.height as f32);
+        // The borders shrink when the box is too small for them
+        let fx = (width / (left + right)).min(1.);
+        let fy = (height / (top + bottom)).min(1.);
+        let columns = [
+            (0., left, 0., left * fx),
+            (
+                left,
+                iw - left - right,
+                left * fx,
+                width - (left + right) * fx,
+            ),
+            (iw - right, right, width - right * fx, right * fx),
+        ];
+        let rows = [
+            (0., top, 0., top * fy),
+            (
+                top,
+                ih - top - bottom,
+                top * fy,
+                height - (top + bottom) * fy,
             ),
-            Scale::Fill => {
-                let ratio = (self.width as f32 / self.size.0 as f32)
-                    .max(self.height as f32 / self.size.1 as f32);
-                (ratio, ratio)
+            (ih - bottom, bottom, height - bottom * fy, bottom * fy),
+        ];
+        for (y, h, dy, dh) in rows {
+            for (x, w, dx, dw) in columns {
+                self.draw_patch(dt, (x, y, w, h), (dx, dy, dw, dh), transform, clip);
             }
         }
     }
@@ -142,15 +269,52 @@ impl Primitive for Image {
         clip: Option<&tiny_skia::ClipMask>,
     ) {
         if let Backend::Pixmap(dt) = ctx.deref_mut() {
-            let (sx, sy) = self.scale();
-            dt.draw_pixmap(
-                0,
-                0,
-                PixmapRef::from_bytes(self.image.as_ref(), self.size.0, self.size.1).unwrap(),
-                &crate::context::PIX_PAINT,
-                transform.pre_scale(sx, sy),
-                clip,
-            );
+            let (width, height) = (self.width as f32, self.height as f32);
+            let (iw, ih) = (self.size.0 as f32, self.size.1 as f32);
+            match self.scale {
+                Scale::NinePatch {
+                    top,
+                    right,
+                    bottom,
+                    left,
+                } => self.draw_nine_patch(dt, (top, right, bottom, left), transform, clip),
+                Scale::Tile => {
+                    let (x, y) = self.offset();
+                    if let Some(rect) = Rect::from_xywh(0., 0., width, height) {
+                        dt.fill_rect(
+                            rect,
+                            &Paint {
+                                shader: Pattern::new(
+                                    self.pixmap(),
+                                    SpreadMode::Repeat,
+                                    FilterQuality::Nearest,
+                                    1.0,
+                                    Transform::from_translate(x, y),
+                                ),
+                                blend_mode: BlendMode::SourceOver,
+                                anti_alias: false,
+                                force_hq_pipeline: false,
+                            },
+                            transform,
+                            clip,
+                        );
+                    }
+                }
+                _ => {
+                    // The image is cropped to its box
+                    let (sx, sy) = self.scale();
+                    let (x, y) = self.offset();
+                    let (x0, y0) = (x.max(0.), y.max(0.));
+                    let (x1, y1) = ((x + iw * sx).min(width), (y + ih * sy).min(height));
+                    self.draw_patch(
+                        dt,
+                        ((x0 - x) / sx, (y0 - y) / sy, (x1 - x0) / sx, (y1 - y0) / sy),
+                        (x0, y0, x1 - x0, y1 - y0),
+                        transform,
+                        clip,
+                    );
+                }
+            }
         }
     }
 }
@@ -174,3 +338,300 @@ impl std::fmt::Debug for Image {
             .finish()
     }
 }
+
+// Shows the frames of an animated GIF or PNG one after the other.
+// Other formats are shown as a still image.
+// Animated WebP isn't supported by the decoder of the image crate,
+// loading one fails with an Unsupported error.
+pub struct AnimatedImage {
+    // The frames with their delay in ms
+    frames: Vec<(Image, u32)>,
+    index: usize,
+    elapsed: u32,
+    // The animation repeats forever if there's no loop count
+    loops: Option<u32>,
+    played: u32,
+    playing: bool,
+    // Tells if frame callbacks were requested
+    animating: bool,
+}
+
+impl AnimatedImage {
+    pub fn new(path: &Path) -> Result<Self, Box<dyn std::error::Error>> {
+        let data = std::fs::read(path)?;
+        let (frames, loops) = match image::guess_format(&data)? {
+            image::ImageFormat::Gif => (
+                image::codecs::gif::GifDecoder::new(data.as_slice())?
+                    .into_frames()
+                    .collect_frames()?,
+                gif_loop_count(&data),
+            ),
+            image::ImageFormat::Png => {
+                let decoder = image::codecs::png::PngDecoder::new(data.as_slice())?;
+                if decoder.is_apng() {
+                    (
+                        decoder.apng().into_frames().collect_frames()?,
+                        apng_loop_count(&data),
+                    )
+                } else {
+                    (Vec::new(), None)
+                }
+            }
+            image::ImageFormat::WebP if is_animated_webp(&data) => {
+                return Err(Box::new(image::ImageError::Unsupported(
+                    image::error::UnsupportedError::from_format_and_kind(
+                        image::ImageFormat::WebP.into(),
+                        image::error::UnsupportedErrorKind::GenericFeature("animation".to_string()),
+                    ),
+                )));
+            }
+            _ => (Vec::new(), None),
+        };
+        let frames: Vec<(Image, u32)> = if frames.is_empty() {
+            vec![(
+                Image::from_bgra(image::load_from_memory(&data)?.to_bgra8()),
+                0,
+            )]
+        } else {
+            frames
+                .into_iter()
+                .map(|frame| {
+                    let (numer, denom) = frame.delay().numer_denom_ms();
+                    let image = image::DynamicImage::ImageRgba8(frame.into_buffer()).to_bgra8();
+                    (Image::from_bgra(image), numer / denom.max(1))
+                })
+                .collect()
+        };
+        Ok(AnimatedImage {
+            frames,
+            index: 0,
+            elapsed: 0,
+            loops,
+            played: 0,
+            playing: true,
+            animating: false,
+        })
+    }
+    // Replaces the loop count of the file
+    pub fn loops(mut self, loops: Option<u32>) -> Self {
+        self.loops = loops;
+        self
+    }
+    pub fn fit(mut self, scale: Scale) -> Self {
+        for (image, _) in self.frames.iter_mut() {
+            image.scale = scale.clone();
+        }
+        self
+    }
+    pub fn align(mut self, horizontal: Alignment, vertical: Alignment) -> Self {
+        for (image, _) in self.frames.iter_mut() {
+            image.set_align(horizontal, vertical);
+        }
+        self
+    }
+    pub fn len(&self) -> usize {
+        self.frames.len()
+    }
+    pub fn is_empty(&self) -> bool {
+        self.frames.is_empty()
+    }
+    pub fn frame(&self) -> usize {
+        self.index
+    }
+    pub fn set_frame(&mut self, index: usize) {
+        if index < self.frames.len() {
+            self.index = index;
+            self.elapsed = 0;
+        }
+    }
+    pub fn is_playing(&self) -> bool {
+        self.playing
+    }
+    // The loops are counted again if the animation had finished
+    pub fn play(&mut self) {
+        if !self.playing {
+            if self
+                .loops
+                .map(|loops| self.played >= loops)
+                .unwrap_or(false)
+            {
+                self.played = 0;
+                self.index = 0;
+            }
+            self.playing = true;
+            self.animating = false;
+        }
+    }
+    pub fn pause(&mut self) {
+        self.playing = false;
+    }
+    // Moves through the frames as time goes
+    fn advance(&mut self, frame_time: u32) {
+        self.elapsed += frame_time;
+        while self.playing && self.elapsed >= self.delay() {
+            self.elapsed -= self.delay();
+            if self.index + 1 < self.frames.len() {
+                self.index += 1;
+            } else {
+                self.played += 1;
+                // The last frame stays once all the loops are played
+                if self
+                    .loops
+                    .map(|loops| self.played >= loops)
+                    .unwrap_or(false)
+                {
+                    self.playing = false;
+                } else {
+                    self.index = 0;
+                }
+            }
+        }
+    }
+    fn delay(&self) -> u32 {
+        // Like browsers, very short delays are slowed down
+        match self.frames[self.index].1 {
+            delay if delay <= 10 => 100,
+            delay => delay,
+        }
+    }
+}
+
+impl Image {
+    fn from_bgra(image: image::ImageBuffer<image::Bgra<u8>, Vec<u8>>) -> Self {
+        let (width, height) = image.dimensions();
+        Image::from_raw(image.into_raw(), width, height)
+    }
+}
+
+// Reads the number of times a GIF plays from its NETSCAPE2.0 extension.
+// The GIF plays forever if it's None.
+fn gif_loop_count(data: &[u8]) -> Option<u32> {
+    // The header and the logical screen descriptor
+    let flags = *data.get(10)?;
+    let mut i = 13;
+    if flags & 0x80 != 0 {
+        // The global color table
+        i += 3 << ((flags & 0x07) + 1);
+    }
+    // Skips the sub-blocks of data and returns the index after them
+    let skip = |mut i: usize| -> Option<usize> {
+        loop {
+            let len = *data.get(i)? as usize;
+            i += 1 + len;
+            if len == 0 {
+                return Some(i);
+            }
+        }
+    };
+    loop {
+        match *data.get(i)? {
+            // Extension
+            0x21 => {
+                let label = *data.get(i + 1)?;
+                let block = data.get(i + 2..)?;
+                if label == 0xFF
+                    && (block.starts_with(b"\x0bNETSCAPE2.0")
+                        || block.starts_with(b"\x0bANIMEXTS1.0"))
+                {
+                    let sub_block = block.get(12..16)?;
+                    if sub_block[0] == 3 && sub_block[1] == 1 {
+                        // How many times the animation repeats after the first time
+                        let repeat = u16::from_le_bytes([sub_block[2], sub_block[3]]) as u32;
+                        return (repeat > 0).then_some(repeat + 1);
+                    }
+                }
+                i = skip(i + 2)?;
+            }
+            // Image descriptor
+            0x2C => {
+                let flags = *data.get(i + 9)?;
+                i += 10;
+                if flags & 0x80 != 0 {
+                    // The local color table
+                    i += 3 << ((flags & 0x07) + 1);
+                }
+                // The LZW minimum code size comes before the image data
+                i = skip(i + 1)?;
+            }
+            // A GIF without the extension plays once
+            0x3B => return Some(1),
+            _ => return None,
+        }
+    }
+}
+
+// Reads the number of times an APNG plays from its acTL chunk.
+// The APNG plays forever if it's None.
+fn apng_loop_count(data: &[u8]) -> Option<u32> {
+    // After the signature
+    let mut i = 8;
+    loop {
+        let len = u32::from_be_bytes(data.get(i..i + 4)?.try_into().ok()?) as usize;
+        let kind = data.get(i + 4..i + 8)?;
+        // The acTL chunk comes before the image data
+        match kind {
+            b"acTL" => {
+                let plays = u32::from_be_bytes(data.get(i + 12..i + 16)?.try_into().ok()?);
+                return (plays > 0).then_some(plays);
+            }
+            b"IDAT" | b"IEND" => return None,
+            // The length, the type, the data and the CRC
+            _ => i += 12 + len,
+        }
+    }
+}
+
+// Tells if the animation flag of the VP8X chunk is set
+fn is_animated_webp(data: &[u8]) -> bool {
+    data.get(12..16) == Some(b"VP8X") && data.get(20).is_some_and(|flags| flags & 0x02 != 0)
+}
+
+impl Geometry for AnimatedImage {
+    fn width(&self) -> f32 {
+        self.frames[0].0.width()
+    }
+    fn height(&self) -> f32 {
+        self.frames[0].0.height()
+    }
+    fn set_width(&mut self, width: f32) -> Result<(), f32> {
+        for (image, _) in self.frames.iter_mut() {
+            image.set_width(width)?;
+        }
+        Ok(())
+    }
+    fn set_height(&mut self, height: f32) -> Result<(), f32> {
+        for (image, _) in self.frames.iter_mut() {
+            image.set_height(height)?;
+        }
+        Ok(())
+    }
+}
+
+impl<M> Widget<M> for AnimatedImage {
+    fn create_node(&mut self, x: f32, y: f32) -> RenderNode {
+        // Only the region of the image is damaged when the frame changes
+        RenderNode::Instruction(Instruction::new(x, y, self.frames[self.index].0.clone()))
+    }
+    fn sync<'d>(&'d mut self, _ctx: &mut SyncContext<M>, event: Event<'d, M>) -> Damage {
+        if !self.playing || self.frames.len() < 2 {
+            self.animating = false;
+            return Damage::None;
+        }
+        match event {
+            Event::Callback(frame_time) => {
+                self.advance(frame_time);
+                if self.playing {
+                    // Keeps the frame callbacks coming
+                    Damage::Frame
+                } else {
+                    Damage::Some
+                }
+            }
+            _ if !self.animating => {
+                self.animating = true;
+                Damage::Frame
+            }
+            _ => Damage::None,
+        }
+    }
+}
@@ -0,0 +1,423 @@
+use crate::controller::*;
+use crate::*;
+use scene::{Instruction, Region};
+use std::marker::PhantomData;
+use widgets::entry::DOUBLE_CLICK;
+use widgets::shapes::rectangle::Rectangle;
+use widgets::shapes::ShapeStyle;
+
+// The items shown by a ListView
+pub trait ListSource {
+    type Item;
+    fn len(&self) -> usize;
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+    fn get(&self, index: usize) -> Option<&Self::Item>;
+}
+
+impl<T> ListSource for Vec<T> {
+    type Item = T;
+    fn len(&self) -> usize {
+        self.len()
+    }
+    fn get(&self, index: usize) -> Option<&T> {
+        self.as_slice().get(index)
+    }
+}
+
+// Creates the rows of a ListView.
+// Rows scrolled out of the view are bound to the items scrolled into it.
+pub trait RowFactory<M, T> {
+    type Row: Widget<M>;
+    fn create(&mut self, item: &T) -> Self::Row;
+    // Updates the row in place to show the item
+    fn bind(&mut self, row: &mut Self::Row, item: &T);
+}
+
+// A pair of closures creating a row and binding it to an item
+impl<M, T, W, C, B> RowFactory<M, T> for (C, B)
+where
+    W: Widget<M>,
+    C: FnMut(&T) -> W,
+    B: FnMut(&mut W, &T),
+{
+    type Row = W;
+    fn create(&mut self, item: &T) -> W {
+        (self.0)(item)
+    }
+    fn bind(&mut self, row: &mut W, item: &T) {
+        (self.1)(row, item)
+    }
+}
+
+// A list of rows of the same height where only the visible rows exist.
+// The message is sent with the index of the selected item
+// and the activate message on Return or a double click.
+pub struct ListView<M, S, F>
+where
+    M: TryIntoMessage<usize>,
+    S: ListSource,
+    F: RowFactory<M, S::Item>,
+{
+    source: S,
+    factory: F,
+    // The visible rows with the index of their item
+    rows: Vec<(usize, F::Row)>,
+    // Rows waiting to be bound to an item
+    recycled: Vec<F::Row>,
+    width: f32,
+    height: f32,
+    row_height: f32,
+    scroll: f32,
+    selected: Option<usize>,
+    selection: u32,
+    focused: bool,
+    click: Option<(usize, u32)>,
+    message: Option<M>,
+    activate: Option<M>,
+    _request: PhantomData<M>,
+}
+
+impl<M, S, F> ListView<M, S, F>
+where
+    M: TryIntoMessage<usize>,
+    S: ListSource,
+    F: RowFactory<M, S::Item>,
+{
+    pub fn new(source: S, factory: F, width: f32, height: f32, row_height: f32) -> Self {
+        Self {
+            source,
+            factory,
+            rows: Vec::new(),
+            recycled: Vec::new(),
+            width,
+            height,
+            row_height: row_height.max(1.),
+            scroll: 0.,
+            selected: None,
+            selection: style::BLU,
+            focused: false,
+            click: None,
+            message: None,
+            activate: None,
+            _request: PhantomData,
+        }
+    }
+    pub fn selection_color(mut self, color: u32) -> Self {
+        self.selection = color;
+        self
+    }
+    pub fn message(mut self, message: M) -> Self {
+        self.message = Some(message);
+        self
+    }
+    pub fn activate(mut self, message: M) -> Self {
+        self.activate = Some(message);
+        self
+    }
+    pub fn source(&self) -> &S {
+        &self.source
+    }
+    // The rows are bound again since the items may have changed
+    pub fn source_mut(&mut self) -> &mut S {
+        self.recycle();
+        &mut self.source
+    }
+    pub fn set_source(&mut self, source: S) {
+        self.recycle();
+        self.source = source;
+        self.selected = self.selected.filter(|index| *index < self.source.len());
+        self.set_scroll(self.scroll);
+    }
+    pub fn selected(&self) -> Option<usize> {
+        self.selected
+    }
+    pub fn selected_item(&self) -> Option<&S::Item> {
+        self.source.get(self.selected?)
+    }
+    // Selects the item and scrolls to it without sending the message
+    pub fn set_selected(&mut self, selected: Option<usize>) {
+        self.selected = selected.filter(|index| *index < self.source.len());
+        if let Some(index) = self.selected {
+            let y = index as f32 * self.row_height;
+            self.set_scroll(self.scroll.min(y).max(y + self.row_height - self.height));
+        }
+    }
+    pub fn is_focused(&self) -> bool {
+        self.focused
+    }
+    pub fn set_focus(&mut self, focused: bool) {
+        self.focused = focused;
+    }
+    pub fn scroll(&self) -> f32 {
+        self.scroll
+    }
+    pub fn set_scroll(&mut self, scroll: f32) {
+        self.scroll = scroll.min(self.max_scroll()).max(0.).round();
+    }
+    fn max_scroll(&self) -> f32 {
+        (self.source.len() as f32 * self.row_height - self.height).max(0.)
+    }
+    fn recycle(&mut self) {
+        self.recycled
+            .extend(self.rows.drain(..).map(|(_, row)| row));
+    }
+    // The range of the items in the view
+    fn visible(&self) -> (usize, usize) {
+        let len = self.source.len();
+        let start = (self.scroll / self.row_height).floor() as usize;
+        let end = ((self.scroll + self.height) / self.row_height).ceil() as usize;
+        (start.min(len), end.min(len))
+    }
+    // Recycles the rows scrolled out of the view
+    // and binds them to the items scrolled into it.
+    // Returns true if the rows changed.
+    fn update_rows(&mut self) -> bool {
+        let (start, end) = self.visible();
+        if self.rows.iter().map(|(index, _)| *index).eq(start..end) {
+            return false;
+        }
+        let (rows, hidden): (Vec<_>, Vec<_>) = self
+            .rows
+            .drain(..)
+            .partition(|(index, _)| (start..end).contains(index));
+        self.recycled.extend(hidden.into_iter().map(|(_, row)| row));
+        let mut rows = rows.into_iter().peekable();
+        for index in start..end {
+            if let Some((_, row)) = rows.next_if(|(i, _)| *i == index) {
+                self.rows.push((index, row));
+            } else if let Some(item) = self.source.get(index) {
+                let mut row = match self.recycled.pop() {
+                    Some(mut row) => {
+                        self.factory.bind(&mut row, item);
+                        row
+                    }
+                    None => self.factory.create(item),
+                };
+                let _ = row.set_size(self.width, self.row_height);
+                self.rows.push((index, row));
+            }
+        }
+        true
+    }
+    fn index_at(&self, y: f32) -> Option<usize> {
+        let index = ((y + self.scroll) / self.row_height).floor();
+        if index >= 0. && (index as usize) < self.source.len() {
+            return Some(index as usize);
+        }
+        None
+    }
+    fn select(&mut self, ctx: &mut SyncContext<M>, index: usize) -> Damage {
+        let index = index.min(self.source.len().saturating_sub(1));
+        if self.source.is_empty() || self.selected == Some(index) {
+            return Damage::None;
+        }
+        self.set_selected(Some(index));
+        self.send(ctx, self.message.as_ref());
+        Damage::Some
+    }
+    fn send(&self, ctx: &mut SyncContext<M>, message: Option<&M>) {
+        if let (Some(message), Some(index)) = (message, self.selected) {
+            if let Ok(msg) = TryIntoMessage::try_into(message, index) {
+                let _ = ctx.send(msg);
+            }
+        }
+    }
+    fn pointer(&mut self, ctx: &mut SyncContext<M>, x: f32, y: f32, pointer: Pointer) -> Damage {
+        match pointer {
+            Pointer::MouseClick {
+                time,
+                button,
+                pressed: true,
+            } if button.is_left() => {
+                if !self.contains(x, y) {
+                    self.focused = false;
+                    return Damage::None;
+                }
+                self.focused = true;
+                if let Some(index) = self.index_at(y) {
+                    if self
+                        .click
+                        .map(|(i, click)| i == index && time.wrapping_sub(click) < DOUBLE_CLICK)
+                        .unwrap_or(false)
+                    {
+                        self.click = None;
+                        self.send(ctx, self.activate.as_ref());
+                    } else {
+                        self.click = Some((index, time));
+                    }
+                    return self.select(ctx, index);
+                }
+            }
+            Pointer::Scroll {
+                orientation: Orientation::Vertical,
+                value,
+            } if self.contains(x, y) => {
+                let scroll = self.scroll;
+                self.set_scroll(scroll + value);
+                if scroll != self.scroll {
+                    return Damage::Some;
+                }
+            }
+            _ => {}
+        }
+        Damage::None
+    }
+    fn keyboard(&mut self, ctx: &mut SyncContext<M>, key: Key) -> Damage {
+        if !self.focused || !key.pressed {
+            return Damage::None;
+        }
+        let page = ((self.height / self.row_height).floor() as usize).max(1);
+        let last = self.source.len().saturating_sub(1);
+        let index = match self.selected {
+            Some(index) => {
+                if key.is(keys::UP) {
+                    index.saturating_sub(1)
+                } else if key.is(keys::DOWN) {
+                    index + 1
+                } else if key.is(keys::PAGE_UP) {
+                    index.saturating_sub(page)
+                } else if key.is(keys::PAGE_DOWN) {
+                    index + page
+                } else if key.is(keys::HOME) {
+                    0
+                } else if key.is(keys::END) {
+                    last
+                } else {
+                    if key.is(keys::RETURN) || key.is(keys::KP_ENTER) {
+                        self.send(ctx, self.activate.as_ref());
+                    }
+                    return Damage::None;
+                }
+            }
+            None if key.is(keys::END) => last,
+            None if [
+                keys::UP,
+                keys::DOWN,
+                keys::PAGE_UP,
+                keys::PAGE_DOWN,
+                keys::HOME,
+            ]
+            .iter()
+            .any(|keysym| key.is(*keysym)) =>
+            {
+                0
+            }
+            None => return Damage::None,
+        };
+        self.select(ctx, index)
+    }
+}
+
+impl<M, S, F> Geometry for ListView<M, S, F>
+where
+    M: TryIntoMessage<usize>,
+    S: ListSource,
+    F: RowFactory<M, S::Item>,
+{
+    fn width(&self) -> f32 {
+        self.width
+    }
+    fn height(&self) -> f32 {
+        self.height
+    }
+    fn set_width(&mut self, width: f32) -> Result<(), f32> {
+        if width > 0. {
+            self.width = width;
+            for (_, row) in self.rows.iter_mut() {
+                let _ = row.set_width(width);
+            }
+            return Ok(());
+        }
+        Err(self.width)
+    }
+    fn set_height(&mut self, height: f32) -> Result<(), f32> {
+        if height > 0. {
+            self.height = height;
+            self.set_scroll(self.scroll);
+            return Ok(());
+        }
+        Err(self.height)
+    }
+}
+
+impl<M, S, F> Widget<M> for ListView<M, S, F>
+where
+    M: TryIntoMessage<usize>,
+    S: ListSource,
+    F: RowFactory<M, S::Item>,
+{
+    fn create_node(&mut self, x: f32, y: f32) -> RenderNode {
+        self.update_rows();
+        let region = Region::new(x, y, self.width, self.height);
+        let (width, row_height, scroll) = (self.width, self.row_height, self.scroll);
+        let selected = self.selected;
+        let selection = self.selection;
+        let nodes = self
+            .rows
+            .iter_mut()
+            .map(|(index, row)| {
+                let dy = *index as f32 * row_height - scroll;
+                RenderNode::Extension {
+                    background: if selected == Some(*index) {
+                        Instruction::new(
+                            x,
+                            y + dy,
+                            Rectangle::new(width, row_height, ShapeStyle::solid(selection)),
+                        )
+                    } else {
+                        Instruction::empty(x, y + dy, width, row_height)
+                    },
+                    border: None,
+                    node: Box::new(row.create_node(x, y + dy)),
+                }
+            })
+            .collect();
+        RenderNode::Clip {
+            region,
+            node: Box::new(RenderNode::Container { region, nodes }),
+        }
+    }
+    fn sync<'d>(&'d mut self, ctx: &mut SyncContext<M>, event: Event<'d, M>) -> Damage {
+        let scroll = self.scroll;
+        let mut damage = match event {
+            Event::Pointer(x, y, pointer) => self.pointer(ctx, x, y, pointer),
+            Event::Keyboard(key) => self.keyboard(ctx, key),
+            _ => Damage::None,
+        };
+        // Every row is moved when the list is scrolled
+        let moved = self.update_rows() || scroll != self.scroll;
+        let (row_height, scroll) = (self.row_height, self.scroll);
+        let inside = match event {
+            Event::Pointer(x, y, _) => self.contains(x, y),
+            _ => true,
+        };
+        for (index, row) in self.rows.iter_mut() {
+            let event = match event {
+                Event::Pointer(x, y, pointer) => {
+                    let y = y + scroll - *index as f32 * row_height;
+                    // Parts of the rows hidden by the view can't be hovered
+                    match pointer {
+                        Pointer::Hover if !inside => Event::Pointer(x, y, Pointer::Leave),
+                        Pointer::MouseClick { pressed: true, .. } | Pointer::Scroll { .. }
+                            if !inside =>
+                        {
+                            continue
+                        }
+                        _ => Event::Pointer(x, y, pointer),
+                    }
+                }
+                _ => event,
+            };
+            damage = damage.max(row.sync(ctx, event));
+        }
+        if moved && !event.is_redraw() {
+            for (_, row) in self.rows.iter_mut() {
+                damage = damage.max(row.sync(ctx, Event::Redraw));
+            }
+            damage = damage.max(Damage::Some);
+        }
+        damage
+    }
+}
@@ -0,0 +1,762 @@
+use crate::context::{Popup, PopupId};
+use crate::controller::*;
+use crate::scene::{Instruction, Region};
+use crate::widgets::shapes::{Rectangle, ShapeStyle, Style};
+use crate::widgets::text::Label;
+use crate::*;
+use std::marker::PhantomData;
+use std::ops::{Deref, DerefMut};
+
+// Space around the items
+const PADDING: f32 = 4.;
+// Space between the columns of the items
+const GAP: f32 = 12.;
+// Size of the check mark
+const MARK: f32 = 8.;
+const FONT_SIZE: f32 = 14.;
+
+pub enum MenuItem<M> {
+    // The message is sent with true when the item is activated
+    Action {
+        label: Label,
+        shortcut: Option<Label>,
+        enabled: bool,
+        message: M,
+    },
+    // The message is sent with the new state of the item
+    Check {
+        label: Label,
+        shortcut: Option<Label>,
+        enabled: bool,
+        checked: bool,
+        message: M,
+    },
+    Submenu {
+        label: Label,
+        enabled: bool,
+        menu: Menu<M>,
+    },
+    Separator,
+}
+
+impl<M> MenuItem<M> {
+    pub fn action(label: &str, message: M) -> Self {
+        MenuItem::Action {
+            label: Label::default(label, FONT_SIZE),
+            shortcut: None,
+            enabled: true,
+            message,
+        }
+    }
+    pub fn check(label: &str, checked: bool, message: M) -> Self {
+        MenuItem::Check {
+            label: Label::default(label, FONT_SIZE),
+            shortcut: None,
+            enabled: true,
+            checked,
+            message,
+        }
+    }
+    pub fn submenu(label: &str, menu: Menu<M>) -> Self {
+        MenuItem::Submenu {
+            label: Label::default(label, FONT_SIZE),
+            enabled: true,
+            menu,
+        }
+    }
+    pub fn separator() -> Self {
+        MenuItem::Separator
+    }
+    // The text of the keyboard shortcut shown next to the label.
+    // The menu doesn't handle the shortcut itself.
+    pub fn shortcut(mut self, text: &str) -> Self {
+        match &mut self {
+            MenuItem::Action { shortcut, .. } | MenuItem::Check { shortcut, .. } => {
+                *shortcut = Some(Label::default(text, FONT_SIZE).color(style::FG2));
+            }
+            _ => {}
+        }
+        self
+    }
+    pub fn enabled(mut self, enabled: bool) -> Self {
+        self.set_enabled(enabled);
+        self
+    }
+    pub fn set_enabled(&mut self, enabled: bool) {
+        let color = if enabled {
+            style::FG0
+        } else {
+            // Disabled items are dimmed
+            (style::FG0 & 0x00_FF_FF_FF) | 0x80_00_00_00
+        };
+        match self {
+            MenuItem::Action {
+                label, enabled: e, ..
+            }
+            | MenuItem::Check {
+                label, enabled: e, ..
+            }
+            | MenuItem::Submenu {
+                label, enabled: e, ..
+            } => {
+                *e = enabled;
+                label.set_color(color);
+            }
+            MenuItem::Separator => {}
+        }
+    }
+    pub fn is_enabled(&self) -> bool {
+        match self {
+            MenuItem::Action { enabled, .. }
+            | MenuItem::Check { enabled, .. }
+            | MenuItem::Submenu { enabled, .. } => *enabled,
+            MenuItem::Separator => false,
+        }
+    }
+    pub fn is_checked(&self) -> bool {
+        matches!(self, MenuItem::Check { checked: true, .. })
+    }
+    // Changes the state of a check item without sending the message
+    pub fn set_checked(&mut self, state: bool) {
+        if let MenuItem::Check { checked, .. } = self {
+            *checked = state;
+        }
+    }
+    fn label(&self) -> Option<&Label> {
+        match self {
+            MenuItem::Action { label, .. }
+            | MenuItem::Check { label, .. }
+            | MenuItem::Submenu { label, .. } => Some(label),
+            MenuItem::Separator => None,
+        }
+    }
+    fn get_shortcut(&self) -> Option<&Label> {
+        match self {
+            MenuItem::Action { shortcut, .. } | MenuItem::Check { shortcut, .. } => {
+                shortcut.as_ref()
+            }
+            _ => None,
+        }
+    }
+    fn height(&self) -> f32 {
+        match self.label() {
+            Some(label) => label.height().ceil() + 2. * PADDING,
+            None => 2. * PADDING + 1.,
+        }
+    }
+}
+
+// A list of items shown by a ContextMenu
+pub struct Menu<M> {
+    items: Vec<MenuItem<M>>,
+}
+
+impl<M> Default for Menu<M> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<M> Menu<M> {
+    pub fn new() -> Self {
+        Menu { items: Vec::new() }
+    }
+    pub fn item(mut self, item: MenuItem<M>) -> Self {
+        self.push(item);
+        self
+    }
+    pub fn separator(self) -> Self {
+        self.item(MenuItem::Separator)
+    }
+    pub fn push(&mut self, item: MenuItem<M>) {
+        self.items.push(item);
+    }
+    pub fn len(&self) -> usize {
+        self.items.len()
+    }
+    pub fn is_empty(&self) -> bool {
+        self.items.is_empty()
+    }
+    pub fn get(&self, index: usize) -> Option<&MenuItem<M>> {
+        self.items.get(index)
+    }
+    pub fn get_mut(&mut self, index: usize) -> Option<&mut MenuItem<M>> {
+        self.items.get_mut(index)
+    }
+    fn sync_labels(&mut self, ctx: &mut SyncContext<M>, event: Event<M>) -> Damage {
+        let mut damage = Damage::None;
+        for item in self.items.iter_mut() {
+            match item {
+                MenuItem::Action {
+                    label, shortcut, ..
+                }
+                | MenuItem::Check {
+                    label, shortcut, ..
+                } => {
+                    damage = damage.max(Widget::<M>::sync(label, ctx, event));
+                    if let Some(shortcut) = shortcut.as_mut() {
+                        damage = damage.max(Widget::<M>::sync(shortcut, ctx, event));
+                    }
+                }
+                MenuItem::Submenu { label, menu, .. } => {
+                    damage = damage
+                        .max(Widget::<M>::sync(label, ctx, event))
+                        .max(menu.sync_labels(ctx, event));
+                }
+                MenuItem::Separator => {}
+            }
+        }
+        damage
+    }
+    // The width of the check mark, label, shortcut and arrow columns
+    fn columns(&self, arrow: &Label) -> (f32, f32, f32, f32) {
+        let mut columns = (0., 0., 0., 0.);
+        for item in self.items.iter() {
+            match item {
+                MenuItem::Check { .. } => columns.0 = MARK,
+                MenuItem::Submenu { .. } => columns.3 = arrow.width().ceil(),
+                _ => {}
+            }
+            if let Some(label) = item.label() {
+                columns.1 = label.width().ceil().max(columns.1);
+            }
+            if let Some(shortcut) = item.get_shortcut() {
+                columns.2 = shortcut.width().ceil().max(columns.2);
+            }
+        }
+        columns
+    }
+    fn size(&self, arrow: &Label) -> (f32, f32) {
+        let (mark, label, shortcut, arrow) = self.columns(arrow);
+        let width = [mark, shortcut, arrow]
+            .iter()
+            .filter(|column| **column > 0.)
+            .fold(label, |width, column| width + GAP + column);
+        (
+            width + 2. * PADDING,
+            self.items.iter().map(|item| item.height()).sum::<f32>() + 2. * PADDING,
+        )
+    }
+    // The offset of the item from the top of the menu
+    fn item_y(&self, index: usize) -> f32 {
+        PADDING
+            + self.items[..index]
+                .iter()
+                .map(|item| item.height())
+                .sum::<f32>()
+    }
+    fn item_at(&self, y: f32) -> Option<usize> {
+        let mut offset = PADDING;
+        for (i, item) in self.items.iter().enumerate() {
+            offset += item.height();
+            if y < offset {
+                return (y >= PADDING).then_some(i);
+            }
+        }
+        None
+    }
+    // The next item which can be highlighted, going backward if step is negative
+    fn next(&self, from: Option<usize>, step: isize) -> Option<usize> {
+        let len = self.items.len() as isize;
+        let start = match from {
+            Some(index) => index as isize,
+            None if step > 0 => -1,
+            None => len,
+        };
+        (1..=len)
+            .map(|n| (start + n * step).rem_euclid(len) as usize)
+            .find(|index| self.items[*index].is_enabled())
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+struct Level {
+    x: f32,
+    y: f32,
+    highlighted: Option<usize>,
+}
+
+// Opens a menu where the widget is right clicked.
+// The menu is shown in a popup if the backend has them,
+// otherwise it's drawn over the widget, inside of its region.
+pub struct ContextMenu<M: TryIntoMessage<bool>, W: Widget<M>> {
+    widget: W,
+    menu: Menu<M>,
+    arrow: Label,
+    background: u32,
+    highlight: u32,
+    // The open menus, each one a submenu of the previous
+    levels: Vec<Level>,
+    popups: bool,
+    popup: PopupId,
+    origin: (f32, f32),
+    _request: PhantomData<M>,
+}
+
+impl<M: TryIntoMessage<bool>, W: Widget<M>> ContextMenu<M, W> {
+    pub fn new(widget: W, menu: Menu<M>) -> Self {
+        Self {
+            widget,
+            menu,
+            arrow: Label::default("›", FONT_SIZE),
+            background: style::BG0,
+            highlight: style::BG2,
+            levels: Vec::new(),
+            popups: false,
+            popup: PopupId::new(),
+            origin: (0., 0.),
+            _request: PhantomData,
+        }
+    }
+    pub fn background(mut self, color: u32) -> Self {
+        self.background = color;
+        self
+    }
+    pub fn highlight(mut self, color: u32) -> Self {
+        self.highlight = color;
+        self
+    }
+    pub fn get_menu(&self) -> &Menu<M> {
+        &self.menu
+    }
+    pub fn get_menu_mut(&mut self) -> &mut Menu<M> {
+        &mut self.menu
+    }
+    pub fn is_open(&self) -> bool {
+        !self.levels.is_empty()
+    }
+    // Opens the menu at this position relative to the widget
+    pub fn open(&mut self, x: f32, y: f32) {
+        self.levels = vec![Level {
+            x,
+            y,
+            highlighted: None,
+        }];
+        self.fit(0);
+    }
+    pub fn close(&mut self) {
+        self.levels.clear();
+    }
+    // The indexes of the submenus leading to the menu at this depth
+    fn path(&self, depth: usize) -> Option<Vec<usize>> {
+        self.levels[..depth]
+            .iter()
+            .map(|level| level.highlighted)
+            .collect()
+    }
+    fn get(&self, depth: usize) -> Option<&Menu<M>> {
+        let mut menu = &self.menu;
+        for index in self.path(depth)? {
+            match menu.items.get(index)? {
+                MenuItem::Submenu { menu: submenu, .. } => menu = submenu,
+                _ => return None,
+            }
+        }
+        Some(menu)
+    }
+    fn get_mut(&mut self, depth: usize) -> Option<&mut Menu<M>> {
+        let path = self.path(depth)?;
+        let mut menu = &mut self.menu;
+        for index in path {
+            match menu.items.get_mut(index)? {
+                MenuItem::Submenu { menu: submenu, .. } => menu = submenu,
+                _ => return None,
+            }
+        }
+        Some(menu)
+    }
+    fn level_region(&self, depth: usize) -> Option<Region> {
+        let level = self.levels.get(depth)?;
+        let (width, height) = self.get(depth)?.size(&self.arrow);
+        Some(Region::new(level.x, level.y, width, height))
+    }
+    // The area covered by all the open menus
+    fn bounds(&self) -> Option<Region> {
+        (0..self.levels.len())
+            .filter_map(|depth| self.level_region(depth))
+            .reduce(|a, b| a.merge(&b))
+    }
+    // The deepest menu under the position
+    fn level_at(&self, x: f32, y: f32) -> Option<usize> {
+        (0..self.levels.len()).rev().find(|depth| {
+            self.level_region(*depth)
+                .map(|region| region.contains(x, y))
+                .unwrap_or(false)
+        })
+    }
+    // Highlights an item and closes the submenus of its menu
+    fn highlight_item(&mut self, depth: usize, index: Option<usize>, open: bool) {
+        self.levels.truncate(depth + 1);
+        let index = index.filter(|index| {
+            self.get(depth)
+                .and_then(|menu| menu.get(*index))
+                .map(|item| item.is_enabled())
+                .unwrap_or(false)
+        });
+        self.levels[depth].highlighted = index;
+        if !open {
+            return;
+        }
+        if let (Some(index), Some(region)) = (index, self.level_region(depth)) {
+            if let Some(menu) = self.get(depth) {
+                if let MenuItem::Submenu { .. } = menu.items[index] {
+                    // The submenu is aligned with its item
+                    let y = region.y + menu.item_y(index) - PADDING;
+                    self.levels.push(Level {
+                        x: region.x + region.width,
+                        y,
+                        highlighted: None,
+                    });
+                    self.fit(depth + 1);
+                }
+            }
+        }
+    }
+    // Moves the menu inside of the widget when it's drawn in it.
+    // A submenu which doesn't fit on the right of its menu opens on the left.
+    fn fit(&mut self, depth: usize) {
+        if self.popups {
+            return;
+        }
+        let region = match self.level_region(depth) {
+            Some(region) => region,
+            None => return,
+        };
+        let (width, height) = (self.width(), self.height());
+        let parent = depth
+            .checked_sub(1)
+            .and_then(|depth| self.level_region(depth));
+        let mut x = region.x;
+        if x + region.width > width {
+            x = match parent {
+                Some(parent) => parent.x - region.width,
+                None => width - region.width,
+            };
+        }
+        let level = &mut self.levels[depth];
+        level.x = x.min(width - region.width).max(0.);
+        level.y = region.y.min(height - region.height).max(0.);
+    }
+    // The position in the coordinates of the menus.
+    // The compositor may have moved the popup if it didn't fit on the output.
+    fn menu_position(&self, ctx: &SyncContext<M>, x: f32, y: f32) -> (f32, f32) {
+        match (ctx.popup_region(self.popup), self.bounds()) {
+            (Some(popup), Some(bounds)) => (
+                x - (popup.x - self.origin.0 - bounds.x),
+                y - (popup.y - self.origin.1 - bounds.y),
+            ),
+            _ => (x, y),
+        }
+    }
+    fn activate(&mut self, ctx: &mut SyncContext<M>, depth: usize, index: usize) {
+        let item = match self.get_mut(depth).and_then(|menu| menu.get_mut(index)) {
+            Some(item) if item.is_enabled() => item,
+            _ => return,
+        };
+        let value = match item {
+            MenuItem::Action { .. } => true,
+            MenuItem::Check { checked, .. } => {
+                *checked = !*checked;
+                *checked
+            }
+            _ => {
+                self.highlight_item(depth, Some(index), true);
+                if let Some(menu) = self.get(depth + 1) {
+                    self.levels[depth + 1].highlighted = menu.next(None, 1);
+                }
+                return;
+            }
+        };
+        if let MenuItem::Action { message, .. } | MenuItem::Check { message, .. } = item {
+            if let Ok(msg) = TryIntoMessage::try_into(message, value) {
+                let _ = ctx.send(msg);
+            }
+        }
+        self.close();
+    }
+    fn keyboard(&mut self, ctx: &mut SyncContext<M>, key: Key) {
+        let depth = self.levels.len() - 1;
+        let highlighted = self.levels[depth].highlighted;
+        let menu = match self.get(depth) {
+            Some(menu) => menu,
+            None => return,
+        };
+        if key.is(keys::ESCAPE) {
+            self.close();
+        } else if key.is(keys::UP) {
+            let index = menu.next(highlighted, -1);
+            self.highlight_item(depth, index, false);
+        } else if key.is(keys::DOWN) {
+            let index = menu.next(highlighted, 1);
+            self.highlight_item(depth, index, false);
+        } else if key.is(keys::HOME) {
+            let index = menu.next(None, 1);
+            self.highlight_item(depth, index, false);
+        } else if key.is(keys::END) {
+            let index = menu.next(None, -1);
+            self.highlight_item(depth, index, false);
+        } else if key.is(keys::LEFT) && depth > 0 {
+            self.levels.pop();
+        } else if key.is(keys::RIGHT) {
+            if let Some(index) = highlighted {
+                if let Some(MenuItem::Submenu { .. }) = menu.get(index) {
+                    self.activate(ctx, depth, index);
+                }
+            }
+        } else if key.is(keys::RETURN) || key.is(keys::KP_ENTER) || key.is(keys::SPACE) {
+            if let Some(index) = highlighted {
+                self.activate(ctx, depth, index);
+            }
+        }
+    }
+    fn level_node(&self, depth: usize, x: f32, y: f32) -> RenderNode {
+        let menu = match self.get(depth) {
+            Some(menu) => menu,
+            None => return RenderNode::None,
+        };
+        let (width, height) = menu.size(&self.arrow);
+        let (mark, _, _, arrow) = menu.columns(&self.arrow);
+        let highlighted = self.levels[depth].highlighted;
+        let mut ry = y + PADDING;
+        let nodes = menu
+            .items
+            .iter()
+            .enumerate()
+            .map(|(i, item)| {
+                let row_height = item.height();
+                let row = Region::new(x, ry, width, row_height);
+                ry += row_height;
+                let label = match item.label() {
+                    Some(label) => label,
+                    None => {
+                        return Instruction::new(
+                            x + PADDING,
+                            row.y + PADDING,
+                            Rectangle::new(width - 2. * PADDING, 1., ShapeStyle::solid(style::BG2)),
+                        )
+                        .into()
+                    }
+                };
+                let center = |height: f32| row.y + ((row_height - height) / 2.).floor();
+                let mut lx = x + PADDING;
+                let mut nodes = Vec::new();
+                if mark > 0. {
+                    if item.is_checked() {
+                        nodes.push(
+                            Instruction::new(
+                                lx,
+                                center(MARK),
+                                Rectangle::new(MARK, MARK, ShapeStyle::solid(style::FG0))
+                                    .radius(2., 2., 2., 2.),
+                            )
+                            .into(),
+                        );
+                    } else {
+                        nodes.push(Instruction::empty(lx, center(MARK), MARK, MARK).into());
+                    }
+                    lx += MARK + GAP;
+                }
+                nodes.push(Instruction::new(lx, center(label.height()), label.clone()).into());
+                let mut rx = x + width - PADDING;
+                if arrow > 0. {
+                    rx -= arrow;
+                    if let MenuItem::Submenu { .. } = item {
+                        nodes.push(
+                            Instruction::new(rx, center(self.arrow.height()), self.arrow.clone())
+                                .into(),
+                        );
+                    }
+                    rx -= GAP;
+                }
+                if let Some(shortcut) = item.get_shortcut() {
+                    nodes.push(
+                        Instruction::new(
+                            rx - shortcut.width().ceil(),
+                            center(shortcut.height()),
+                            shortcut.clone(),
+                        )
+                        .into(),
+                    );
+                }
+                let background = if highlighted == Some(i) {
+                    Rectangle::new(width, row_height, ShapeStyle::solid(self.highlight))
+                } else {
+                    Rectangle::empty(width, row_height)
+                };
+                RenderNode::Extension {
+                    background: Instruction::new(x, row.y, background),
+                    border: None,
+                    node: Box::new(RenderNode::Container { region: row, nodes }),
+                }
+            })
+            .collect();
+        RenderNode::Extension {
+            background: Instruction::new(
+                x,
+                y,
+                Rectangle::new(width, height, ShapeStyle::solid(self.background))
+                    .radius(PADDING, PADDING, PADDING, PADDING),
+            ),
+            border: None,
+            node: Box::new(RenderNode::Container {
+                region: Region::new(x, y, width, height),
+                nodes,
+            }),
+        }
+    }
+    // The open menus drawn from the top left corner of their bounds
+    fn menu_node(&self, x: f32, y: f32) -> RenderNode {
+        let bounds = match self.bounds() {
+            Some(bounds) => bounds,
+            None => return RenderNode::None,
+        };
+        let region = Region::new(x, y, bounds.width, bounds.height);
+        let nodes = self
+            .levels
+            .iter()
+            .enumerate()
+            .map(|(depth, level)| {
+                self.level_node(depth, x + level.x - bounds.x, y + level.y - bounds.y)
+            })
+            .collect();
+        // The bounds change when submenus open or close which redraws the menus
+        RenderNode::Extension {
+            background: Instruction::empty(x, y, bounds.width, bounds.height),
+            border: None,
+            node: Box::new(RenderNode::Container { region, nodes }),
+        }
+    }
+}
+
+impl<M: TryIntoMessage<bool>, W: Widget<M>> Geometry for ContextMenu<M, W> {
+    fn width(&self) -> f32 {
+        self.widget.width()
+    }
+    fn height(&self) -> f32 {
+        self.widget.height()
+    }
+    fn set_width(&mut self, width: f32) -> Result<(), f32> {
+        self.widget.set_width(width)
+    }
+    fn set_height(&mut self, height: f32) -> Result<(), f32> {
+        self.widget.set_height(height)
+    }
+}
+
+impl<M: TryIntoMessage<bool>, W: Widget<M>> Widget<M> for ContextMenu<M, W> {
+    fn create_node(&mut self, x: f32, y: f32) -> RenderNode {
+        self.origin = (x, y);
+        let node = self.widget.create_node(x, y);
+        if self.popups {
+            return node;
+        }
+        let region = Region::new(x, y, self.width(), self.height());
+        let mut nodes = vec![node];
+        if let Some(bounds) = self.bounds() {
+            nodes.push(self.menu_node(x + bounds.x, y + bounds.y));
+        }
+        // The menus cover parts of the widget so they're drawn again together
+        RenderNode::Clip {
+            region,
+            node: Box::new(RenderNode::Container { region, nodes }),
+        }
+    }
+    fn sync<'d>(&'d mut self, ctx: &mut SyncContext<M>, event: Event<'d, M>) -> Damage {
+        self.popups = ctx.has_popups();
+        let mut damage =
+            Widget::<M>::sync(&mut self.arrow, ctx, event).max(self.menu.sync_labels(ctx, event));
+        let state = self.levels.clone();
+        match event {
+            Event::Pointer(x, y, pointer) if self.is_open() => {
+                let (mx, my) = self.menu_position(ctx, x, y);
+                let level = self.level_at(mx, my);
+                match (pointer, level) {
+                    (Pointer::Hover, Some(depth)) => {
+                        let region = self.level_region(depth).unwrap();
+                        let index = self.get(depth).unwrap().item_at(my - region.y);
+                        self.highlight_item(depth, index, true);
+                    }
+                    (Pointer::MouseClick { pressed: true, .. }, Some(depth)) => {
+                        let region = self.level_region(depth).unwrap();
+                        if let Some(index) = self.get(depth).unwrap().item_at(my - region.y) {
+                            self.activate(ctx, depth, index);
+                        }
+                    }
+                    (
+                        Pointer::MouseClick {
+                            button,
+                            pressed: true,
+                            ..
+                        },
+                        None,
+                    ) => {
+                        self.close();
+                        if button == MouseButton::Right && self.contains(x, y) {
+                            self.open(x, y);
+                        } else {
+                            damage = damage.max(self.widget.sync(ctx, event));
+                        }
+                    }
+                    (_, None) => damage = damage.max(self.widget.sync(ctx, event)),
+                    _ => {}
+                }
+            }
+            Event::Pointer(
+                x,
+                y,
+                Pointer::MouseClick {
+                    button: MouseButton::Right,
+                    pressed: true,
+                    ..
+                },
+            ) if self.contains(x, y) && !self.menu.is_empty() => self.open(x, y),
+            Event::Keyboard(key) if self.is_open() => {
+                if key.pressed {
+                    self.keyboard(ctx, key);
+                }
+            }
+            // The compositor closed the menu, like after a click outside of the window
+            Event::PopupDone(owner) if owner == self.popup => self.close(),
+            _ => damage = damage.max(self.widget.sync(ctx, event)),
+        }
+        let changed = state != self.levels;
+        if self.popups {
+            if self.is_open() && (changed || damage.is_some()) {
+                if let Some(bounds) = self.bounds() {
+                    let (x, y) = (self.origin.0 + bounds.x, self.origin.1 + bounds.y);
+                    ctx.show_popup(
+                        self.popup,
+                        Popup {
+                            anchor: Region::new(
+                                self.origin.0 + self.levels[0].x,
+                                self.origin.1 + self.levels[0].y,
+                                1.,
+                                1.,
+                            ),
+                            region: Region::new(x, y, bounds.width, bounds.height),
+                            node: self.menu_node(0., 0.),
+                        },
+                    );
+                }
+            } else if !self.is_open() && !state.is_empty() {
+                ctx.hide_popup(self.popup);
+            }
+        } else if changed {
+            damage = damage.max(Damage::Some);
+        }
+        damage
+    }
+}
+
+impl<M: TryIntoMessage<bool>, W: Widget<M>> Deref for ContextMenu<M, W> {
+    type Target = W;
+    fn deref(&self) -> &Self::Target {
+        &self.widget
+    }
+}
+
+impl<M: TryIntoMessage<bool>, W: Widget<M>> DerefMut for ContextMenu<M, W> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.widget
+    }
+}
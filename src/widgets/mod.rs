@@ -1,21 +1,43 @@
 pub mod button;
 pub mod container;
+pub mod dialog;
+pub mod dropdown;
+pub mod editor;
+pub mod entry;
 pub mod extra;
 pub mod image;
+pub mod list;
+pub mod menu;
+pub mod progress;
+pub mod scroll;
 pub mod shapes;
 pub mod slider;
+pub mod spin;
+pub mod tabs;
 pub mod text;
+pub mod tooltip;
 
 use crate::scene::Coords;
-pub use crate::widgets::image::Image;
+pub use crate::widgets::image::{AnimatedImage, Image};
 use crate::*;
 pub use button::Button;
 pub use container::*;
+pub use dialog::Dialog;
+pub use dropdown::Dropdown;
+pub use editor::TextEditor;
+pub use entry::Entry;
+pub use list::ListView;
+pub use menu::{ContextMenu, Menu, MenuItem};
+pub use progress::{ProgressBar, Spinner};
+pub use scroll::ScrollView;
 pub use shapes::Style;
 pub use slider::Slider;
+pub use spin::SpinButton;
 use std::marker::PhantomData;
 use std::ops::{Deref, DerefMut};
+pub use tabs::Tabs;
 use tiny_skia::*;
+pub use tooltip::Tooltip;
 
 pub const START: Alignment = Alignment::Start;
 pub const CENTER: Alignment = Alignment::Center;
@@ -35,7 +57,7 @@ fn blend_f32(background: f32, foreground: f32, alpha_fg: f32) -> f32 {
     foreground * alpha_fg + background * (1. - alpha_fg)
 }
 
-#[derive(Copy, Clone, Debug)]
+#[derive(Copy, Clone, Debug, PartialEq)]
 pub enum Alignment {
     Start,
     Center,
@@ -0,0 +1,348 @@
+use crate::controller::*;
+use crate::scene::{Instruction, Region};
+use crate::widgets::shapes::{Rectangle, ShapeStyle, Style};
+use crate::widgets::text::{Label, Text};
+use crate::*;
+use std::f32::consts::PI;
+
+// Displays the progress of a task as a value between 0 and 1.
+// Like a Listener, the value is retreived on messages with a matching id or on Frame.
+pub struct ProgressBar<M: PartialEq + TryInto<f32>> {
+    message: Option<M>,
+    value: f32,
+    size: f32,
+    track: Rectangle,
+    fill: u32,
+    orientation: Orientation,
+    label: Option<Text>,
+    // The percentage replaces `{}` in the format
+    format: String,
+}
+
+impl<M: PartialEq + TryInto<f32>> ProgressBar<M> {
+    pub fn new(width: u32, height: u32) -> Self {
+        let orientation = if height > width {
+            Orientation::Vertical
+        } else {
+            Orientation::Horizontal
+        };
+        ProgressBar {
+            message: None,
+            value: 0.,
+            size: match &orientation {
+                Orientation::Horizontal => width as f32,
+                Orientation::Vertical => height as f32,
+            },
+            track: Rectangle::new(width as f32, height as f32, ShapeStyle::solid(style::BG2)),
+            fill: style::BLU,
+            orientation,
+            label: None,
+            format: String::from("{}%"),
+        }
+    }
+    pub fn message(mut self, message: M) -> Self {
+        self.message = Some(message);
+        self
+    }
+    // The bar fills along the track in this orientation
+    pub fn orientation(mut self, orientation: Orientation) -> Self {
+        self.size = match &orientation {
+            Orientation::Horizontal => self.track.width(),
+            Orientation::Vertical => self.track.height(),
+        };
+        self.orientation = orientation;
+        self
+    }
+    pub fn fill(mut self, color: u32) -> Self {
+        self.fill = color;
+        self
+    }
+    pub fn set_fill(&mut self, color: u32) {
+        self.fill = color;
+    }
+    // Shows the percentage over the bar
+    pub fn text(mut self, font_size: f32) -> Self {
+        self.label = Some(Label::default("", font_size).into());
+        self.update_label();
+        self
+    }
+    pub fn format(mut self, format: &str) -> Self {
+        self.format = format.to_string();
+        self.update_label();
+        self
+    }
+    pub fn value(&self) -> f32 {
+        self.value
+    }
+    pub fn set_value(&mut self, value: f32) {
+        self.value = value.clamp(0., 1.);
+        self.update_label();
+    }
+    fn update_label(&mut self) {
+        if let Some(label) = self.label.as_mut() {
+            let text = self
+                .format
+                .replace("{}", &((self.value * 100.).round() as u32).to_string());
+            label.edit(&text);
+        }
+    }
+    fn length(&self) -> f32 {
+        (self.value * self.size).round()
+    }
+}
+
+impl<M: PartialEq + TryInto<f32>> Geometry for ProgressBar<M> {
+    fn width(&self) -> f32 {
+        if let Orientation::Horizontal = &self.orientation {
+            self.size
+        } else {
+            self.track.width()
+        }
+    }
+    fn height(&self) -> f32 {
+        if let Orientation::Vertical = &self.orientation {
+            self.size
+        } else {
+            self.track.height()
+        }
+    }
+    fn set_width(&mut self, width: f32) -> Result<(), f32> {
+        if let Orientation::Horizontal = &self.orientation {
+            self.size = width.max(0.);
+        }
+        self.track.set_width(width.max(0.))
+    }
+    fn set_height(&mut self, height: f32) -> Result<(), f32> {
+        if let Orientation::Vertical = &self.orientation {
+            self.size = height.max(0.);
+        }
+        self.track.set_height(height.max(0.))
+    }
+}
+
+impl<M: PartialEq + TryInto<f32>> Widget<M> for ProgressBar<M> {
+    fn create_node(&mut self, x: f32, y: f32) -> RenderNode {
+        let (width, height) = (self.width(), self.height());
+        let length = self.length();
+        let (tl, tr, br, bl) = self.track.get_radius();
+        // The vertical bar fills up from the bottom
+        let fill = match self.orientation {
+            Orientation::Horizontal => Instruction::new(
+                x,
+                y,
+                Rectangle::new(length, height, ShapeStyle::solid(self.fill)).radius(tl, tr, br, bl),
+            ),
+            Orientation::Vertical => Instruction::new(
+                x,
+                y + height - length,
+                Rectangle::new(width, length, ShapeStyle::solid(self.fill)).radius(tl, tr, br, bl),
+            ),
+        };
+        let mut nodes = vec![RenderNode::Instruction(fill)];
+        if let Some(label) = self.label.as_ref() {
+            nodes.push(
+                Instruction::new(
+                    x + ((width - label.width()) / 2.).round(),
+                    y + ((height - label.height()) / 2.).round(),
+                    Label::clone(label),
+                )
+                .into(),
+            );
+        }
+        RenderNode::Extension {
+            background: Instruction::new(x, y, self.track.clone()),
+            border: None,
+            node: Box::new(RenderNode::Container {
+                region: Region::new(x, y, width, height),
+                nodes,
+            }),
+        }
+    }
+    fn sync<'d>(&'d mut self, ctx: &mut SyncContext<M>, event: Event<'d, M>) -> Damage {
+        let value = self.value;
+        if let Some(message) = self.message.as_ref() {
+            let update = match event {
+                Event::Message(msg) => message.eq(msg),
+                Event::Frame => true,
+                _ => false,
+            };
+            if update {
+                if let Ok(msg) = ctx.get(message) {
+                    if let Ok(value) = msg.try_into() {
+                        self.set_value(value);
+                    }
+                }
+            }
+        }
+        let damage = match self.label.as_mut() {
+            Some(label) => Widget::<M>::sync(label, ctx, event),
+            None => Damage::None,
+        };
+        if value != self.value {
+            return Damage::Some;
+        }
+        damage
+    }
+}
+
+impl<M: PartialEq + TryInto<f32>> Style for ProgressBar<M> {
+    fn set_background<B: Into<scene::Background>>(&mut self, background: B) {
+        self.track.set_background(background);
+    }
+    fn set_border(&mut self, color: u32, width: f32) {
+        self.track.set_border(color, width);
+    }
+    fn set_border_color(&mut self, color: u32) {
+        self.track.set_border_color(color);
+    }
+    fn set_radius(&mut self, tl: f32, tr: f32, br: f32, bl: f32) {
+        self.track.set_radius(tl, tr, br, bl);
+    }
+    fn set_border_size(&mut self, size: f32) {
+        self.track.set_border_size(size);
+    }
+    fn background<B: Into<scene::Background>>(mut self, background: B) -> Self {
+        self.set_background(background);
+        self
+    }
+    fn border(mut self, color: u32, size: f32) -> Self {
+        self.set_border(color, size);
+        self
+    }
+    fn border_color(mut self, color: u32) -> Self {
+        self.set_border_color(color);
+        self
+    }
+    fn border_size(mut self, size: f32) -> Self {
+        self.set_border_size(size);
+        self
+    }
+    fn radius(mut self, tl: f32, tr: f32, br: f32, bl: f32) -> Self {
+        self.set_radius(tl, tr, br, bl);
+        self
+    }
+}
+
+// Shows that something is going on with a ring of dots.
+// The brightest dot goes around the ring and the others fade behind it.
+pub struct Spinner {
+    size: f32,
+    dots: usize,
+    color: u32,
+    // Time in ms taken to go around the ring
+    period: u32,
+    elapsed: u32,
+    running: bool,
+    // Tells if frame callbacks were requested
+    animating: bool,
+}
+
+impl Spinner {
+    pub fn new(size: f32) -> Self {
+        Spinner {
+            size,
+            dots: 8,
+            color: style::FG0,
+            period: 1000,
+            elapsed: 0,
+            running: true,
+            animating: false,
+        }
+    }
+    pub fn color(mut self, color: u32) -> Self {
+        self.color = color;
+        self
+    }
+    pub fn set_color(&mut self, color: u32) {
+        self.color = color;
+    }
+    pub fn dots(mut self, dots: usize) -> Self {
+        self.dots = dots.max(2);
+        self
+    }
+    pub fn period(mut self, period: u32) -> Self {
+        self.period = period.max(1);
+        self
+    }
+    pub fn is_running(&self) -> bool {
+        self.running
+    }
+    pub fn start(&mut self) {
+        self.running = true;
+        self.animating = false;
+    }
+    // The spinner stays still until it starts again
+    pub fn stop(&mut self) {
+        self.running = false;
+    }
+    // The dot at the front of the ring
+    fn head(&self) -> usize {
+        (self.elapsed as usize * self.dots / self.period as usize) % self.dots
+    }
+}
+
+impl Geometry for Spinner {
+    fn width(&self) -> f32 {
+        self.size
+    }
+    fn height(&self) -> f32 {
+        self.size
+    }
+    fn set_width(&mut self, width: f32) -> Result<(), f32> {
+        self.size = width.max(0.);
+        Ok(())
+    }
+    fn set_height(&mut self, height: f32) -> Result<(), f32> {
+        self.size = height.max(0.);
+        Ok(())
+    }
+}
+
+impl<M> Widget<M> for Spinner {
+    fn create_node(&mut self, x: f32, y: f32) -> RenderNode {
+        let dot = (self.size / 5.).round().max(1.);
+        let radius = (self.size - dot) / 2.;
+        let head = self.head();
+        let nodes = (0..self.dots)
+            .map(|i| {
+                let angle = 2. * PI * i as f32 / self.dots as f32 - PI / 2.;
+                let behind = (head + self.dots - i) % self.dots;
+                let alpha = (1. - behind as f32 / self.dots as f32).max(0.2);
+                let alpha = (((self.color >> 24) as f32) * alpha).round() as u32;
+                Instruction::new(
+                    x + (radius + radius * angle.cos()).round(),
+                    y + (radius + radius * angle.sin()).round(),
+                    Rectangle::new(
+                        dot,
+                        dot,
+                        ShapeStyle::solid((self.color & 0x00FF_FFFF) | (alpha << 24)),
+                    )
+                    .radius(dot / 2., dot / 2., dot / 2., dot / 2.),
+                )
+                .into()
+            })
+            .collect();
+        RenderNode::Container {
+            region: Region::new(x, y, self.size, self.size),
+            nodes,
+        }
+    }
+    fn sync<'d>(&'d mut self, _ctx: &mut SyncContext<M>, event: Event<'d, M>) -> Damage {
+        if !self.running {
+            self.animating = false;
+            return Damage::None;
+        }
+        match event {
+            // Keeps the frame callbacks coming
+            Event::Callback(frame_time) => {
+                self.elapsed = (self.elapsed + frame_time) % self.period;
+                Damage::Frame
+            }
+            _ if !self.animating => {
+                self.animating = true;
+                Damage::Frame
+            }
+            _ => Damage::None,
+        }
+    }
+}
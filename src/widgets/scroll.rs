@@ -0,0 +1,292 @@
+use crate::*;
+use scene::{Instruction, Region};
+use std::marker::PhantomData;
+use std::ops::{Deref, DerefMut};
+use widgets::shapes::rectangle::Rectangle;
+use widgets::shapes::{ShapeStyle, Style};
+
+// Thickness of the scrollbars
+const SCROLLBAR: f32 = 6.;
+// Minimal length of the thumb of a scrollbar
+const THUMB: f32 = 20.;
+
+// Shows a part of its child and scrolls through it.
+pub struct ScrollView<M, W: Widget<M>> {
+    widget: W,
+    width: f32,
+    height: f32,
+    // Position of the view in the child
+    position: (f32, f32),
+    scrollbar: Option<u32>,
+    // The scrollbar being dragged with the position
+    // of the pointer from the start of the thumb
+    grab: Option<(Orientation, f32)>,
+    // The child's node needs to be recreated after scrolling
+    moved: bool,
+    _request: PhantomData<M>,
+}
+
+impl<M, W: Widget<M>> ScrollView<M, W> {
+    pub fn new(widget: W, width: f32, height: f32) -> Self {
+        Self {
+            widget,
+            width,
+            height,
+            position: (0., 0.),
+            scrollbar: None,
+            grab: None,
+            moved: false,
+            _request: PhantomData,
+        }
+    }
+    // Draws scrollbars of this color over the child
+    pub fn scrollbar(mut self, color: u32) -> Self {
+        self.scrollbar = Some(color);
+        self
+    }
+    pub fn set_scrollbar(&mut self, scrollbar: Option<u32>) {
+        self.scrollbar = scrollbar;
+    }
+    pub fn position(&self) -> (f32, f32) {
+        self.position
+    }
+    // The furthest the view can be scrolled in each orientation
+    pub fn max_position(&self) -> (f32, f32) {
+        (
+            (self.widget.width() - self.width).max(0.),
+            (self.widget.height() - self.height).max(0.),
+        )
+    }
+    pub fn set_position(&mut self, x: f32, y: f32) {
+        let (max_x, max_y) = self.max_position();
+        let position = (x.clamp(0., max_x).round(), y.clamp(0., max_y).round());
+        if position != self.position {
+            self.position = position;
+            self.moved = true;
+        }
+    }
+    pub fn scroll_by(&mut self, dx: f32, dy: f32) {
+        let (x, y) = self.position;
+        self.set_position(x + dx, y + dy);
+    }
+    // Scrolls the least to show the region of the child
+    pub fn scroll_to(&mut self, region: Region) {
+        let (mut x, mut y) = self.position;
+        x = x.min(region.x).max(region.x + region.width - self.width);
+        y = y.min(region.y).max(region.y + region.height - self.height);
+        self.set_position(x, y);
+    }
+    // Returns the offset and the length of the thumb of a scrollbar
+    fn thumb(&self, orientation: Orientation) -> Option<(f32, f32)> {
+        let (max_x, max_y) = self.max_position();
+        let (length, content, position, max) = match orientation {
+            Orientation::Horizontal => (self.width, self.widget.width(), self.position.0, max_x),
+            Orientation::Vertical => (self.height, self.widget.height(), self.position.1, max_y),
+        };
+        if self.scrollbar.is_none() || max <= 0. {
+            return None;
+        }
+        // Leaves room in the corner for the other scrollbar
+        let track = length - SCROLLBAR;
+        let thumb = (track * length / content).max(THUMB).min(track);
+        Some(((track - thumb) * position / max, thumb))
+    }
+    // The region of the scrollbar's track
+    fn track(&self, orientation: Orientation) -> Region {
+        match orientation {
+            Orientation::Horizontal => Region::new(
+                0.,
+                self.height - SCROLLBAR,
+                self.width - SCROLLBAR,
+                SCROLLBAR,
+            ),
+            Orientation::Vertical => Region::new(
+                self.width - SCROLLBAR,
+                0.,
+                SCROLLBAR,
+                self.height - SCROLLBAR,
+            ),
+        }
+    }
+    fn drag(&mut self, orientation: Orientation, offset: f32) {
+        if let Some((_, thumb)) = self.thumb(orientation) {
+            let (max_x, max_y) = self.max_position();
+            let (x, y) = self.position;
+            let length = match orientation {
+                Orientation::Horizontal => self.width,
+                Orientation::Vertical => self.height,
+            };
+            // The thumb fills the track, there's nowhere to drag it
+            let track = length - SCROLLBAR - thumb;
+            if track <= 0. {
+                return;
+            }
+            match orientation {
+                Orientation::Horizontal => self.set_position(offset / track * max_x, y),
+                Orientation::Vertical => self.set_position(x, offset / track * max_y),
+            }
+        }
+    }
+}
+
+impl<M, W: Widget<M>> Geometry for ScrollView<M, W> {
+    fn width(&self) -> f32 {
+        self.width
+    }
+    fn height(&self) -> f32 {
+        self.height
+    }
+    fn set_width(&mut self, width: f32) -> Result<(), f32> {
+        if width > 0. {
+            self.width = width;
+            let (x, y) = self.position;
+            self.set_position(x, y);
+            return Ok(());
+        }
+        Err(self.width)
+    }
+    fn set_height(&mut self, height: f32) -> Result<(), f32> {
+        if height > 0. {
+            self.height = height;
+            let (x, y) = self.position;
+            self.set_position(x, y);
+            return Ok(());
+        }
+        Err(self.height)
+    }
+}
+
+impl<M, W: Widget<M>> Widget<M> for ScrollView<M, W> {
+    fn create_node(&mut self, x: f32, y: f32) -> RenderNode {
+        let (px, py) = self.position;
+        let region = Region::new(x, y, self.width, self.height);
+        let mut nodes = vec![self.widget.create_node(x - px, y - py)];
+        for orientation in [Orientation::Horizontal, Orientation::Vertical] {
+            if let (Some((offset, length)), Some(color)) = (self.thumb(orientation), self.scrollbar)
+            {
+                let track = self.track(orientation);
+                let (dx, dy, width, height) = match orientation {
+                    Orientation::Horizontal => (offset, 0., length, SCROLLBAR),
+                    Orientation::Vertical => (0., offset, SCROLLBAR, length),
+                };
+                let radius = SCROLLBAR / 2.;
+                nodes.push(RenderNode::Instruction(Instruction::new(
+                    x + track.x + dx,
+                    y + track.y + dy,
+                    Rectangle::new(width, height, ShapeStyle::solid(color))
+                        .radius(radius, radius, radius, radius),
+                )));
+            }
+        }
+        RenderNode::Clip {
+            region,
+            node: Box::new(RenderNode::Container { region, nodes }),
+        }
+    }
+    fn sync<'d>(&'d mut self, ctx: &mut SyncContext<M>, event: Event<'d, M>) -> Damage {
+        let position = self.position;
+        let damage = match event {
+            Event::Pointer(x, y, pointer) => {
+                let (px, py) = self.position;
+                let inside = self.contains(x, y);
+                match pointer {
+                    Pointer::MouseClick {
+                        button, pressed, ..
+                    } if button.is_left() => {
+                        if !pressed && self.grab.take().is_some() {
+                            return Damage::None;
+                        }
+                        for orientation in [Orientation::Horizontal, Orientation::Vertical] {
+                            if let Some((offset, length)) = self.thumb(orientation) {
+                                let track = self.track(orientation);
+                                if pressed && track.contains(x, y) {
+                                    let pointer = match orientation {
+                                        Orientation::Horizontal => x - track.x,
+                                        Orientation::Vertical => y - track.y,
+                                    };
+                                    // Clicking the track moves the thumb under the pointer
+                                    let grab = if pointer < offset || pointer > offset + length {
+                                        length / 2.
+                                    } else {
+                                        pointer - offset
+                                    };
+                                    self.grab = Some((orientation, grab));
+                                    self.drag(orientation, pointer - grab);
+                                    return self.damage(ctx, position);
+                                }
+                            }
+                        }
+                        if inside || !pressed {
+                            self.widget
+                                .sync(ctx, Event::Pointer(x + px, y + py, pointer))
+                        } else {
+                            Damage::None
+                        }
+                    }
+                    Pointer::Hover if self.grab.is_some() => {
+                        if let Some((orientation, grab)) = self.grab {
+                            let track = self.track(orientation);
+                            match orientation {
+                                Orientation::Horizontal => {
+                                    self.drag(orientation, x - track.x - grab)
+                                }
+                                Orientation::Vertical => self.drag(orientation, y - track.y - grab),
+                            }
+                        }
+                        Damage::None
+                    }
+                    Pointer::Scroll { orientation, value } if inside => {
+                        let damage = self
+                            .widget
+                            .sync(ctx, Event::Pointer(x + px, y + py, pointer));
+                        // The child gets to scroll first
+                        if damage.is_none() {
+                            match orientation {
+                                Orientation::Horizontal => self.scroll_by(value, 0.),
+                                Orientation::Vertical => self.scroll_by(0., value),
+                            }
+                        }
+                        damage
+                    }
+                    // Parts of the child hidden by the view can't be hovered
+                    Pointer::Hover if !inside => self
+                        .widget
+                        .sync(ctx, Event::Pointer(x + px, y + py, Pointer::Leave)),
+                    Pointer::Hover | Pointer::Enter | Pointer::Leave => self
+                        .widget
+                        .sync(ctx, Event::Pointer(x + px, y + py, pointer)),
+                    _ if inside => self
+                        .widget
+                        .sync(ctx, Event::Pointer(x + px, y + py, pointer)),
+                    _ => Damage::None,
+                }
+            }
+            _ => self.widget.sync(ctx, event),
+        };
+        damage.max(self.damage(ctx, position))
+    }
+}
+
+impl<M, W: Widget<M>> ScrollView<M, W> {
+    // Every node of the child is moved when the view is scrolled
+    fn damage(&mut self, ctx: &mut SyncContext<M>, position: (f32, f32)) -> Damage {
+        if self.moved || position != self.position {
+            self.moved = false;
+            return self.widget.sync(ctx, Event::Redraw).max(Damage::Some);
+        }
+        Damage::None
+    }
+}
+
+impl<M, W: Widget<M>> Deref for ScrollView<M, W> {
+    type Target = W;
+    fn deref(&self) -> &Self::Target {
+        &self.widget
+    }
+}
+
+impl<M, W: Widget<M>> DerefMut for ScrollView<M, W> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.widget
+    }
+}
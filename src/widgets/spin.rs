@@ -0,0 +1,398 @@
+use crate::controller::*;
+use crate::widgets::entry::{BLINK, CARET_WIDTH};
+use crate::widgets::shapes::{Rectangle, Style};
+use crate::widgets::text::{Label, Text};
+use crate::*;
+use std::ops::Deref;
+
+// Space around the text of the field
+const PADDING: f32 = 4.;
+
+// A numeric field with buttons to decrement and increment its value.
+// The value is typed, changed by a step with the buttons, the arrow keys or scrolling,
+// and sent with the message like a Slider.
+pub struct SpinButton<M: PartialEq + TryIntoMessage<f32> + TryInto<f32>> {
+    value: f32,
+    min: f32,
+    max: f32,
+    step: f32,
+    // Number of digits after the decimal point
+    precision: usize,
+    text: String,
+    display: Text,
+    advances: Vec<f32>,
+    cursor: usize,
+    minus: Label,
+    plus: Label,
+    frame: Rectangle,
+    // Width of the field without the buttons
+    width: f32,
+    height: f32,
+    // Direction of the button under the pointer
+    hovered: Option<f32>,
+    focused: bool,
+    caret: bool,
+    blink: u32,
+    edited: bool,
+    message: Option<M>,
+}
+
+impl<M: PartialEq + TryIntoMessage<f32> + TryInto<f32>> SpinButton<M> {
+    pub fn new(min: f32, max: f32, step: f32) -> Self {
+        let mut spin = SpinButton {
+            value: min,
+            min,
+            max: max.max(min),
+            step,
+            precision: 0,
+            text: String::new(),
+            display: Label::default("", 14.).into(),
+            advances: Vec::new(),
+            cursor: 0,
+            minus: Label::default("−", 14.),
+            plus: Label::default("+", 14.),
+            frame: Rectangle::empty(0., 0.)
+                .background(style::BG2)
+                .radius(3., 3., 3., 3.),
+            width: 60.,
+            height: 14.,
+            hovered: None,
+            focused: false,
+            caret: true,
+            blink: 0,
+            edited: true,
+            message: None,
+        };
+        spin.set_value(min);
+        spin
+    }
+    pub fn precision(mut self, precision: usize) -> Self {
+        self.precision = precision;
+        self.set_value(self.value);
+        self
+    }
+    pub fn message(mut self, message: M) -> Self {
+        self.message = Some(message);
+        self
+    }
+    pub fn value(&self) -> f32 {
+        self.value
+    }
+    // Changes the value without sending the message
+    pub fn set_value(&mut self, value: f32) {
+        let factor = 10f32.powi(self.precision as i32);
+        self.value =
+            ((value.clamp(self.min, self.max) * factor).round() / factor).clamp(self.min, self.max);
+        self.text = format!("{:.*}", self.precision, self.value);
+        self.cursor = self.text.chars().count();
+        self.edited = true;
+    }
+    pub fn set_range(&mut self, min: f32, max: f32) {
+        self.min = min;
+        self.max = max.max(min);
+        self.set_value(self.value);
+    }
+    pub fn set_step(&mut self, step: f32) {
+        self.step = step;
+    }
+    pub fn is_focused(&self) -> bool {
+        self.focused
+    }
+    pub fn set_focus(&mut self, focused: bool) {
+        self.focused = focused;
+        self.caret = true;
+        self.blink = 0;
+    }
+    fn button_size(&self) -> f32 {
+        self.height()
+    }
+    // Which button is at the position
+    fn button_at(&self, x: f32, y: f32) -> Option<f32> {
+        if !self.contains(x, y) || x < self.width {
+            return None;
+        }
+        if x < self.width + self.button_size() {
+            Some(-1.)
+        } else {
+            Some(1.)
+        }
+    }
+    fn send(&self, ctx: &mut SyncContext<M>) {
+        if let Some(message) = self.message.as_ref() {
+            if let Ok(msg) = TryIntoMessage::try_into(message, self.value) {
+                let _ = ctx.send(msg);
+            }
+        }
+    }
+    // Sets the value and sends the message if it changed
+    fn change(&mut self, ctx: &mut SyncContext<M>, value: f32) {
+        let previous = self.value;
+        self.set_value(value);
+        if previous != self.value {
+            self.send(ctx);
+        }
+    }
+    // The typed text replaces the value if it's a valid number
+    fn commit(&mut self, ctx: &mut SyncContext<M>) {
+        match self.text.parse::<f32>() {
+            Ok(value) => self.change(ctx, value),
+            Err(_) => self.set_value(self.value),
+        }
+    }
+    fn spin(&mut self, ctx: &mut SyncContext<M>, steps: f32) {
+        self.commit(ctx);
+        self.change(ctx, self.value + steps * self.step);
+    }
+    // Only the characters of a number within the range are accepted
+    fn insert(&mut self, c: char) {
+        let index = crate::widgets::entry::byte_index(&self.text, self.cursor);
+        let valid = match c {
+            '0'..='9' => !self.text[index..].starts_with('-'),
+            '-' => self.min < 0. && self.cursor == 0 && !self.text.contains('-'),
+            '.' => self.precision > 0 && !self.text.contains('.'),
+            _ => false,
+        };
+        if valid {
+            self.text.insert(index, c);
+            self.cursor += 1;
+            self.edited = true;
+        }
+    }
+    fn delete(&mut self, start: usize, end: usize) {
+        if start < end {
+            let (start, end) = (
+                crate::widgets::entry::byte_index(&self.text, start),
+                crate::widgets::entry::byte_index(&self.text, end),
+            );
+            self.text.replace_range(start..end, "");
+            self.edited = true;
+        }
+    }
+    fn keyboard(&mut self, ctx: &mut SyncContext<M>, key: Key) -> Damage {
+        if !self.focused || !key.pressed {
+            return Damage::None;
+        }
+        let len = self.text.chars().count();
+        if key.is(keys::UP) {
+            self.spin(ctx, 1.);
+        } else if key.is(keys::DOWN) {
+            self.spin(ctx, -1.);
+        } else if key.is(keys::PAGE_UP) {
+            self.spin(ctx, 10.);
+        } else if key.is(keys::PAGE_DOWN) {
+            self.spin(ctx, -10.);
+        } else if key.is(keys::LEFT) {
+            self.cursor = self.cursor.saturating_sub(1);
+        } else if key.is(keys::RIGHT) {
+            self.cursor = (self.cursor + 1).min(len);
+        } else if key.is(keys::HOME) {
+            self.cursor = 0;
+        } else if key.is(keys::END) {
+            self.cursor = len;
+        } else if key.is(keys::BACKSPACE) {
+            self.delete(self.cursor.saturating_sub(1), self.cursor);
+            self.cursor = self.cursor.saturating_sub(1);
+        } else if key.is(keys::DELETE) {
+            self.delete(self.cursor, (self.cursor + 1).min(len));
+        } else if key.is(keys::RETURN) || key.is(keys::KP_ENTER) {
+            self.commit(ctx);
+        } else if key.is(keys::ESCAPE) {
+            // The typed text is discarded
+            self.set_value(self.value);
+            self.set_focus(false);
+        } else if let Some(utf8) = key.utf8 {
+            if key.modifiers.ctrl {
+                return Damage::None;
+            }
+            for c in utf8.chars() {
+                self.insert(c);
+            }
+        } else {
+            return Damage::None;
+        }
+        self.caret = true;
+        self.blink = 0;
+        Damage::Some
+    }
+    fn pointer(&mut self, ctx: &mut SyncContext<M>, x: f32, y: f32, pointer: Pointer) -> Damage {
+        let hovered = self.hovered;
+        let focused = self.focused;
+        match pointer {
+            Pointer::MouseClick {
+                button,
+                pressed: true,
+                ..
+            } if button.is_left() => match self.button_at(x, y) {
+                Some(direction) => self.spin(ctx, direction),
+                None if self.contains(x, y) => {
+                    self.set_focus(true);
+                    // The cursor moves to the closest character
+                    let x = x - PADDING;
+                    self.cursor = (0..self.advances.len())
+                        .min_by(|a, b| {
+                            (self.advances[*a] - x)
+                                .abs()
+                                .total_cmp(&(self.advances[*b] - x).abs())
+                        })
+                        .unwrap_or(0);
+                    return Damage::Some;
+                }
+                None => {
+                    if self.focused {
+                        self.commit(ctx);
+                        self.set_focus(false);
+                    }
+                }
+            },
+            Pointer::Scroll {
+                orientation: Orientation::Vertical,
+                value,
+            } if self.contains(x, y) && value != 0. => {
+                self.spin(ctx, -value.signum());
+            }
+            Pointer::Hover | Pointer::Enter => self.hovered = self.button_at(x, y),
+            Pointer::Leave => self.hovered = None,
+            _ => {}
+        }
+        if hovered != self.hovered || focused != self.focused {
+            return Damage::Some;
+        }
+        Damage::None
+    }
+}
+
+impl<M: PartialEq + TryIntoMessage<f32> + TryInto<f32>> Geometry for SpinButton<M> {
+    fn width(&self) -> f32 {
+        self.width + 2. * self.button_size()
+    }
+    fn height(&self) -> f32 {
+        self.height.ceil() + 2. * PADDING
+    }
+    fn set_width(&mut self, width: f32) -> Result<(), f32> {
+        let field = width - 2. * self.button_size();
+        if field > 2. * PADDING {
+            self.width = field;
+            return Ok(());
+        }
+        Err(self.width())
+    }
+    fn set_height(&mut self, _height: f32) -> Result<(), f32> {
+        Err(self.height())
+    }
+}
+
+impl<M: PartialEq + TryIntoMessage<f32> + TryInto<f32>> Widget<M> for SpinButton<M> {
+    fn create_node(&mut self, x: f32, y: f32) -> RenderNode {
+        let (width, height) = (self.width(), self.height());
+        let size = self.button_size();
+        let mut canvas = self.create_canvas(x, y);
+        let mut frame = self.frame.clone();
+        let _ = frame.set_size(width, height);
+        canvas.draw(0., 0., frame);
+        canvas.draw(PADDING, PADDING, self.display.deref().clone());
+        if self.focused && self.caret {
+            let caret = self.advances.get(self.cursor).copied().unwrap_or(0.);
+            canvas.draw_rectangle(
+                PADDING + caret,
+                PADDING,
+                CARET_WIDTH,
+                self.height,
+                self.display.get_color(),
+            );
+        }
+        for (i, (label, direction)) in [(&self.minus, -1.), (&self.plus, 1.)]
+            .into_iter()
+            .enumerate()
+        {
+            let bx = self.width + i as f32 * size;
+            if self.hovered == Some(direction) {
+                canvas.draw_rectangle(bx, 0., size, height, style::BG0);
+            } else {
+                canvas.draw_rectangle(bx, 0., 1., height, style::BG0);
+            }
+            canvas.draw(
+                bx + ((size - label.width()) / 2.).round(),
+                ((height - label.height()) / 2.).round(),
+                label.clone(),
+            );
+        }
+        canvas.finish()
+    }
+    fn sync<'d>(&'d mut self, ctx: &mut SyncContext<M>, event: Event<'d, M>) -> Damage {
+        let mut damage = match event {
+            Event::Pointer(x, y, pointer) => self.pointer(ctx, x, y, pointer),
+            Event::Keyboard(key) => self.keyboard(ctx, key),
+            Event::Message(msg) if self.message.as_ref() == Some(msg) => {
+                if let Ok(msg) = ctx.get(msg) {
+                    if let Ok(value) = msg.try_into() {
+                        self.set_value(value);
+                    }
+                }
+                Damage::None
+            }
+            Event::Callback(frame_time) => {
+                if self.focused {
+                    self.blink += frame_time;
+                    if self.blink >= BLINK {
+                        self.blink = 0;
+                        self.caret = !self.caret;
+                    }
+                    // Keeps receiving frame callbacks for the caret to blink
+                    Damage::Frame
+                } else {
+                    Damage::None
+                }
+            }
+            _ => Damage::None,
+        };
+        if self.edited {
+            self.edited = false;
+            self.display.edit(&self.text);
+            self.advances = ctx.font_cache().advances(&self.display, &self.text);
+            self.height = ctx.font_cache().line_height(&self.display);
+            damage = damage.max(Damage::Some);
+        }
+        for label in [&mut self.minus, &mut self.plus] {
+            damage = damage.max(Widget::<M>::sync(label, ctx, event));
+        }
+        damage.max(self.display.sync(ctx, event))
+    }
+}
+
+impl<M: PartialEq + TryIntoMessage<f32> + TryInto<f32>> Style for SpinButton<M> {
+    fn set_background<B: Into<scene::Background>>(&mut self, background: B) {
+        self.frame.set_background(background);
+    }
+    fn set_border(&mut self, color: u32, width: f32) {
+        self.frame.set_border(color, width);
+    }
+    fn set_border_color(&mut self, color: u32) {
+        self.frame.set_border_color(color);
+    }
+    fn set_radius(&mut self, tl: f32, tr: f32, br: f32, bl: f32) {
+        self.frame.set_radius(tl, tr, br, bl);
+    }
+    fn set_border_size(&mut self, size: f32) {
+        self.frame.set_border_size(size);
+    }
+    fn background<B: Into<scene::Background>>(mut self, background: B) -> Self {
+        self.set_background(background);
+        self
+    }
+    fn border(mut self, color: u32, size: f32) -> Self {
+        self.set_border(color, size);
+        self
+    }
+    fn border_color(mut self, color: u32) -> Self {
+        self.set_border_color(color);
+        self
+    }
+    fn border_size(mut self, size: f32) -> Self {
+        self.set_border_size(size);
+        self
+    }
+    fn radius(mut self, tl: f32, tr: f32, br: f32, bl: f32) -> Self {
+        self.set_radius(tl, tr, br, bl);
+        self
+    }
+}
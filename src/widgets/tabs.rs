@@ -0,0 +1,379 @@
+use crate::controller::*;
+use crate::widgets::button::{Button, Proxy};
+use crate::widgets::container::Child;
+use crate::widgets::extra::{Curve, Easer, Start};
+use crate::widgets::shapes::{ShapeStyle, Style, WidgetExt};
+use crate::widgets::text::Label;
+use crate::*;
+use scene::{Instruction, Region};
+use widgets::shapes::rectangle::Rectangle;
+
+// Thickness of the active tab indicator
+const INDICATOR: f32 = 2.;
+
+type Tab<M> = Button<
+    M,
+    WidgetExt<M, Label>,
+    fn(&mut Proxy<M, WidgetExt<M, Label>>, &mut SyncContext<M>, Pointer),
+>;
+
+// Highlights the tab under the pointer
+fn hover<M>(tab: &mut Proxy<M, WidgetExt<M, Label>>, _: &mut SyncContext<M>, pointer: Pointer) {
+    match pointer {
+        Pointer::Enter => tab.set_background(style::BG2),
+        Pointer::Leave => tab.set_background(style::TRANSPARENT),
+        _ => {}
+    }
+}
+
+// A row of tabs over the page of the current tab.
+// The message is sent with the index of the tab when the user switches to it.
+pub struct Tabs<M: TryIntoMessage<usize>> {
+    tabs: Vec<Tab<M>>,
+    pages: Vec<Child<M>>,
+    current: usize,
+    size: (Option<f32>, Option<f32>),
+    indicator: u32,
+    // Duration of the indicator's animation in ms
+    duration: u32,
+    // Where the indicator moves from and how far along it is
+    origin: (f32, f32),
+    progress: f32,
+    easer: Option<Easer>,
+    focused: bool,
+    message: Option<M>,
+}
+
+impl<M: TryIntoMessage<usize>> Default for Tabs<M> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<M: TryIntoMessage<usize>> Tabs<M> {
+    pub fn new() -> Self {
+        Tabs {
+            tabs: Vec::new(),
+            pages: Vec::new(),
+            current: 0,
+            size: (None, None),
+            indicator: style::BLU,
+            duration: 0,
+            origin: (0., 0.),
+            progress: 1.,
+            easer: None,
+            focused: false,
+            message: None,
+        }
+    }
+    pub fn push(&mut self, label: Label, page: impl Widget<M> + 'static) {
+        let tab: Tab<M> = Button::new(label.ext().padding(4., 8., 4., 8.), hover);
+        self.tabs.push(tab);
+        let mut page = Child::new(page);
+        if let Some(width) = self.size.0 {
+            let _ = page.set_width(width);
+        }
+        if let Some(height) = self.size.1 {
+            let _ = page.set_height(height - self.header_height());
+        }
+        self.pages.push(page);
+    }
+    pub fn tab(mut self, label: Label, page: impl Widget<M> + 'static) -> Self {
+        self.push(label, page);
+        self
+    }
+    pub fn indicator(mut self, color: u32) -> Self {
+        self.indicator = color;
+        self
+    }
+    pub fn set_indicator(&mut self, color: u32) {
+        self.indicator = color;
+    }
+    // Time in ms taken by the indicator to reach the current tab
+    pub fn animate(mut self, duration: u32) -> Self {
+        self.duration = duration;
+        self
+    }
+    pub fn message(mut self, message: M) -> Self {
+        self.message = Some(message);
+        self
+    }
+    pub fn len(&self) -> usize {
+        self.tabs.len()
+    }
+    pub fn is_empty(&self) -> bool {
+        self.tabs.is_empty()
+    }
+    pub fn current(&self) -> usize {
+        self.current
+    }
+    // Switches to the tab without sending the message
+    pub fn set_current(&mut self, index: usize) {
+        if index < self.tabs.len() && index != self.current {
+            self.origin = self.indicator_position();
+            self.current = index;
+            if self.duration > 0 {
+                self.progress = 0.;
+                self.easer = Some(Easer::new(Start::Min, 1., self.duration, Curve::Linear));
+            }
+        }
+    }
+    pub fn is_focused(&self) -> bool {
+        self.focused
+    }
+    pub fn set_focus(&mut self, focused: bool) {
+        self.focused = focused;
+    }
+    pub fn clear(&mut self) {
+        self.tabs.clear();
+        self.pages.clear();
+        self.current = 0;
+        self.easer = None;
+        self.progress = 1.;
+    }
+    fn header_width(&self) -> f32 {
+        self.tabs.iter().map(|tab| tab.width()).sum()
+    }
+    fn header_height(&self) -> f32 {
+        self.tabs.iter().map(|tab| tab.height()).fold(0., f32::max) + INDICATOR
+    }
+    // The offset and the width of a tab
+    fn tab_position(&self, index: usize) -> (f32, f32) {
+        let x = self.tabs[..index].iter().map(|tab| tab.width()).sum();
+        (x, self.tabs.get(index).map(|tab| tab.width()).unwrap_or(0.))
+    }
+    fn indicator_position(&self) -> (f32, f32) {
+        let (x, width) = self.tab_position(self.current);
+        let (ox, owidth) = self.origin;
+        let progress = self.progress.clamp(0., 1.);
+        (
+            (ox + (x - ox) * progress).round(),
+            (owidth + (width - owidth) * progress).round(),
+        )
+    }
+    fn tab_at(&self, x: f32, y: f32) -> Option<usize> {
+        if y < 0. || y >= self.header_height() {
+            return None;
+        }
+        let mut offset = 0.;
+        for (i, tab) in self.tabs.iter().enumerate() {
+            offset += tab.width();
+            if x >= 0. && x < offset {
+                return Some(i);
+            }
+        }
+        None
+    }
+    fn select(&mut self, ctx: &mut SyncContext<M>, index: usize) -> Damage {
+        if index >= self.tabs.len() || index == self.current {
+            return Damage::None;
+        }
+        self.set_current(index);
+        if let Some(message) = self.message.as_ref() {
+            if let Ok(msg) = TryIntoMessage::try_into(message, index) {
+                let _ = ctx.send(msg);
+            }
+        }
+        // The new page replaces the node of the previous one
+        let damage = self.pages[index].sync(ctx, Event::Redraw).max(Damage::Some);
+        if self.easer.is_some() {
+            return Damage::Frame;
+        }
+        damage
+    }
+    fn keyboard(&mut self, ctx: &mut SyncContext<M>, key: Key) -> Option<Damage> {
+        if !key.pressed || self.tabs.is_empty() {
+            return None;
+        }
+        let Modifiers { ctrl, shift, .. } = key.modifiers;
+        let last = self.tabs.len() - 1;
+        let previous = if self.current == 0 {
+            last
+        } else {
+            self.current - 1
+        };
+        let next = if self.current == last {
+            0
+        } else {
+            self.current + 1
+        };
+        let index = if ctrl && (key.is(keys::ISO_LEFT_TAB) || (shift && key.is(keys::TAB))) {
+            previous
+        } else if ctrl && key.is(keys::TAB) {
+            next
+        } else if ctrl && key.is(keys::PAGE_UP) {
+            self.current.saturating_sub(1)
+        } else if ctrl && key.is(keys::PAGE_DOWN) {
+            (self.current + 1).min(last)
+        } else if self.focused && key.is(keys::LEFT) {
+            self.current.saturating_sub(1)
+        } else if self.focused && key.is(keys::RIGHT) {
+            (self.current + 1).min(last)
+        } else if self.focused && key.is(keys::HOME) {
+            0
+        } else if self.focused && key.is(keys::END) {
+            last
+        } else {
+            return None;
+        };
+        Some(self.select(ctx, index))
+    }
+}
+
+impl<M: TryIntoMessage<usize>> Geometry for Tabs<M> {
+    fn width(&self) -> f32 {
+        self.size.0.unwrap_or_else(|| {
+            self.pages
+                .get(self.current)
+                .map(|page| page.width())
+                .unwrap_or(0.)
+                .max(self.header_width())
+        })
+    }
+    fn height(&self) -> f32 {
+        self.size.1.unwrap_or_else(|| {
+            self.pages
+                .get(self.current)
+                .map(|page| page.height())
+                .unwrap_or(0.)
+                + self.header_height()
+        })
+    }
+    fn set_width(&mut self, width: f32) -> Result<(), f32> {
+        if width > 0. {
+            self.size.0 = Some(width);
+            for page in self.pages.iter_mut() {
+                let _ = page.set_width(width);
+            }
+            return Ok(());
+        }
+        Err(self.width())
+    }
+    fn set_height(&mut self, height: f32) -> Result<(), f32> {
+        let header = self.header_height();
+        if height > header {
+            self.size.1 = Some(height);
+            for page in self.pages.iter_mut() {
+                let _ = page.set_height(height - header);
+            }
+            return Ok(());
+        }
+        Err(self.height())
+    }
+}
+
+impl<M: TryIntoMessage<usize>> Widget<M> for Tabs<M> {
+    fn create_node(&mut self, x: f32, y: f32) -> RenderNode {
+        let (width, height) = (self.width(), self.height());
+        let header = self.header_height();
+        let mut offset = 0.;
+        let mut nodes: Vec<RenderNode> = self
+            .tabs
+            .iter_mut()
+            .map(|tab| {
+                let node = tab.create_node(x + offset, y);
+                offset += tab.width();
+                node
+            })
+            .collect();
+        let (dx, length) = self.indicator_position();
+        nodes.push(
+            Instruction::new(
+                x + dx,
+                y + header - INDICATOR,
+                Rectangle::new(length, INDICATOR, ShapeStyle::solid(self.indicator)),
+            )
+            .into(),
+        );
+        if let Some(page) = self.pages.get_mut(self.current) {
+            page.set_coords(0., header);
+            let node = page.create_node(x, y);
+            // The previous page may have been larger
+            nodes.push(if node.is_none() {
+                node
+            } else {
+                RenderNode::Extension {
+                    background: Instruction::empty(x, y + header, width, height - header),
+                    border: None,
+                    node: Box::new(node),
+                }
+            });
+        }
+        RenderNode::Container {
+            region: Region::new(x, y, width, height),
+            nodes,
+        }
+    }
+    fn sync<'d>(&'d mut self, ctx: &mut SyncContext<M>, event: Event<'d, M>) -> Damage {
+        let mut damage = Damage::None;
+        match event {
+            Event::Pointer(x, y, pointer) => {
+                let mut offset = 0.;
+                for tab in self.tabs.iter_mut() {
+                    damage = damage.max(tab.sync(ctx, Event::Pointer(x - offset, y, pointer)));
+                    offset += tab.width();
+                }
+                if let Pointer::MouseClick {
+                    button,
+                    pressed: true,
+                    ..
+                } = pointer
+                {
+                    self.focused = false;
+                    if button.is_left() {
+                        if let Some(index) = self.tab_at(x, y) {
+                            self.focused = true;
+                            damage = damage.max(self.select(ctx, index));
+                        }
+                    }
+                }
+                if let Some(page) = self.pages.get_mut(self.current) {
+                    damage = damage.max(page.sync(ctx, event));
+                }
+            }
+            Event::Keyboard(key) => {
+                if let Some(switch) = self.keyboard(ctx, key) {
+                    return switch;
+                }
+                if let Some(page) = self.pages.get_mut(self.current) {
+                    damage = page.sync(ctx, event);
+                }
+            }
+            Event::Callback(frame_time) => {
+                if let Some(easer) = self.easer.as_mut() {
+                    easer.frame_time(frame_time);
+                    match easer.next() {
+                        Some(progress) => {
+                            self.progress = progress;
+                            damage = Damage::Frame;
+                        }
+                        None => {
+                            self.easer = None;
+                            self.progress = 1.;
+                            damage = Damage::Some;
+                        }
+                    }
+                }
+                for tab in self.tabs.iter_mut() {
+                    damage = damage.max(tab.sync(ctx, event));
+                }
+                if let Some(page) = self.pages.get_mut(self.current) {
+                    damage = damage.max(page.sync(ctx, event));
+                }
+            }
+            _ => {
+                for tab in self.tabs.iter_mut() {
+                    damage = damage.max(tab.sync(ctx, event));
+                }
+                // Hidden pages are kept up to date but only the current one is drawn
+                for (index, page) in self.pages.iter_mut().enumerate() {
+                    let page_damage = page.sync(ctx, event);
+                    if index == self.current {
+                        damage = damage.max(page_damage);
+                    }
+                }
+            }
+        }
+        damage
+    }
+}
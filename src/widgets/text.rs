@@ -1,26 +1,52 @@
-pub use crate::font::FontProperty;
+pub use crate::font::{FontProperty, GlyphPosition, Layout, TextStyle};
 use crate::{style::FG0, *};
+use font::FontCache;
 pub use fontdue::{
     layout,
-    layout::{
-        CoordinateSystem, GlyphPosition, GlyphRasterConfig, Layout, LayoutSettings, TextStyle,
-    },
+    layout::{CoordinateSystem, GlyphRasterConfig, LayoutSettings},
     Font, FontResult, FontSettings,
 };
 use scene::Instruction;
+use std::cell::{Cell, OnceCell};
 use std::ops::{Deref, DerefMut};
 use std::rc::Rc;
 use tiny_skia::*;
 
+// Stroke drawn around the glyphs
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Outline {
+    pub color: Color,
+    pub width: f32,
+}
+
+// Copy of the text drawn behind it
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Shadow {
+    pub color: Color,
+    pub x: f32,
+    pub y: f32,
+    pub blur: f32,
+}
+
 #[derive(Clone)]
 pub struct Label {
     text: String,
     font_size: f32,
     color: Color,
+    line_height: f32,
+    letter_spacing: f32,
+    paragraph_spacing: f32,
+    // Boxed to keep labels small in render nodes
+    outline: Option<Box<Outline>>,
+    shadow: Option<Box<Shadow>>,
+    underline: bool,
+    strikethrough: bool,
     settings: LayoutSettings,
     fonts: Vec<FontProperty>,
-    layout: Option<Rc<[GlyphPosition]>>,
-    size: (f32, f32),
+    layout: OnceCell<Rc<[GlyphPosition]>>,
+    size: Cell<(f32, f32)>,
+    // The text with its decorations, shared with the clones of the label
+    layer: Rc<OnceCell<Option<Pixmap>>>,
 }
 
 impl Label {
@@ -30,17 +56,47 @@ impl Label {
     pub fn get_font_size(&self) -> f32 {
         self.font_size
     }
+    pub fn get_line_height(&self) -> f32 {
+        self.line_height
+    }
+    pub fn get_letter_spacing(&self) -> f32 {
+        self.letter_spacing
+    }
+    pub fn get_paragraph_spacing(&self) -> f32 {
+        self.paragraph_spacing
+    }
+    pub fn get_outline(&self) -> Option<Outline> {
+        self.outline.as_deref().copied()
+    }
+    pub fn get_shadow(&self) -> Option<Shadow> {
+        self.shadow.as_deref().copied()
+    }
+    pub fn is_underlined(&self) -> bool {
+        self.underline
+    }
+    pub fn is_strikethrough(&self) -> bool {
+        self.strikethrough
+    }
+    pub fn is_decorated(&self) -> bool {
+        self.outline.is_some() || self.shadow.is_some() || self.underline || self.strikethrough
+    }
     pub fn fonts(&self) -> &[FontProperty] {
         &self.fonts
     }
     pub fn max_width(&self) -> f32 {
-        self.settings.max_width.unwrap_or(self.size.0)
+        self.settings.max_width.unwrap_or(self.size().0)
     }
     pub fn max_height(&self) -> f32 {
-        self.settings.max_height.unwrap_or(self.size.1)
+        self.settings.max_height.unwrap_or(self.size().1)
     }
     pub fn set_color(&mut self, color: u32) {
-        self.color = u32_to_source(color);
+        self.set_source(u32_to_source(color));
+    }
+    pub fn set_source(&mut self, source: Color) {
+        if self.color != source {
+            self.layer = Rc::default();
+        }
+        self.color = source;
     }
     pub fn get_color(&self) -> Color {
         self.color
@@ -49,13 +105,43 @@ impl Label {
         &self.settings
     }
     pub fn get_layout(&self) -> Option<&Rc<[GlyphPosition]>> {
-        self.layout.as_ref()
+        self.layout.get()
+    }
+    // The label is measured with the global FontCache until it's synced.
+    // If the cache is in use, like while drawing, the last size is returned.
+    fn size(&self) -> (f32, f32) {
+        if self.layout.get().is_none() {
+            if let Some(mut font_cache) = font::global().try_borrow_mut() {
+                let layout = font_cache.layout(self);
+                self.set_layout(&mut font_cache, layout);
+            }
+        }
+        self.size.get()
+    }
+    // The size includes the room taken by the decorations
+    fn set_layout(&self, font_cache: &mut FontCache, layout: Vec<GlyphPosition>) {
+        self.size.set(font_cache.decorated_size(self, &layout));
+        let _ = self.layout.set(layout.into());
+    }
+    fn reset_layout(&mut self) {
+        self.layout = OnceCell::new();
+        self.layer = Rc::default();
+    }
+    pub(crate) fn decorated_layer<F: FnOnce() -> Option<Pixmap>>(&self, f: F) -> Option<&Pixmap> {
+        self.layer.get_or_init(f).as_ref()
     }
 }
 
 impl PartialEq for Label {
     fn eq(&self, other: &Self) -> bool {
         self.font_size == other.font_size
+            && self.line_height == other.line_height
+            && self.letter_spacing == other.letter_spacing
+            && self.paragraph_spacing == other.paragraph_spacing
+            && self.outline == other.outline
+            && self.shadow == other.shadow
+            && self.underline == other.underline
+            && self.strikethrough == other.strikethrough
             && self.text == other.text
             && self.color == other.color
             && self.settings == other.settings
@@ -72,7 +158,7 @@ impl std::fmt::Debug for Label {
             .field("font_size", &self.font_size)
             .field("color", &self.color)
             .field("fonts", &self.fonts)
-            .field("dimension", &self.size)
+            .field("dimension", &self.size.get())
             .finish()
     }
 }
@@ -90,21 +176,78 @@ impl Label {
             font_size,
             fonts: Vec::new(),
             settings: LayoutSettings::default(),
+            line_height: 1.,
+            letter_spacing: 0.,
+            paragraph_spacing: 0.,
+            outline: None,
+            shadow: None,
+            underline: false,
+            strikethrough: false,
             color: u32_to_source(FG0),
-            layout: None,
-            size: (0., 0.),
+            layout: OnceCell::new(),
+            size: Cell::new((0., 0.)),
+            layer: Rc::default(),
         }
     }
     pub fn font<F: Into<FontProperty>>(mut self, font: F) -> Self {
         self.fonts.push(font.into());
+        self.reset_layout();
         self
     }
     pub fn color(mut self, color: u32) -> Self {
-        self.color = u32_to_source(color);
+        self.set_color(color);
         self
     }
     pub fn settings(mut self, settings: LayoutSettings) -> Self {
         self.settings = settings;
+        self.reset_layout();
+        self
+    }
+    // Scales the distance between lines
+    pub fn line_height(mut self, line_height: f32) -> Self {
+        self.line_height = line_height;
+        self.reset_layout();
+        self
+    }
+    // Space in pixels added after each character
+    pub fn letter_spacing(mut self, letter_spacing: f32) -> Self {
+        self.letter_spacing = letter_spacing;
+        self.reset_layout();
+        self
+    }
+    pub fn outline(mut self, color: u32, width: f32) -> Self {
+        self.outline = Some(Box::new(Outline {
+            color: u32_to_source(color),
+            width,
+        }));
+        self.reset_layout();
+        self
+    }
+    // The shadow is offset by x and y and blurred over the blur radius
+    pub fn shadow(mut self, color: u32, x: f32, y: f32, blur: f32) -> Self {
+        self.shadow = Some(Box::new(Shadow {
+            color: u32_to_source(color),
+            x,
+            y,
+            blur,
+        }));
+        self.reset_layout();
+        self
+    }
+    pub fn underline(mut self) -> Self {
+        self.underline = true;
+        self.reset_layout();
+        self
+    }
+    pub fn strikethrough(mut self) -> Self {
+        self.strikethrough = true;
+        self.reset_layout();
+        self
+    }
+    // Space in pixels added after each line break
+    pub fn paragraph_spacing(mut self, paragraph_spacing: f32) -> Self {
+        self.paragraph_spacing = paragraph_spacing;
+        self.reset_layout();
         self
     }
     pub fn default(text: &str, font_size: f32) -> Label {
@@ -113,27 +256,35 @@ impl Label {
             font_size,
             settings: LayoutSettings::default(),
             fonts: vec![FontProperty::new("sans serif")],
+            line_height: 1.,
+            letter_spacing: 0.,
+            paragraph_spacing: 0.,
+            outline: None,
+            shadow: None,
+            underline: false,
+            strikethrough: false,
             color: u32_to_source(FG0),
-            layout: None,
-            size: (0., 0.),
+            layout: OnceCell::new(),
+            size: Cell::new((0., 0.)),
+            layer: Rc::default(),
         }
     }
 }
 
 impl Geometry for Label {
     fn width(&self) -> f32 {
-        self.size.0
+        self.size().0
     }
     fn height(&self) -> f32 {
-        self.size.1
+        self.size().1
     }
     fn set_width(&mut self, width: f32) -> Result<(), f32> {
         self.settings.max_width = Some(width);
-        Err(self.size.0)
+        Err(self.width())
     }
     fn set_height(&mut self, height: f32) -> Result<(), f32> {
         self.settings.max_height = Some(height);
-        Err(self.size.1)
+        Err(self.height())
     }
 }
 
@@ -142,10 +293,10 @@ impl<M> Widget<M> for Label {
         RenderNode::Instruction(Instruction::new(x, y, self.clone()))
     }
     fn sync<'d>(&'d mut self, ctx: &mut SyncContext<M>, _event: Event<'d, M>) -> Damage {
-        if self.layout.is_none() {
-            let layout = ctx.font_cache.layout(self).glyphs().clone();
-            self.size = font::get_size(&layout);
-            self.layout = Some(layout.into());
+        if self.layout.get().is_none() {
+            let mut font_cache = ctx.font_cache();
+            let layout = font_cache.layout(self);
+            self.set_layout(&mut font_cache, layout);
             Damage::Some
         } else {
             Damage::None
@@ -187,7 +338,7 @@ impl Text {
     }
     pub fn edit(&mut self, s: &str) {
         if s.ne(self.label.text.as_str()) {
-            self.label.layout = None;
+            self.label.reset_layout();
         }
         self.buffer = None;
         self.label.text = s.to_string();
@@ -215,10 +366,16 @@ impl<M> Widget<M> for Text {
     }
     fn sync<'d>(&'d mut self, ctx: &mut SyncContext<M>, event: Event<'d, M>) -> Damage {
         if let Some(string) = &self.buffer {
-            ctx.font_cache.write(&mut self.layout, &self.label, string);
-            let glyphs = self.layout.glyphs().clone();
-            self.label.size = font::get_size(&glyphs);
-            self.label.layout = Some(glyphs.into());
+            // Lines are wrapped from the start of the text
+            if self.label.get_settings().max_width.is_some() {
+                self.buffer = None;
+                self.label.reset_layout();
+                return self.label.sync(ctx, event);
+            }
+            let mut font_cache = ctx.font_cache();
+            let glyphs = font_cache.write(&mut self.layout, &self.label, string);
+            self.label.reset_layout();
+            self.label.set_layout(&mut font_cache, glyphs);
             self.buffer = None;
             Damage::Some
         } else {
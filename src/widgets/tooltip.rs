@@ -0,0 +1,234 @@
+use crate::context::{Popup, PopupId};
+use crate::scene::{Instruction, Region};
+use crate::widgets::shapes::{Rectangle, ShapeStyle, Style};
+use crate::widgets::text::Label;
+use crate::*;
+use std::marker::PhantomData;
+use std::ops::{Deref, DerefMut};
+
+// Space around the text of the bubble
+const PADDING: f32 = 4.;
+// Distance between the pointer and the bubble so the cursor doesn't cover it
+const CURSOR: f32 = 16.;
+
+// Shows a bubble with some text near the pointer once it rested over the widget.
+// The bubble is shown in a popup if the backend has them,
+// otherwise it's drawn over the widget, inside of its region.
+pub struct Tooltip<M, W: Widget<M>> {
+    widget: W,
+    label: Label,
+    background: u32,
+    // Time in ms the pointer must rest before the bubble shows
+    delay: u32,
+    // Time the pointer has rested so far
+    dwell: Option<u32>,
+    pointer: (f32, f32),
+    visible: bool,
+    // The bubble stays hidden after a click until the pointer leaves
+    dismissed: bool,
+    popups: bool,
+    popup: PopupId,
+    origin: (f32, f32),
+    _request: PhantomData<M>,
+}
+
+impl<M, W: Widget<M>> Tooltip<M, W> {
+    pub fn new(widget: W, text: &str) -> Self {
+        Self {
+            widget,
+            label: Label::default(text, 14.),
+            background: style::BG0,
+            delay: 500,
+            dwell: None,
+            pointer: (0., 0.),
+            visible: false,
+            dismissed: false,
+            popups: false,
+            popup: PopupId::new(),
+            origin: (0., 0.),
+            _request: PhantomData,
+        }
+    }
+    pub fn delay(mut self, delay: u32) -> Self {
+        self.delay = delay;
+        self
+    }
+    pub fn set_delay(&mut self, delay: u32) {
+        self.delay = delay;
+    }
+    // Color of the bubble
+    pub fn bubble(mut self, color: u32) -> Self {
+        self.background = color;
+        self
+    }
+    pub fn set_tooltip(&mut self, text: &str) {
+        if text != self.label.get_text() {
+            self.label = Label::default(text, self.label.get_font_size());
+        }
+    }
+    pub fn is_visible(&self) -> bool {
+        self.visible
+    }
+    fn bubble_size(&self) -> (f32, f32) {
+        (
+            self.label.width().ceil() + 2. * PADDING,
+            self.label.height().ceil() + 2. * PADDING,
+        )
+    }
+    // Where the bubble fits in the widget near the pointer
+    fn bubble_position(&self) -> (f32, f32) {
+        let (width, height) = self.bubble_size();
+        let (x, y) = self.pointer;
+        let y = if y + CURSOR + height <= self.height() {
+            y + CURSOR
+        } else {
+            y - height
+        };
+        (
+            x.min(self.width() - width).max(0.),
+            y.min(self.height() - height).max(0.),
+        )
+    }
+    fn bubble_node(&self, x: f32, y: f32) -> RenderNode {
+        let (width, height) = self.bubble_size();
+        RenderNode::Extension {
+            background: Instruction::new(
+                x,
+                y,
+                Rectangle::new(width, height, ShapeStyle::solid(self.background))
+                    .radius(PADDING, PADDING, PADDING, PADDING),
+            ),
+            border: None,
+            node: Box::new(Instruction::new(x + PADDING, y + PADDING, self.label.clone()).into()),
+        }
+    }
+    fn hide(&mut self, ctx: &mut SyncContext<M>) -> Damage {
+        self.dwell = None;
+        if self.visible {
+            self.visible = false;
+            if self.popups {
+                ctx.hide_popup(self.popup);
+            } else {
+                return Damage::Some;
+            }
+        }
+        Damage::None
+    }
+    fn show(&mut self, ctx: &mut SyncContext<M>) -> Damage {
+        self.dwell = None;
+        self.visible = true;
+        if self.popups {
+            let (x, y) = (
+                self.origin.0 + self.pointer.0,
+                self.origin.1 + self.pointer.1,
+            );
+            let (width, height) = self.bubble_size();
+            ctx.show_popup(
+                self.popup,
+                Popup {
+                    anchor: Region::new(x, y, 1., 1.),
+                    region: Region::new(x, y + CURSOR, width, height),
+                    node: self.bubble_node(0., 0.),
+                },
+            );
+            return Damage::None;
+        }
+        Damage::Some
+    }
+}
+
+impl<M, W: Widget<M>> Geometry for Tooltip<M, W> {
+    fn width(&self) -> f32 {
+        self.widget.width()
+    }
+    fn height(&self) -> f32 {
+        self.widget.height()
+    }
+    fn set_width(&mut self, width: f32) -> Result<(), f32> {
+        self.widget.set_width(width)
+    }
+    fn set_height(&mut self, height: f32) -> Result<(), f32> {
+        self.widget.set_height(height)
+    }
+}
+
+impl<M, W: Widget<M>> Widget<M> for Tooltip<M, W> {
+    fn create_node(&mut self, x: f32, y: f32) -> RenderNode {
+        self.origin = (x, y);
+        let node = self.widget.create_node(x, y);
+        if self.popups {
+            return node;
+        }
+        let region = Region::new(x, y, self.width(), self.height());
+        let mut nodes = vec![node];
+        if self.visible {
+            let (bx, by) = self.bubble_position();
+            nodes.push(self.bubble_node(x + bx, y + by));
+        }
+        // The bubble covers parts of the widget so they're drawn again together
+        RenderNode::Clip {
+            region,
+            node: Box::new(RenderNode::Container { region, nodes }),
+        }
+    }
+    fn sync<'d>(&'d mut self, ctx: &mut SyncContext<M>, event: Event<'d, M>) -> Damage {
+        self.popups = ctx.has_popups();
+        let damage =
+            Widget::<M>::sync(&mut self.label, ctx, event).max(self.widget.sync(ctx, event));
+        let tooltip = match event {
+            Event::Pointer(x, y, pointer) => match pointer {
+                Pointer::Leave => {
+                    self.dismissed = false;
+                    self.hide(ctx)
+                }
+                _ if !self.contains(x, y) => {
+                    self.dismissed = false;
+                    self.hide(ctx)
+                }
+                Pointer::MouseClick { .. } => {
+                    self.dismissed = true;
+                    self.hide(ctx)
+                }
+                Pointer::Hover if !self.visible && !self.dismissed => {
+                    let resting = self.dwell.is_some() && self.pointer == (x, y);
+                    self.pointer = (x, y);
+                    if !resting {
+                        self.dwell = Some(0);
+                        // Frame callbacks measure how long the pointer rests
+                        return damage.max(Damage::Frame);
+                    }
+                    Damage::None
+                }
+                _ => Damage::None,
+            },
+            Event::Callback(frame_time) => match self.dwell {
+                Some(dwell) if dwell + frame_time >= self.delay => self.show(ctx),
+                Some(dwell) => {
+                    self.dwell = Some(dwell + frame_time);
+                    Damage::Frame
+                }
+                None => Damage::None,
+            },
+            // The popup of another widget replaced the bubble
+            Event::PopupDone(owner) if owner == self.popup => {
+                self.visible = false;
+                Damage::None
+            }
+            _ => Damage::None,
+        };
+        damage.max(tooltip)
+    }
+}
+
+impl<M, W: Widget<M>> Deref for Tooltip<M, W> {
+    type Target = W;
+    fn deref(&self) -> &Self::Target {
+        &self.widget
+    }
+}
+
+impl<M, W: Widget<M>> DerefMut for Tooltip<M, W> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.widget
+    }
+}
@@ -0,0 +1,78 @@
+use snui::context::SyncContext;
+use snui::scene::RenderNode;
+use snui::widgets::container::FlexBox;
+use snui::{Damage, Event, Geometry, Widget, WidgetUtil};
+use std::cell::Cell;
+use std::rc::Rc;
+
+// A box taking the width it's given
+struct Block(Rc<Cell<f32>>);
+
+impl Block {
+    fn new(width: f32) -> (Self, Rc<Cell<f32>>) {
+        let width = Rc::new(Cell::new(width));
+        (Block(width.clone()), width)
+    }
+}
+
+impl Geometry for Block {
+    fn width(&self) -> f32 {
+        self.0.get()
+    }
+    fn height(&self) -> f32 {
+        10.
+    }
+    fn set_width(&mut self, width: f32) -> Result<(), f32> {
+        self.0.set(width);
+        Ok(())
+    }
+}
+
+impl Widget<()> for Block {
+    fn create_node(&mut self, _: f32, _: f32) -> RenderNode {
+        RenderNode::None
+    }
+    fn sync<'d>(&'d mut self, _: &mut SyncContext<()>, _: Event<'d, ()>) -> Damage {
+        Damage::None
+    }
+}
+
+#[test]
+fn flex_layout_is_idempotent() {
+    let (first, a) = Block::new(50.);
+    let (second, b) = Block::new(50.);
+    let (third, c) = Block::new(100.);
+    let widths = || [a.get(), b.get(), c.get()];
+    let mut flexbox: FlexBox<()> = [first.child(), second.child().grow(1.), third.child()]
+        .into_iter()
+        .collect();
+    flexbox.set_width(300.).unwrap();
+    flexbox.create_node(0., 0.);
+    assert_eq!(widths(), [50., 150., 100.]);
+    flexbox.create_node(0., 0.);
+    assert_eq!(widths(), [50., 150., 100.]);
+
+    // The children shrink from their natural size
+    flexbox.set_width(100.).unwrap();
+    flexbox.create_node(0., 0.);
+    assert_eq!(widths(), [25., 25., 50.]);
+    flexbox.create_node(0., 0.);
+    assert_eq!(widths(), [25., 25., 50.]);
+
+    flexbox.set_width(300.).unwrap();
+    flexbox.create_node(0., 0.);
+    assert_eq!(widths(), [50., 150., 100.]);
+}
+
+#[test]
+fn child_contains() {
+    let (block, _) = Block::new(50.);
+    let mut child = block.child();
+    child.set_coords(100., 20.);
+    assert!(child.contains(100., 20.));
+    assert!(child.contains(149., 29.));
+    assert!(!child.contains(0., 0.));
+    assert!(!child.contains(151., 20.));
+    // Adding the coords used to hit the child mirrored around the origin
+    assert!(!child.contains(-90., -15.));
+}
@@ -0,0 +1,60 @@
+use snui::context::SyncContext;
+use snui::controller::{DummyController, IntoMessage};
+use snui::font::FontCache;
+use snui::widgets::container::{Layer, Overlay};
+use snui::widgets::text::Label;
+use snui::widgets::{Alignment, Dropdown};
+use snui::{Event, Geometry, MouseButton, Pointer, Widget};
+
+const MONO: &[u8] = include_bytes!("fonts/DejaVuSansMono.ttf");
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Selected(usize);
+
+impl IntoMessage<usize> for Selected {
+    fn into(&self, index: usize) -> Self {
+        Selected(index)
+    }
+}
+
+fn click(y: f32) -> Event<'static, Selected> {
+    Event::Pointer(
+        5.,
+        y,
+        Pointer::MouseClick {
+            time: 0,
+            button: MouseButton::Left,
+            pressed: true,
+        },
+    )
+}
+
+#[test]
+fn list_without_popups() {
+    let mut font_cache = FontCache::new();
+    font_cache.load_font_bytes("Mono", MONO).unwrap();
+    let mut controller = DummyController::new(Selected(0));
+    let mut ctx = SyncContext::new(&mut controller, &mut font_cache);
+    let dropdown = ["one", "two", "three"]
+        .into_iter()
+        .fold(Dropdown::new(), |dropdown, text| {
+            dropdown.option(Label::new(text, 15.).font("Mono"))
+        })
+        .message(Selected(0));
+    let mut overlay = Overlay::new().with(
+        dropdown,
+        Layer::new().anchor(Alignment::Start, Alignment::Start),
+    );
+    overlay.sync(&mut ctx, Event::Frame);
+    let row = overlay.height();
+    assert!(row > 0.);
+
+    // The list is part of the dropdown while it's open
+    overlay.sync(&mut ctx, click(1.));
+    overlay.create_node(0., 0.);
+    assert_eq!(overlay.height(), 4. * row);
+
+    // The containers send the clicks on the list to the dropdown which closes it
+    overlay.sync(&mut ctx, click(2.5 * row));
+    assert_eq!(overlay.height(), row);
+}
@@ -0,0 +1,175 @@
+use snui::context::SyncContext;
+use snui::controller::{DummyController, IntoMessage};
+use snui::font::FontCache;
+use snui::widgets::text::Label;
+use snui::widgets::TextEditor;
+use snui::{keys, Event, Geometry, Key, Modifiers, Widget};
+
+const MONO: &[u8] = include_bytes!("fonts/DejaVuSansMono.ttf");
+
+#[derive(Debug, Clone, PartialEq)]
+struct Edited(String);
+
+impl IntoMessage<String> for Edited {
+    fn into(&self, text: String) -> Self {
+        Edited(text)
+    }
+}
+
+fn font_cache() -> FontCache {
+    let mut font_cache = FontCache::new();
+    font_cache.load_font_bytes("Mono", MONO).unwrap();
+    font_cache
+}
+
+fn label(text: &str) -> Label {
+    Label::new(text, 15.).font("Mono")
+}
+
+fn modifiers(ctrl: bool, shift: bool) -> Modifiers {
+    Modifiers {
+        ctrl,
+        shift,
+        ..Modifiers::default()
+    }
+}
+
+fn press(
+    editor: &mut TextEditor<Edited>,
+    ctx: &mut SyncContext<Edited>,
+    keysym: u32,
+    modifiers: Modifiers,
+) {
+    let value = [keysym];
+    let key = Key {
+        utf8: None,
+        value: &value,
+        modifiers,
+        pressed: true,
+    };
+    editor.sync(ctx, Event::Keyboard(key));
+}
+
+fn write(editor: &mut TextEditor<Edited>, ctx: &mut SyncContext<Edited>, text: &str) {
+    for c in text.chars() {
+        let utf8 = c.to_string();
+        let value = [c as u32];
+        let key = Key {
+            utf8: Some(&utf8),
+            value: &value,
+            modifiers: Modifiers::default(),
+            pressed: true,
+        };
+        editor.sync(ctx, Event::Keyboard(key));
+    }
+}
+
+#[test]
+fn undo_redo() {
+    let mut font_cache = font_cache();
+    let mut controller = DummyController::new(Edited(String::new()));
+    let mut ctx = SyncContext::new(&mut controller, &mut font_cache);
+    let mut editor: TextEditor<Edited> = label("").into();
+    editor.set_focus(true);
+
+    // Typing is undone word by word
+    write(&mut editor, &mut ctx, "hello world");
+    press(&mut editor, &mut ctx, keys::Z, modifiers(true, false));
+    assert_eq!(editor.text(), "hello ");
+    press(&mut editor, &mut ctx, keys::Z, modifiers(true, false));
+    assert_eq!(editor.text(), "hello");
+    press(&mut editor, &mut ctx, keys::Z, modifiers(true, false));
+    assert_eq!(editor.text(), "");
+    assert!(!editor.can_undo());
+
+    // Shift turns the keysym of Z into its uppercase
+    press(&mut editor, &mut ctx, keys::Z_UPPER, modifiers(true, true));
+    assert_eq!(editor.text(), "hello");
+    press(&mut editor, &mut ctx, keys::Z_UPPER, modifiers(true, true));
+    press(&mut editor, &mut ctx, keys::Y, modifiers(true, false));
+    assert_eq!(editor.text(), "hello world");
+    assert_eq!(editor.cursor(), 11);
+    assert!(!editor.can_redo());
+
+    // So is deleting
+    for _ in 0..3 {
+        press(&mut editor, &mut ctx, keys::BACKSPACE, Modifiers::default());
+    }
+    assert_eq!(editor.text(), "hello wo");
+    press(&mut editor, &mut ctx, keys::Z, modifiers(true, false));
+    assert_eq!(editor.text(), "hello world");
+
+    // A new edit clears what could be redone
+    press(&mut editor, &mut ctx, keys::Z, modifiers(true, false));
+    write(&mut editor, &mut ctx, "there");
+    assert_eq!(editor.text(), "hello there");
+    assert!(!editor.can_redo());
+}
+
+#[test]
+fn selection() {
+    let mut font_cache = font_cache();
+    let mut controller = DummyController::new(Edited(String::new()));
+    let mut ctx = SyncContext::new(&mut controller, &mut font_cache);
+    let mut editor: TextEditor<Edited> = label("one two\nthree").into();
+    editor.set_focus(true);
+
+    press(&mut editor, &mut ctx, keys::LEFT, modifiers(true, true));
+    assert_eq!(editor.selected_text(), "three");
+    press(&mut editor, &mut ctx, keys::LEFT, modifiers(true, true));
+    assert_eq!(editor.selected_text(), "two\nthree");
+    press(&mut editor, &mut ctx, keys::RETURN, Modifiers::default());
+    assert_eq!(editor.text(), "one \n");
+
+    press(&mut editor, &mut ctx, keys::A, modifiers(true, false));
+    assert_eq!(editor.selected_text(), "one \n");
+    press(&mut editor, &mut ctx, keys::DELETE, Modifiers::default());
+    assert_eq!(editor.text(), "");
+}
+
+#[test]
+fn keys_before_layout() {
+    let mut font_cache = font_cache();
+    let mut controller = DummyController::new(Edited(String::new()));
+    let mut ctx = SyncContext::new(&mut controller, &mut font_cache);
+    let mut editor: TextEditor<Edited> = label("abc\ndef").into();
+
+    // The editor is focused before it's synced for the first time
+    editor.set_focus(true);
+    press(&mut editor, &mut ctx, keys::HOME, Modifiers::default());
+    assert_eq!(editor.cursor(), 4);
+    press(&mut editor, &mut ctx, keys::UP, Modifiers::default());
+    assert_eq!(editor.cursor(), 0);
+    press(&mut editor, &mut ctx, keys::END, Modifiers::default());
+    assert_eq!(editor.cursor(), 3);
+}
+
+#[test]
+fn wrap() {
+    let mut font_cache = font_cache();
+    let advance = font_cache.advances(&label("a"), "a")[1];
+    let mut controller = DummyController::new(Edited(String::new()));
+    let mut ctx = SyncContext::new(&mut controller, &mut font_cache);
+    let mut editor: TextEditor<Edited> = label("aaa bbb").into();
+    // Fits four characters and the caret
+    editor.set_width(4.5 * advance + 1.).unwrap();
+    editor.sync(&mut ctx, Event::Frame);
+    editor.set_focus(true);
+
+    // The caret moves through the wrapped lines
+    press(&mut editor, &mut ctx, keys::HOME, Modifiers::default());
+    assert_eq!(editor.cursor(), 4);
+    press(&mut editor, &mut ctx, keys::UP, Modifiers::default());
+    assert_eq!(editor.cursor(), 0);
+    // The end of a wrapped line is before the space it was broken at
+    press(&mut editor, &mut ctx, keys::END, Modifiers::default());
+    assert_eq!(editor.cursor(), 3);
+    press(&mut editor, &mut ctx, keys::DOWN, Modifiers::default());
+    assert_eq!(editor.cursor(), 7);
+
+    // The lines are wrapped again when the width changes
+    editor.set_width(10. * advance).unwrap();
+    editor.sync(&mut ctx, Event::Frame);
+    press(&mut editor, &mut ctx, keys::HOME, Modifiers::default());
+    assert_eq!(editor.cursor(), 0);
+}
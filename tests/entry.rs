@@ -0,0 +1,184 @@
+use snui::context::SyncContext;
+use snui::controller::{DummyController, IntoMessage};
+use snui::font::FontCache;
+use snui::widgets::text::Label;
+use snui::widgets::Entry;
+use snui::{keys, Event, Key, Modifiers, MouseButton, Pointer, Widget};
+
+const MONO: &[u8] = include_bytes!("fonts/DejaVuSansMono.ttf");
+
+#[derive(Debug, Clone, PartialEq)]
+struct Edited(String);
+
+impl IntoMessage<String> for Edited {
+    fn into(&self, text: String) -> Self {
+        Edited(text)
+    }
+}
+
+fn font_cache() -> FontCache {
+    let mut font_cache = FontCache::new();
+    font_cache.load_font_bytes("Mono", MONO).unwrap();
+    font_cache
+}
+
+fn label(text: &str) -> Label {
+    Label::new(text, 15.).font("Mono")
+}
+
+fn modifiers(ctrl: bool, shift: bool) -> Modifiers {
+    Modifiers {
+        ctrl,
+        shift,
+        ..Modifiers::default()
+    }
+}
+
+fn press(
+    entry: &mut Entry<Edited>,
+    ctx: &mut SyncContext<Edited>,
+    keysym: u32,
+    modifiers: Modifiers,
+) {
+    let value = [keysym];
+    let key = Key {
+        utf8: None,
+        value: &value,
+        modifiers,
+        pressed: true,
+    };
+    entry.sync(ctx, Event::Keyboard(key));
+}
+
+fn write(entry: &mut Entry<Edited>, ctx: &mut SyncContext<Edited>, text: &str) {
+    for c in text.chars() {
+        let utf8 = c.to_string();
+        let value = [c as u32];
+        let key = Key {
+            utf8: Some(&utf8),
+            value: &value,
+            modifiers: Modifiers::default(),
+            pressed: true,
+        };
+        entry.sync(ctx, Event::Keyboard(key));
+    }
+}
+
+fn click(entry: &mut Entry<Edited>, ctx: &mut SyncContext<Edited>, x: f32, time: u32) {
+    for pressed in [true, false] {
+        let pointer = Pointer::MouseClick {
+            time,
+            button: MouseButton::Left,
+            pressed,
+        };
+        entry.sync(ctx, Event::Pointer(x, 1., pointer));
+    }
+}
+
+#[test]
+fn editing() {
+    let mut font_cache = font_cache();
+    let mut controller = DummyController::new(Edited(String::new()));
+    let mut ctx = SyncContext::new(&mut controller, &mut font_cache);
+    let mut entry: Entry<Edited> = label("").into();
+
+    // Keys are ignored until the Entry has the focus
+    write(&mut entry, &mut ctx, "hello");
+    assert_eq!(entry.text(), "");
+
+    entry.set_focus(true);
+    write(&mut entry, &mut ctx, "hello world");
+    assert_eq!(entry.text(), "hello world");
+    assert_eq!(entry.cursor(), 11);
+
+    press(&mut entry, &mut ctx, keys::LEFT, modifiers(true, false));
+    assert_eq!(entry.cursor(), 6);
+    press(&mut entry, &mut ctx, keys::END, modifiers(false, true));
+    assert_eq!(entry.selected_text(), "world");
+    write(&mut entry, &mut ctx, "there");
+    assert_eq!(entry.text(), "hello there");
+
+    press(&mut entry, &mut ctx, keys::HOME, Modifiers::default());
+    assert_eq!(entry.cursor(), 0);
+    press(&mut entry, &mut ctx, keys::DELETE, Modifiers::default());
+    assert_eq!(entry.text(), "ello there");
+
+    // Moving without shift collapses the selection to its start
+    press(&mut entry, &mut ctx, keys::RIGHT, modifiers(false, true));
+    press(&mut entry, &mut ctx, keys::RIGHT, modifiers(false, true));
+    assert_eq!(entry.selected_text(), "el");
+    press(&mut entry, &mut ctx, keys::LEFT, Modifiers::default());
+    assert_eq!(entry.cursor(), 0);
+    assert_eq!(entry.selected_text(), "");
+
+    press(&mut entry, &mut ctx, keys::END, Modifiers::default());
+    press(
+        &mut entry,
+        &mut ctx,
+        keys::BACKSPACE,
+        modifiers(true, false),
+    );
+    assert_eq!(entry.text(), "ello ");
+
+    press(&mut entry, &mut ctx, keys::A, modifiers(true, false));
+    assert_eq!(entry.selected_text(), "ello ");
+    press(&mut entry, &mut ctx, keys::BACKSPACE, Modifiers::default());
+    assert_eq!(entry.text(), "");
+    assert_eq!(entry.cursor(), 0);
+}
+
+#[test]
+fn password() {
+    let mut font_cache = font_cache();
+    let mut controller = DummyController::new(Edited(String::new()));
+    let mut ctx = SyncContext::new(&mut controller, &mut font_cache);
+    let mut entry: Entry<Edited> = Entry::from(label("")).password();
+    entry.set_focus(true);
+    write(&mut entry, &mut ctx, "ab cd");
+    assert_eq!(entry.text(), "ab cd");
+
+    // Words aren't revealed by the movements of the caret
+    press(&mut entry, &mut ctx, keys::LEFT, modifiers(true, false));
+    assert_eq!(entry.cursor(), 0);
+    press(&mut entry, &mut ctx, keys::RIGHT, modifiers(true, false));
+    assert_eq!(entry.cursor(), 5);
+}
+
+#[test]
+fn pointer() {
+    let mut font_cache = font_cache();
+    let advance = font_cache.advances(&label("a"), "a")[1];
+    let mut controller = DummyController::new(Edited(String::new()));
+    let mut ctx = SyncContext::new(&mut controller, &mut font_cache);
+    let mut entry: Entry<Edited> = label("hello world").into();
+    entry.sync(&mut ctx, Event::Frame);
+
+    click(&mut entry, &mut ctx, 2. * advance, 0);
+    assert!(entry.is_focused());
+    assert_eq!(entry.cursor(), 2);
+
+    // Dragging selects the text
+    let pointer = Pointer::MouseClick {
+        time: 1000,
+        button: MouseButton::Left,
+        pressed: true,
+    };
+    entry.sync(&mut ctx, Event::Pointer(2. * advance, 1., pointer));
+    entry.sync(&mut ctx, Event::Pointer(5. * advance, 1., Pointer::Hover));
+    assert_eq!(entry.selected_text(), "llo");
+    entry.sync(&mut ctx, Event::Pointer(5. * advance, 1., Pointer::Leave));
+
+    // A double click selects the word
+    click(&mut entry, &mut ctx, 7. * advance, 2000);
+    click(&mut entry, &mut ctx, 7. * advance, 2100);
+    assert_eq!(entry.selected_text(), "world");
+
+    // A click elsewhere removes the focus
+    let pointer = Pointer::MouseClick {
+        time: 3000,
+        button: MouseButton::Left,
+        pressed: true,
+    };
+    entry.sync(&mut ctx, Event::Pointer(0., 100., pointer));
+    assert!(!entry.is_focused());
+}
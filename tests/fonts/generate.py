@@ -0,0 +1,141 @@
+#!/usr/bin/env python3
+# Generates the small fonts used by the tests.
+#
+# Boxes.ttf maps U+E000 to a square and nothing else.
+# Faces.ttc holds two faces mapping 'a' to boxes of different widths.
+
+import struct
+from pathlib import Path
+
+UNITS_PER_EM = 1000
+ASCENDER = 800
+DESCENDER = -200
+
+
+def checksum(data):
+    data += b"\0" * (-len(data) % 4)
+    return sum(struct.unpack(">%dI" % (len(data) // 4), data)) & 0xFFFFFFFF
+
+
+def box(x_min, y_min, x_max, y_max):
+    points = [(x_min, y_min), (x_min, y_max), (x_max, y_max), (x_max, y_min)]
+    data = struct.pack(">hhhhh", 1, x_min, y_min, x_max, y_max)
+    data += struct.pack(">HH", len(points) - 1, 0)
+    data += bytes([0x01] * len(points))
+    previous = 0
+    for x, _ in points:
+        data += struct.pack(">h", x - previous)
+        previous = x
+    previous = 0
+    for _, y in points:
+        data += struct.pack(">h", y - previous)
+        previous = y
+    return data + b"\0" * (len(data) % 2)
+
+
+# glyphs is a list of (char, advance, box)
+def tables(glyphs):
+    outlines = [b""] + [box(*bounds) for _, _, bounds in glyphs]
+    advances = [500] + [advance for _, advance, _ in glyphs]
+    num_glyphs = len(outlines)
+
+    glyf = b"".join(outlines)
+    offsets = [0]
+    for outline in outlines:
+        offsets.append(offsets[-1] + len(outline))
+    loca = struct.pack(">%dH" % len(offsets), *[offset // 2 for offset in offsets])
+
+    x_min = min(bounds[0] for _, _, bounds in glyphs)
+    y_min = min(bounds[1] for _, _, bounds in glyphs)
+    x_max = max(bounds[2] for _, _, bounds in glyphs)
+    y_max = max(bounds[3] for _, _, bounds in glyphs)
+    head = struct.pack(
+        ">IIIIHHqqhhhhHHhhh",
+        0x00010000, 0x00010000, 0, 0x5F0F3CF5, 0b1011, UNITS_PER_EM, 0, 0,
+        x_min, y_min, x_max, y_max, 0, 8, 2, 0, 0,
+    )
+    hhea = struct.pack(
+        ">IhhhHhhhhhhhhhhhH",
+        0x00010000, ASCENDER, DESCENDER, 0, max(advances), 0, 0, x_max,
+        1, 0, 0, 0, 0, 0, 0, 0, num_glyphs,
+    )
+    maxp = struct.pack(">IH", 0x00005000, num_glyphs)
+    hmtx = b"".join(struct.pack(">Hh", advance, 0) for advance in advances)
+
+    # One segment per character and the final 0xFFFF segment
+    codes = [ord(c) for c, _, _ in glyphs] + [0xFFFF]
+    deltas = [(i + 1 - ord(c)) % 0x10000 for i, (c, _, _) in enumerate(glyphs)] + [1]
+    segments = len(codes)
+    subtable = struct.pack(">%dH" % segments, *codes) + struct.pack(">H", 0)
+    subtable += struct.pack(">%dH" % segments, *codes)
+    subtable += struct.pack(">%dH" % segments, *deltas)
+    subtable += struct.pack(">%dH" % segments, *[0] * segments)
+    power = 1 << (segments.bit_length() - 1)
+    subtable = (
+        struct.pack(
+            ">HHHHHHH", 4, 14 + len(subtable), 0, segments * 2,
+            power * 2, power.bit_length() - 1, (segments - power) * 2,
+        )
+        + subtable
+    )
+    cmap = struct.pack(">HHHHI", 0, 1, 3, 1, 12) + subtable
+
+    post = struct.pack(">IIhhIIIII", 0x00030000, 0, -100, 50, 0, 0, 0, 0, 0)
+
+    return {
+        b"cmap": cmap,
+        b"glyf": glyf,
+        b"head": head,
+        b"hhea": hhea,
+        b"hmtx": hmtx,
+        b"loca": loca,
+        b"maxp": maxp,
+        b"post": post,
+    }
+
+
+# Lays out the table directory of a font whose tables start at the offset
+def directory(tables, offset):
+    count = len(tables)
+    power = 1 << (count.bit_length() - 1)
+    header = struct.pack(
+        ">IHHHH", 0x00010000, count, power * 16, power.bit_length() - 1, (count - power) * 16
+    )
+    offset += len(header) + 16 * count
+    records = b""
+    data = b""
+    for tag in sorted(tables):
+        table = tables[tag]
+        records += struct.pack(">4sIII", tag, checksum(table), offset + len(data), len(table))
+        data += table + b"\0" * (-len(table) % 4)
+    return header + records + data
+
+
+def font(glyphs):
+    return directory(tables(glyphs), 0)
+
+
+def collection(faces):
+    header_size = 12 + 4 * len(faces)
+    fonts = []
+    offset = header_size
+    for glyphs in faces:
+        data = directory(tables(glyphs), offset)
+        fonts.append((offset, data))
+        offset += len(data)
+    header = struct.pack(">4sII", b"ttcf", 0x00010000, len(faces))
+    header += b"".join(struct.pack(">I", offset) for offset, _ in fonts)
+    return header + b"".join(data for _, data in fonts)
+
+
+if __name__ == "__main__":
+    here = Path(__file__).parent
+    (here / "Boxes.ttf").write_bytes(font([("\ue000", 1000, (100, 0, 900, 800))]))
+    (here / "Faces.ttc").write_bytes(
+        collection(
+            [
+                [("a", 700, (50, 0, 650, 600))],
+                [("a", 300, (50, 0, 250, 600))],
+            ]
+        )
+    )
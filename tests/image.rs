@@ -0,0 +1,76 @@
+use snui::context::SyncContext;
+use snui::controller::DummyController;
+use snui::font::FontCache;
+use snui::widgets::image::Scale;
+use snui::widgets::{AnimatedImage, Image};
+use snui::{Event, Geometry, Widget};
+
+// A 1x1 GIF with two frames of 100ms and the extensions in the middle
+fn gif(extensions: &[u8]) -> Vec<u8> {
+    let mut data = b"GIF89a".to_vec();
+    // The logical screen descriptor and a global color table of 2 colors
+    data.extend_from_slice(&[1, 0, 1, 0, 0x80, 0, 0]);
+    data.extend_from_slice(&[0, 0, 0, 255, 255, 255]);
+    data.extend_from_slice(extensions);
+    for _ in 0..2 {
+        // The graphic control extension, the image descriptor and the image data
+        data.extend_from_slice(&[0x21, 0xF9, 4, 0, 10, 0, 0, 0]);
+        data.extend_from_slice(&[0x2C, 0, 0, 0, 0, 1, 0, 1, 0, 0]);
+        data.extend_from_slice(&[2, 2, 0x44, 0x01, 0]);
+    }
+    data.push(0x3B);
+    data
+}
+
+// Plays the animation and returns how long it played in ms
+fn play(name: &str, data: &[u8]) -> u32 {
+    let path = std::env::temp_dir().join(name);
+    std::fs::write(&path, data).unwrap();
+    let mut image = AnimatedImage::new(&path).unwrap();
+    std::fs::remove_file(&path).unwrap();
+    assert_eq!(image.len(), 2);
+
+    let mut controller = DummyController::new(());
+    let mut font_cache = FontCache::new();
+    let mut ctx = SyncContext::new(&mut controller, &mut font_cache);
+    let mut time = 0;
+    while image.is_playing() && time < 10000 {
+        Widget::<()>::sync(&mut image, &mut ctx, Event::Callback(100));
+        time += 100;
+    }
+    time
+}
+
+#[test]
+fn gif_loop_count() {
+    // Without the NETSCAPE2.0 extension the GIF plays once
+    let mut comment = vec![0x21, 0xFE, 16];
+    comment.extend_from_slice(b"NETSCAPE2.0\x03\x01\x05\x00\x00");
+    comment.push(0);
+    assert_eq!(play("snui-comment.gif", &gif(&comment)), 200);
+
+    // It repeats twice after the first time
+    let mut netscape = vec![0x21, 0xFF, 11];
+    netscape.extend_from_slice(b"NETSCAPE2.0");
+    netscape.extend_from_slice(&[3, 1, 2, 0, 0]);
+    assert_eq!(play("snui-netscape.gif", &gif(&netscape)), 600);
+
+    // It repeats forever
+    netscape[16] = 0;
+    assert_eq!(play("snui-forever.gif", &gif(&netscape)), 10000);
+}
+
+#[test]
+fn image_scale() {
+    let mut image = Image::from_raw(vec![0; 4 * 20 * 10], 20, 10);
+    image.set_width(40.).unwrap();
+    image.set_height(40.).unwrap();
+    // Images are stretched by default
+    assert_eq!(image.scale(), (2., 4.));
+    image.set_scale(Scale::Stretch);
+    assert_eq!(image.scale(), (2., 4.));
+    image.set_scale(Scale::Contain);
+    assert_eq!(image.scale(), (2., 2.));
+    image.set_scale(Scale::Cover);
+    assert_eq!(image.scale(), (4., 4.));
+}
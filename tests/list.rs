@@ -0,0 +1,106 @@
+use snui::context::SyncContext;
+use snui::controller::{DummyController, IntoMessage};
+use snui::font::FontCache;
+use snui::scene::RenderNode;
+use snui::widgets::ListView;
+use snui::{Damage, Event, Geometry, MouseButton, Orientation, Pointer, Widget};
+use std::cell::{Cell, RefCell};
+use std::rc::Rc;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Selected(usize);
+
+impl IntoMessage<usize> for Selected {
+    fn into(&self, index: usize) -> Self {
+        Selected(index)
+    }
+}
+
+// Logs the item it shows when it's clicked
+struct Row {
+    item: usize,
+    clicks: Rc<RefCell<Vec<usize>>>,
+}
+
+impl Geometry for Row {
+    fn width(&self) -> f32 {
+        100.
+    }
+    fn height(&self) -> f32 {
+        10.
+    }
+}
+
+impl Widget<Selected> for Row {
+    fn create_node(&mut self, _: f32, _: f32) -> RenderNode {
+        RenderNode::None
+    }
+    fn sync<'d>(&'d mut self, _: &mut SyncContext<Selected>, event: Event<'d, Selected>) -> Damage {
+        if let Event::Pointer(x, y, Pointer::MouseClick { pressed: true, .. }) = event {
+            if self.contains(x, y) {
+                self.clicks.borrow_mut().push(self.item);
+            }
+        }
+        Damage::None
+    }
+}
+
+fn click(y: f32) -> Event<'static, Selected> {
+    Event::Pointer(
+        10.,
+        y,
+        Pointer::MouseClick {
+            time: 0,
+            button: MouseButton::Left,
+            pressed: true,
+        },
+    )
+}
+
+#[test]
+fn recycle_rows() {
+    let created = Rc::new(Cell::new(0));
+    let bound = Rc::new(Cell::new(0));
+    let clicks = Rc::new(RefCell::new(Vec::new()));
+    let factory = {
+        let (created, bound, clicks) = (created.clone(), bound.clone(), clicks.clone());
+        (
+            move |item: &usize| {
+                created.set(created.get() + 1);
+                Row {
+                    item: *item,
+                    clicks: clicks.clone(),
+                }
+            },
+            move |row: &mut Row, item: &usize| {
+                bound.set(bound.get() + 1);
+                row.item = *item;
+            },
+        )
+    };
+    let items: Vec<usize> = (0..1000).collect();
+    let mut list = ListView::new(items, factory, 100., 50., 10.).message(Selected(0));
+
+    let mut controller = DummyController::new(Selected(0));
+    let mut font_cache = FontCache::new();
+    let mut ctx = SyncContext::new(&mut controller, &mut font_cache);
+    list.create_node(0., 0.);
+    assert_eq!(created.get(), 5);
+
+    for _ in 0..100 {
+        let scroll = Pointer::Scroll {
+            orientation: Orientation::Vertical,
+            value: 25.,
+        };
+        list.sync(&mut ctx, Event::Pointer(10., 10., scroll));
+    }
+    assert_eq!(list.scroll(), 2500.);
+    // A row is only created when the view shows part of one more row
+    assert_eq!(created.get(), 6);
+    assert!(bound.get() > 0);
+
+    // The recycled rows show the items they were bound to
+    list.sync(&mut ctx, click(15.));
+    assert_eq!(list.selected(), Some(251));
+    assert_eq!(*clicks.borrow(), [251]);
+}
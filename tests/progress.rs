@@ -0,0 +1,13 @@
+use snui::widgets::ProgressBar;
+use snui::{Geometry, Orientation};
+
+#[test]
+fn progress_bar_orientation() {
+    let bar: ProgressBar<f32> = ProgressBar::new(200, 20).orientation(Orientation::Vertical);
+    assert_eq!((bar.width(), bar.height()), (200., 20.));
+
+    let mut bar: ProgressBar<f32> = ProgressBar::new(20, 200).orientation(Orientation::Horizontal);
+    assert_eq!((bar.width(), bar.height()), (20., 200.));
+    bar.set_width(100.).unwrap();
+    assert_eq!((bar.width(), bar.height()), (100., 200.));
+}
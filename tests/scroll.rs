@@ -0,0 +1,62 @@
+use snui::context::SyncContext;
+use snui::controller::DummyController;
+use snui::font::FontCache;
+use snui::scene::RenderNode;
+use snui::widgets::ScrollView;
+use snui::{Damage, Event, Geometry, Orientation, Pointer, Widget, WidgetUtil};
+use std::cell::RefCell;
+use std::rc::Rc;
+
+// Logs the events redrawing it and the creation of its node
+struct Log(Rc<RefCell<Vec<&'static str>>>);
+
+impl Geometry for Log {
+    fn width(&self) -> f32 {
+        100.
+    }
+    fn height(&self) -> f32 {
+        400.
+    }
+}
+
+impl Widget<()> for Log {
+    fn create_node(&mut self, _: f32, _: f32) -> RenderNode {
+        self.0.borrow_mut().push("node");
+        RenderNode::None
+    }
+    fn sync<'d>(&'d mut self, _: &mut SyncContext<()>, event: Event<'d, ()>) -> Damage {
+        match event {
+            Event::Frame => self.0.borrow_mut().push("frame"),
+            Event::Redraw => self.0.borrow_mut().push("redraw"),
+            _ => {}
+        }
+        Damage::None
+    }
+}
+
+#[test]
+fn scrolling_recreates_the_child_node() {
+    let log = Rc::new(RefCell::new(Vec::new()));
+    let mut view = ScrollView::new(Log(log.clone()).child(), 100., 100.);
+    let mut controller = DummyController::new(());
+    let mut font_cache = FontCache::new();
+    let mut ctx = SyncContext::new(&mut controller, &mut font_cache);
+    view.sync(&mut ctx, Event::Frame);
+    view.create_node(0., 0.);
+    assert_eq!(*log.borrow(), ["frame", "node"]);
+
+    // The node of the child is kept while it isn't damaged
+    view.sync(&mut ctx, Event::Pointer(10., 10., Pointer::Hover));
+    view.create_node(0., 0.);
+    assert_eq!(log.borrow().len(), 2);
+
+    // It's created again at its new position without sending it a Frame
+    let scroll = Pointer::Scroll {
+        orientation: Orientation::Vertical,
+        value: 50.,
+    };
+    view.sync(&mut ctx, Event::Pointer(10., 10., scroll));
+    view.create_node(0., 0.);
+    assert_eq!(view.position(), (0., 50.));
+    assert_eq!(*log.borrow(), ["frame", "node", "redraw", "node"]);
+}
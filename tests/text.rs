@@ -0,0 +1,409 @@
+use snui::context::{Backend, DrawContext, SyncContext};
+use snui::controller::DummyController;
+use snui::font::{self, Font, FontCache, FontSettings, GlyphImage, LayoutSettings};
+use snui::scene::{Instruction, RenderNode};
+use snui::widgets::text::Label;
+use snui::{Color, Damage, Event, Geometry, Pixmap, Widget};
+
+const MONO: &[u8] = include_bytes!("fonts/DejaVuSansMono.ttf");
+const BOXES: &[u8] = include_bytes!("fonts/Boxes.ttf");
+
+fn font_cache() -> FontCache {
+    let mut font_cache = FontCache::new();
+    font_cache.load_font_bytes("Mono", MONO).unwrap();
+    font_cache.load_font_bytes("Boxes", BOXES).unwrap();
+    font_cache
+}
+
+fn mono() -> Font {
+    Font::from_bytes(MONO, FontSettings::default()).unwrap()
+}
+
+#[test]
+fn advances() {
+    let mut font_cache = font_cache();
+    let label = Label::new("abc", 20.).font("Mono");
+    let advance = mono().metrics('a', 20.).advance_width.ceil();
+    assert_eq!(
+        font_cache.advances(&label, "abc"),
+        vec![0., advance, 2. * advance, 3. * advance]
+    );
+
+    let label = label.letter_spacing(2.);
+    assert_eq!(
+        font_cache.advances(&label, "ab"),
+        vec![0., advance + 2., 2. * (advance + 2.)]
+    );
+}
+
+#[test]
+fn measure() {
+    let mut font_cache = font_cache();
+    let label = Label::new("MMMM", 20.).font("Mono");
+    let advance = mono().metrics('M', 20.).advance_width;
+    let metrics = font_cache.measure(&label);
+    assert!((metrics.width - 4. * advance).abs() < advance);
+    assert!(metrics.height > 0. && metrics.height <= font_cache.line_height(&label));
+
+    let ascent = mono().horizontal_line_metrics(20.).unwrap().ascent.ceil();
+    assert_eq!(metrics.baseline, ascent);
+
+    let shorter = font_cache.measure_str(&label, "MM");
+    assert!(shorter.width < metrics.width);
+    assert_eq!(shorter.height, metrics.height);
+}
+
+#[test]
+fn line_height() {
+    let mut font_cache = font_cache();
+    let new_line_size = mono().horizontal_line_metrics(20.).unwrap().new_line_size;
+    let label = Label::new("a", 20.).font("Mono");
+    assert_eq!(font_cache.line_height(&label), new_line_size.ceil());
+
+    let label = label.line_height(1.5);
+    assert_eq!(
+        font_cache.line_height(&label),
+        (new_line_size.ceil() * 1.5).round()
+    );
+
+    // Without fonts the font size is used
+    let label = Label::new("a", 20.);
+    assert_eq!(font_cache.line_height(&label), 20.);
+}
+
+#[test]
+fn lines() {
+    let mut font_cache = font_cache();
+    let label = Label::new("ab\ncd", 20.).font("Mono");
+    let glyphs = font_cache.layout(&label);
+    let lines = font_cache.lines(&glyphs);
+    assert_eq!(lines.len(), 2);
+    assert_eq!(lines[0].1, lines[1].1);
+    assert!(lines[1].0 > lines[0].0);
+}
+
+#[test]
+fn wrap() {
+    let mut font_cache = font_cache();
+    let label = Label::new("aaaa aaaa aaaa", 20.)
+        .font("Mono")
+        .settings(LayoutSettings {
+            max_width: Some(80.),
+            ..LayoutSettings::default()
+        });
+    let glyphs = font_cache.layout(&label);
+    assert_eq!(font_cache.lines(&glyphs).len(), 3);
+    assert!(font_cache.measure(&label).width <= 80.);
+}
+
+#[test]
+fn decorations() {
+    let mut font_cache = font_cache();
+    // Boxes.ttf has an underline 100 units below the baseline and 50 units thick
+    let label = Label::new("\u{E000}", 20.).font("Boxes");
+    let (underline, _) = font_cache.decorations(&label);
+    assert_eq!(underline.position, -2.);
+    assert_eq!(underline.thickness, 1.);
+}
+
+#[test]
+fn load_font_file() {
+    let mut font_cache = FontCache::new();
+    let path = concat!(
+        env!("CARGO_MANIFEST_DIR"),
+        "/tests/fonts/DejaVuSansMono.ttf"
+    );
+    font_cache.load_font_file("Mono", path).unwrap();
+    assert!(font_cache.load_font_file("Missing", "missing.ttf").is_err());
+
+    let label = Label::new("abc", 20.).font("Mono");
+    assert_eq!(
+        font_cache.measure(&label),
+        self::font_cache().measure(&label)
+    );
+}
+
+#[test]
+fn label_size() {
+    font::global()
+        .borrow_mut()
+        .load_font_bytes("Mono", MONO)
+        .unwrap();
+    let label = Label::new("abc", 20.).font("Mono");
+    let metrics = font::global().measure(&label).unwrap();
+    assert_eq!(label.width(), metrics.width);
+    assert_eq!(label.height(), metrics.height);
+}
+
+#[test]
+fn fallback() {
+    let mut font_cache = font_cache();
+    let boxes = Font::from_bytes(BOXES, FontSettings::default()).unwrap();
+
+    // The fonts of the label are tried in order
+    let label = Label::new("a\u{E000}a", 20.).font("Mono").font("Boxes");
+    let glyphs = font_cache.layout(&label);
+    let hashes: Vec<usize> = glyphs.iter().map(|glyph| glyph.key.font_hash).collect();
+    assert_eq!(
+        hashes,
+        vec![mono().file_hash(), boxes.file_hash(), mono().file_hash()]
+    );
+    let label = Label::new("\u{E000}", 20.).font("Boxes").font("Mono");
+    assert_eq!(
+        font_cache.layout(&label)[0].key.font_hash,
+        boxes.file_hash()
+    );
+
+    // Characters no font covers are left out
+    let label = Label::new("a\u{10FFFD}a", 20.).font("Mono");
+    assert_eq!(font_cache.layout(&label).len(), 2);
+    assert_eq!(font_cache.advances(&label, "a\u{10FFFD}").len(), 3);
+}
+
+#[test]
+#[allow(deprecated)]
+fn bold_style() {
+    use snui::font::{FontProperty, FontStyle};
+    assert_eq!(
+        FontProperty::new("Mono").italic().style(FontStyle::Bold),
+        FontProperty::new("Mono").bold()
+    );
+}
+
+#[test]
+fn wrap_with_spacing() {
+    let mut font_cache = font_cache();
+    let settings = LayoutSettings {
+        max_width: Some(80.),
+        ..LayoutSettings::default()
+    };
+    for letter_spacing in [0., 1., 4., 10.] {
+        let label = Label::new("aaaa aaaa aaaa aaaaaaaaaa", 20.)
+            .font("Mono")
+            .settings(settings)
+            .letter_spacing(letter_spacing);
+        let glyphs = font_cache.layout(&label);
+        for (_, start, end) in font_cache.lines(&glyphs) {
+            assert!(start >= 0. && end <= 80., "{} overflows", letter_spacing);
+        }
+        // fontdue puts the glyphs on the lines they were wrapped to
+        let mut line = 0;
+        let mut lines: Vec<Vec<f32>> = vec![Vec::new()];
+        for glyph in &glyphs {
+            if glyph.parent == 'a' {
+                lines[line].push(glyph.y);
+            }
+            if glyph.user_data.line_break {
+                line += 1;
+                lines.push(Vec::new());
+            }
+        }
+        for (i, line) in lines.iter().enumerate() {
+            assert!(line.iter().all(|y| *y == line[0]));
+            assert!(i == 0 || lines[i - 1][0] < line[0]);
+        }
+        // The characters are all laid out in order
+        let text: String = glyphs
+            .iter()
+            .filter(|glyph| !glyph.user_data.wrapped)
+            .map(|glyph| glyph.parent)
+            .collect();
+        assert_eq!(text, label.get_text());
+    }
+
+    let label = Label::new("aaaa aaaa", 20.)
+        .font("Mono")
+        .settings(settings)
+        .letter_spacing(4.);
+    let glyphs = font_cache.layout(&label);
+    assert_eq!(font_cache.lines(&glyphs).len(), 2);
+    assert_eq!(
+        glyphs
+            .iter()
+            .filter(|glyph| glyph.user_data.wrapped)
+            .count(),
+        1
+    );
+}
+
+#[test]
+fn paragraph_spacing() {
+    let mut font_cache = font_cache();
+    let settings = LayoutSettings {
+        max_width: Some(80.),
+        ..LayoutSettings::default()
+    };
+    let label = Label::new("aaaa aaaa\naaaa", 20.)
+        .font("Mono")
+        .settings(settings);
+    let glyphs = font_cache.layout(&label);
+    let lines = font_cache.lines(&glyphs);
+    let spaced = label.paragraph_spacing(10.).line_height(2.);
+    let glyphs = font_cache.layout(&spaced);
+    let spaced_lines = font_cache.lines(&glyphs);
+    assert_eq!(lines.len(), 3);
+    assert_eq!(spaced_lines.len(), 3);
+
+    // The wrapped line only gets the line height
+    let line = lines[1].0 - lines[0].0;
+    assert_eq!(spaced_lines[0].0, lines[0].0);
+    assert_eq!(spaced_lines[1].0 - spaced_lines[0].0, 2. * line);
+    assert_eq!(spaced_lines[2].0 - spaced_lines[1].0, 2. * line + 10.);
+}
+
+// Measures a label which hasn't been synced
+struct Measure(f32);
+
+impl Geometry for Measure {
+    fn width(&self) -> f32 {
+        self.0
+    }
+    fn height(&self) -> f32 {
+        0.
+    }
+}
+
+impl Widget<()> for Measure {
+    fn create_node(&mut self, _: f32, _: f32) -> RenderNode {
+        RenderNode::None
+    }
+    fn sync<'d>(&'d mut self, _: &mut SyncContext<()>, _: Event<'d, ()>) -> Damage {
+        self.0 = Label::new("abc", 20.).font("Mono").width();
+        Damage::None
+    }
+}
+
+#[test]
+fn label_size_while_syncing() {
+    let global = font::global();
+    global.borrow_mut().load_font_bytes("Mono", MONO).unwrap();
+    let width = global
+        .measure(&Label::new("abc", 20.).font("Mono"))
+        .unwrap()
+        .width;
+
+    let mut controller = DummyController::new(());
+    let mut ctx = SyncContext::shared(&mut controller, global);
+    let mut measure = Measure(0.);
+    measure.sync(&mut ctx, Event::Frame);
+    assert_eq!(measure.width(), width);
+}
+
+#[test]
+fn label_size_with_own_cache() {
+    let mut font_cache = font_cache();
+    let label = Label::new("abc", 20.).font("Mono");
+    let width = font_cache.measure(&label).width;
+    {
+        let mut controller = DummyController::new(());
+        let mut ctx = SyncContext::new(&mut controller, &mut font_cache);
+        let mut measure = Measure(0.);
+        measure.sync(&mut ctx, Event::Frame);
+        assert_eq!(measure.width(), width);
+    }
+
+    // The caches are swapped back once the sync is done
+    assert!(font::global()
+        .borrow_mut()
+        .get_fonts(label.fonts())
+        .is_empty());
+    assert_eq!(font_cache.measure(&label).width, width);
+}
+
+#[test]
+fn label_size_while_drawing() {
+    let global = font::global();
+    let _drawing = global.borrow_mut();
+    assert_eq!(Label::new("abc", 20.).width(), 0.);
+}
+
+#[test]
+fn decorations_inside_geometry() {
+    font::global()
+        .borrow_mut()
+        .load_font_bytes("Mono", MONO)
+        .unwrap();
+    let mut font_cache = font_cache();
+    let plain = Label::new("ag", 20.).font("Mono");
+    let label = plain
+        .clone()
+        .outline(0xff_00_00_ff, 2.)
+        .shadow(0xff_ff_00_00, 3., 3., 2.)
+        .underline();
+    let margin = font_cache.margin(&label);
+    assert!(margin > 0.);
+    assert!(label.width() >= plain.width() + 2. * margin);
+    assert!(label.height() >= plain.height() + 2. * margin);
+
+    // Nothing is drawn outside of the label
+    let (x, y) = (20, 20);
+    let mut pixmap = Pixmap::new(100, 100).unwrap();
+    pixmap.fill(Color::WHITE);
+    let mut damage = Vec::new();
+    let mut ctx = DrawContext::new(
+        Backend::Pixmap(pixmap.as_mut()),
+        &mut font_cache,
+        &mut damage,
+    );
+    let node = RenderNode::Instruction(Instruction::new(x as f32, y as f32, label.clone()));
+    node.render(&mut ctx, None);
+    let (width, height) = (label.width() as usize, label.height() as usize);
+    let mut inked = 0;
+    for (i, pixel) in pixmap.pixels().iter().enumerate() {
+        if pixel.red() != 255 || pixel.green() != 255 || pixel.blue() != 255 {
+            let (px, py) = (i % 100, i / 100);
+            assert!(px >= x && px < x + width && py >= y && py < y + height);
+            inked += 1;
+        }
+    }
+    assert!(inked > 0);
+}
+
+#[test]
+fn collection_faces() {
+    const FACES: &[u8] = include_bytes!("fonts/Faces.ttc");
+    let mut font_cache = FontCache::new();
+    font_cache
+        .load_font_bytes_indexed("Wide", FACES, 0)
+        .unwrap();
+    font_cache
+        .load_font_bytes_indexed("Narrow", FACES, 1)
+        .unwrap();
+    let wide = Label::new("a", 20.).font("Wide");
+    let narrow = Label::new("a", 20.).font("Narrow");
+    assert!(font_cache.measure(&narrow).width < font_cache.measure(&wide).width);
+
+    // Each glyph is rendered with its own face
+    for label in [wide, narrow] {
+        for glyph in font_cache.layout(&label) {
+            match font_cache.render_glyph(&glyph, Color::BLACK) {
+                Some(GlyphImage::Mask(mask)) => {
+                    assert_eq!(mask.len(), glyph.width * glyph.height)
+                }
+                _ => panic!("{} isn't rendered", label.get_text()),
+            }
+        }
+    }
+}
+
+#[test]
+fn clear_glyph_cache() {
+    let mut font_cache = font_cache();
+    let label = Label::new("abc", 20.).font("Mono");
+    let render = |font_cache: &mut FontCache| {
+        for glyph in font_cache.layout(&label) {
+            font_cache.render_glyph(&glyph, Color::BLACK);
+        }
+        font_cache.size()
+    };
+    let size = render(&mut font_cache);
+    assert!(size > 0);
+
+    font_cache.clear();
+    assert_eq!(font_cache.size(), 0);
+
+    // The byte count and the LRU order start over
+    assert_eq!(render(&mut font_cache), size);
+    font_cache.set_limit(0);
+    assert_eq!(font_cache.size(), 0);
+}